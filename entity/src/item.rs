@@ -1,8 +1,11 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+use chrono::Utc;
 use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveValue, Set};
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "item")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -10,6 +13,34 @@ pub struct Model {
     pub name: String,
     pub product_id: i32,
     pub quantity: i32,
+    pub version: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    // Canonical form of the item's attribute values; unique per product
+    #[sea_orm(column_type = "Text", nullable)]
+    pub variant_key: Option<String>,
+    #[sea_orm(unique)]
+    pub sku: Option<String>,
+    // GTIN-8/12/13/14 as entered; unique in its zero-padded 14 digit form
+    pub barcode: Option<String>,
+    // Used where no price list has a price; set together with `currency`
+    #[sea_orm(column_type = "Decimal(Some((19, 4)))", nullable)]
+    pub base_price: Option<Decimal>,
+    #[sea_orm(column_type = "Char(Some(3))", nullable)]
+    pub currency: Option<String>,
+}
+
+impl Entity {
+    // Every query outside the trash goes through this, so soft deleted items
+    // stay hidden
+    pub fn find_live() -> Select<Entity> {
+        Self::find().filter(Column::DeletedAt.is_null())
+    }
+
+    pub fn find_live_by_id(id: i32) -> Select<Entity> {
+        Self::find_live().filter(Column::Id.eq(id))
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -22,6 +53,16 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     Product,
+    #[sea_orm(has_many = "super::item_attribute_value::Entity")]
+    ItemAttributeValue,
+    #[sea_orm(has_many = "super::price_entry::Entity")]
+    PriceEntry,
+    #[sea_orm(has_many = "super::reservation::Entity")]
+    Reservation,
+    #[sea_orm(has_many = "super::stock_level::Entity")]
+    StockLevel,
+    #[sea_orm(has_many = "super::stock_movement::Entity")]
+    StockMovement,
 }
 
 impl Related<super::product::Entity> for Entity {
@@ -30,4 +71,51 @@ impl Related<super::product::Entity> for Entity {
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+impl Related<super::item_attribute_value::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ItemAttributeValue.def()
+    }
+}
+
+impl Related<super::price_entry::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PriceEntry.def()
+    }
+}
+
+impl Related<super::reservation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Reservation.def()
+    }
+}
+
+impl Related<super::stock_level::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockLevel.def()
+    }
+}
+
+impl Related<super::stock_movement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockMovement.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // Every update of a loaded row bumps the version behind its ETag, and
+    // every save stamps the timestamps, so handlers never set them
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now: DateTimeWithTimeZone = Utc::now().into();
+        if insert {
+            self.created_at = Set(now);
+        } else if let ActiveValue::Unchanged(version) = self.version {
+            self.version = Set(version + 1);
+        }
+        self.updated_at = Set(now);
+        Ok(self)
+    }
+}
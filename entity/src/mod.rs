@@ -2,5 +2,17 @@
 
 pub mod prelude;
 
+pub mod attribute;
+pub mod audit_log;
+pub mod category;
 pub mod item;
+pub mod item_attribute_value;
+pub mod location;
+pub mod price_entry;
+pub mod price_list;
 pub mod product;
+pub mod product_category;
+pub mod reservation;
+pub mod stock_level;
+pub mod stock_movement;
+pub mod warehouse;
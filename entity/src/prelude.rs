@@ -1,4 +1,16 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+pub use super::attribute::Entity as Attribute;
+pub use super::audit_log::Entity as AuditLog;
+pub use super::category::Entity as Category;
 pub use super::item::Entity as Item;
+pub use super::item_attribute_value::Entity as ItemAttributeValue;
+pub use super::location::Entity as Location;
+pub use super::price_entry::Entity as PriceEntry;
+pub use super::price_list::Entity as PriceList;
 pub use super::product::Entity as Product;
+pub use super::product_category::Entity as ProductCategory;
+pub use super::reservation::Entity as Reservation;
+pub use super::stock_level::Entity as StockLevel;
+pub use super::stock_movement::Entity as StockMovement;
+pub use super::warehouse::Entity as Warehouse;
@@ -1,8 +1,11 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
+use chrono::Utc;
 use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveValue, Set};
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize)]
 #[sea_orm(table_name = "product")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -11,13 +14,26 @@ pub struct Model {
     pub uuid: Uuid,
     pub name: String,
     pub description: String,
-    pub created_at: DateTime,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    pub version: i32,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+}
+
+impl Entity {
+    // Every query outside the trash goes through this, so soft deleted products
+    // stay hidden
+    pub fn find_live() -> Select<Entity> {
+        Self::find().filter(Column::DeletedAt.is_null())
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::item::Entity")]
     Item,
+    #[sea_orm(has_many = "super::product_category::Entity")]
+    ProductCategory,
 }
 
 impl Related<super::item::Entity> for Entity {
@@ -26,4 +42,37 @@ impl Related<super::item::Entity> for Entity {
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+impl Related<super::product_category::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductCategory.def()
+    }
+}
+
+impl Related<super::category::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::product_category::Relation::Category.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::product_category::Relation::Product.def().rev())
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // Every update of a loaded row bumps the version behind its ETag, and
+    // every save stamps the timestamps, so handlers never set them
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        let now: DateTimeWithTimeZone = Utc::now().into();
+        if insert {
+            self.created_at = Set(now);
+        } else if let ActiveValue::Unchanged(version) = self.version {
+            self.version = Set(version + 1);
+        }
+        self.updated_at = Set(now);
+        Ok(self)
+    }
+}
@@ -1,12 +1,41 @@
 pub use sea_orm_migration::prelude::*;
 
+// Applied migrations are never edited, so the baseline keeps its original
+// identifier spelling
+#[allow(non_camel_case_types)]
 mod m20220101_000001_create_table;
+mod m20261018_000002_create_stock_movement_table;
+mod m20261018_000003_create_warehouse_tables;
+mod m20261018_000004_create_reservation_table;
+mod m20261018_000005_add_version_columns;
+mod m20261018_000006_add_deleted_at_columns;
+mod m20261018_000007_create_audit_log_table;
+mod m20261018_000008_add_timestamps;
+mod m20261018_000009_create_category_tables;
+mod m20261018_000010_create_variant_tables;
+mod m20261018_000011_add_item_codes;
+mod m20261018_000012_create_price_tables;
+mod m20261018_000013_convert_remaining_timestamps;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_table::Migration),
+            Box::new(m20261018_000002_create_stock_movement_table::Migration),
+            Box::new(m20261018_000003_create_warehouse_tables::Migration),
+            Box::new(m20261018_000004_create_reservation_table::Migration),
+            Box::new(m20261018_000005_add_version_columns::Migration),
+            Box::new(m20261018_000006_add_deleted_at_columns::Migration),
+            Box::new(m20261018_000007_create_audit_log_table::Migration),
+            Box::new(m20261018_000008_add_timestamps::Migration),
+            Box::new(m20261018_000009_create_category_tables::Migration),
+            Box::new(m20261018_000010_create_variant_tables::Migration),
+            Box::new(m20261018_000011_add_item_codes::Migration),
+            Box::new(m20261018_000012_create_price_tables::Migration),
+            Box::new(m20261018_000013_convert_remaining_timestamps::Migration),
+        ]
     }
 }
@@ -0,0 +1,85 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockMovement::Table)
+                    .if_not_exists()
+                    .col(pk_auto(StockMovement::Id))
+                    .col(integer(StockMovement::ItemId))
+                    .col(string_len(StockMovement::Kind, 16))
+                    .col(string(StockMovement::ReasonCode))
+                    .col(integer(StockMovement::Delta))
+                    .col(integer(StockMovement::QuantityAfter))
+                    .col(string(StockMovement::Actor))
+                    .col(string_null(StockMovement::Reference))
+                    .col(date_time(StockMovement::CreatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_movement_item")
+                            .from(StockMovement::Table, StockMovement::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_stock_movement_item_id")
+                    .table(StockMovement::Table)
+                    .col(StockMovement::ItemId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Record the stock that already exists as an opening balance, so the
+        // ledger for every item sums to its current quantity.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO stock_movement (item_id, kind, reason_code, delta, quantity_after, actor, created_at)
+                 SELECT id, 'adjustment', 'opening_balance', quantity, quantity, 'migration', now()
+                 FROM item WHERE quantity <> 0",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(StockMovement::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum StockMovement {
+    Table,
+    Id,
+    ItemId,
+    Kind,
+    ReasonCode,
+    Delta,
+    QuantityAfter,
+    Actor,
+    Reference,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
@@ -0,0 +1,200 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Warehouse::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Warehouse::Id))
+                    .col(string(Warehouse::Code).unique_key())
+                    .col(string(Warehouse::Name))
+                    .col(date_time(Warehouse::CreatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Location::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Location::Id))
+                    .col(integer(Location::WarehouseId))
+                    .col(string(Location::Code))
+                    .col(string(Location::Name))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_location_warehouse")
+                            .from(Location::Table, Location::WarehouseId)
+                            .to(Warehouse::Table, Warehouse::Id),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_location_warehouse_code")
+                            .col(Location::WarehouseId)
+                            .col(Location::Code)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(StockLevel::Table)
+                    .if_not_exists()
+                    .col(pk_auto(StockLevel::Id))
+                    .col(integer(StockLevel::ItemId))
+                    .col(integer(StockLevel::LocationId))
+                    .col(integer(StockLevel::Quantity))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_level_item")
+                            .from(StockLevel::Table, StockLevel::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_stock_level_location")
+                            .from(StockLevel::Table, StockLevel::LocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_stock_level_item_location")
+                            .col(StockLevel::ItemId)
+                            .col(StockLevel::LocationId)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Every existing unit moves into a default warehouse with a single bin
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "INSERT INTO warehouse (code, name, created_at) VALUES ('DEFAULT', 'Default warehouse', now())",
+        )
+        .await?;
+        db.execute_unprepared(
+            "INSERT INTO location (warehouse_id, code, name)
+             SELECT id, 'DEFAULT', 'Default location' FROM warehouse WHERE code = 'DEFAULT'",
+        )
+        .await?;
+        db.execute_unprepared(
+            "INSERT INTO stock_level (item_id, location_id, quantity)
+             SELECT item.id, location.id, item.quantity
+             FROM item, location JOIN warehouse ON warehouse.id = location.warehouse_id
+             WHERE warehouse.code = 'DEFAULT' AND location.code = 'DEFAULT' AND item.quantity <> 0",
+        )
+        .await?;
+
+        // Existing ledger entries all happened in the default location
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(StockMovement::Table)
+                    .add_column(integer_null(StockMovement::LocationId))
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared(
+            "UPDATE stock_movement SET location_id = (
+                 SELECT location.id FROM location JOIN warehouse ON warehouse.id = location.warehouse_id
+                 WHERE warehouse.code = 'DEFAULT' AND location.code = 'DEFAULT'
+             )",
+        )
+        .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(StockMovement::Table)
+                    .modify_column(ColumnDef::new(StockMovement::LocationId).integer().not_null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_stock_movement_location")
+                            .from_tbl(StockMovement::Table)
+                            .from_col(StockMovement::LocationId)
+                            .to_tbl(Location::Table)
+                            .to_col(Location::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(StockMovement::Table)
+                    .drop_foreign_key(Alias::new("fk_stock_movement_location"))
+                    .drop_column(StockMovement::LocationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(StockLevel::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Location::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Warehouse::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    Id,
+    Code,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+    WarehouseId,
+    Code,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum StockLevel {
+    Table,
+    Id,
+    ItemId,
+    LocationId,
+    Quantity,
+}
+
+#[derive(DeriveIden)]
+enum StockMovement {
+    Table,
+    LocationId,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
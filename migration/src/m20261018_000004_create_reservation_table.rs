@@ -0,0 +1,102 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Reservation::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Reservation::Id))
+                    .col(integer(Reservation::ItemId))
+                    .col(integer(Reservation::LocationId))
+                    .col(integer(Reservation::Quantity).check(Expr::col(Reservation::Quantity).gt(0)))
+                    .col(string_len(Reservation::Status, 16))
+                    .col(string(Reservation::Actor))
+                    .col(string_null(Reservation::Reference))
+                    .col(date_time(Reservation::ExpiresAt))
+                    .col(date_time(Reservation::CreatedAt))
+                    .col(date_time_null(Reservation::ResolvedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_reservation_item")
+                            .from(Reservation::Table, Reservation::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_reservation_location")
+                            .from(Reservation::Table, Reservation::LocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Serves both the availability sum per item and location and the
+        // sweeper's scan for expired holds
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_reservation_item_status")
+                    .table(Reservation::Table)
+                    .col(Reservation::ItemId)
+                    .col(Reservation::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_reservation_status_expires_at")
+                    .table(Reservation::Table)
+                    .col(Reservation::Status)
+                    .col(Reservation::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(Reservation::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Reservation {
+    Table,
+    Id,
+    ItemId,
+    LocationId,
+    Quantity,
+    Status,
+    Actor,
+    Reference,
+    ExpiresAt,
+    CreatedAt,
+    ResolvedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+}
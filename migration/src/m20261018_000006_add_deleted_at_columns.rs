@@ -0,0 +1,84 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .add_column(date_time_null(Product::DeletedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .add_column(date_time_null(Item::DeletedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Serve the trash listing and the purge job's scan for old rows
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_deleted_at")
+                    .table(Product::Table)
+                    .col(Product::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_item_deleted_at")
+                    .table(Item::Table)
+                    .col(Item::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .drop_column(Item::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .drop_column(Product::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    DeletedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    DeletedAt,
+}
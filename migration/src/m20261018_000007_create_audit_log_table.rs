@@ -0,0 +1,75 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // No foreign keys: entries must outlive the rows they describe
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AuditLog::Id))
+                    .col(string(AuditLog::Actor))
+                    .col(string_len(AuditLog::Action, 16))
+                    .col(string_len(AuditLog::EntityType, 32))
+                    .col(string(AuditLog::EntityKey))
+                    .col(json_binary_null(AuditLog::Before))
+                    .col(json_binary_null(AuditLog::After))
+                    .col(string_null(AuditLog::RequestId))
+                    .col(date_time(AuditLog::CreatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_log_entity")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::EntityType)
+                    .col(AuditLog::EntityKey)
+                    .col(AuditLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_log_actor_created_at")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::Actor)
+                    .col(AuditLog::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    Table,
+    Id,
+    Actor,
+    Action,
+    EntityType,
+    EntityKey,
+    Before,
+    After,
+    RequestId,
+    CreatedAt,
+}
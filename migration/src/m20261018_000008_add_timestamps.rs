@@ -0,0 +1,113 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Stored values were written as naive UTC, so convert them as UTC
+        // rather than in the session time zone
+        db.execute_unprepared(
+            "ALTER TABLE product ALTER COLUMN created_at TYPE timestamptz USING created_at AT TIME ZONE 'UTC'",
+        )
+        .await?;
+
+        // Columns are added nullable, backfilled, then made required
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .add_column(timestamp_with_time_zone_null(Product::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .add_column(timestamp_with_time_zone_null(Item::CreatedAt))
+                    .add_column(timestamp_with_time_zone_null(Item::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        // `update_product` used to overwrite created_at, so it is the best
+        // record of the last update. Items never had timestamps; their ledger
+        // entries bound when they were created and last touched.
+        db.execute_unprepared("UPDATE product SET updated_at = created_at")
+            .await?;
+        db.execute_unprepared(
+            "UPDATE item SET
+                 created_at = COALESCE(
+                     (SELECT MIN(created_at) AT TIME ZONE 'UTC' FROM stock_movement WHERE item_id = item.id),
+                     now()),
+                 updated_at = COALESCE(
+                     (SELECT MAX(created_at) AT TIME ZONE 'UTC' FROM stock_movement WHERE item_id = item.id),
+                     now())",
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .modify_column(ColumnDef::new(Product::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .modify_column(ColumnDef::new(Item::CreatedAt).timestamp_with_time_zone().not_null())
+                    .modify_column(ColumnDef::new(Item::UpdatedAt).timestamp_with_time_zone().not_null())
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .drop_column(Item::CreatedAt)
+                    .drop_column(Item::UpdatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Product::Table)
+                    .drop_column(Product::UpdatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE product ALTER COLUMN created_at TYPE timestamp USING created_at AT TIME ZONE 'UTC'",
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,119 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // A parent cannot be deleted while it still has children; the API
+        // asks for the subtree to be moved or emptied first
+        manager
+            .create_table(
+                Table::create()
+                    .table(Category::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Category::Id))
+                    .col(integer_null(Category::ParentId))
+                    .col(string(Category::Name))
+                    .col(timestamp_with_time_zone(Category::CreatedAt))
+                    .col(timestamp_with_time_zone(Category::UpdatedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_category_parent")
+                            .from(Category::Table, Category::ParentId)
+                            .to(Category::Table, Category::Id),
+                    )
+                    .check(Expr::col(Category::ParentId).ne(Expr::col(Category::Id)))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Sibling names are unique ignoring case. Roots have a NULL parent,
+        // which a plain unique index would treat as all distinct.
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX idx_category_parent_name ON category (COALESCE(parent_id, 0), lower(name))",
+        )
+        .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProductCategory::Table)
+                    .if_not_exists()
+                    .col(integer(ProductCategory::ProductId))
+                    .col(integer(ProductCategory::CategoryId))
+                    .primary_key(
+                        Index::create()
+                            .col(ProductCategory::ProductId)
+                            .col(ProductCategory::CategoryId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_product_category_product")
+                            .from(ProductCategory::Table, ProductCategory::ProductId)
+                            .to(Product::Table, Product::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_product_category_category")
+                            .from(ProductCategory::Table, ProductCategory::CategoryId)
+                            .to(Category::Table, Category::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The primary key covers lookups by product; this covers listing a
+        // category's products
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_product_category_category")
+                    .table(ProductCategory::Table)
+                    .col(ProductCategory::CategoryId)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(ProductCategory::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Category::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Category {
+    Table,
+    Id,
+    ParentId,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProductCategory {
+    Table,
+    ProductId,
+    CategoryId,
+}
+
+#[derive(DeriveIden)]
+enum Product {
+    Table,
+    Id,
+}
@@ -0,0 +1,136 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // `allowed_values` is a JSON array of normalized values, or NULL to
+        // accept anything of the attribute's type
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attribute::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Attribute::Id))
+                    .col(string(Attribute::Name))
+                    .col(string_len(Attribute::DataType, 16))
+                    .col(json_binary_null(Attribute::AllowedValues))
+                    .col(timestamp_with_time_zone(Attribute::CreatedAt))
+                    .col(timestamp_with_time_zone(Attribute::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared("CREATE UNIQUE INDEX idx_attribute_name ON attribute (lower(name))")
+            .await?;
+
+        // Values are stored in their normalized text form whatever the type
+        manager
+            .create_table(
+                Table::create()
+                    .table(ItemAttributeValue::Table)
+                    .if_not_exists()
+                    .col(integer(ItemAttributeValue::ItemId))
+                    .col(integer(ItemAttributeValue::AttributeId))
+                    .col(string(ItemAttributeValue::Value))
+                    .primary_key(
+                        Index::create()
+                            .col(ItemAttributeValue::ItemId)
+                            .col(ItemAttributeValue::AttributeId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_item_attribute_value_item")
+                            .from(ItemAttributeValue::Table, ItemAttributeValue::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_item_attribute_value_attribute")
+                            .from(ItemAttributeValue::Table, ItemAttributeValue::AttributeId)
+                            .to(Attribute::Table, Attribute::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_item_attribute_value_attribute")
+                    .table(ItemAttributeValue::Table)
+                    .col(ItemAttributeValue::AttributeId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // An item's full set of attribute values in one canonical string, so
+        // the database can hold each combination to one live item per product
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .add_column(text_null(Item::VariantKey))
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX idx_item_product_variant_key ON item (product_id, variant_key)
+             WHERE deleted_at IS NULL AND variant_key IS NOT NULL",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .drop_column(Item::VariantKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ItemAttributeValue::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Attribute::Table).to_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Attribute {
+    Table,
+    Id,
+    Name,
+    DataType,
+    AllowedValues,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ItemAttributeValue {
+    Table,
+    ItemId,
+    AttributeId,
+    Value,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+    VariantKey,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .add_column(string_len_null(Item::Sku, 64))
+                    .add_column(string_len_null(Item::Barcode, 14))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing items get the SKU the default pattern would have given them
+        let db = manager.get_connection();
+        db.execute_unprepared("UPDATE item SET sku = 'ITEM-' || lpad(id::text, 6, '0')")
+            .await?;
+
+        // Trashed items keep their codes, so they stay unique until purged.
+        // Barcodes compare in their 14 digit form, where an EAN-13 and the
+        // same code with a leading zero are equal.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_item_sku")
+                    .table(Item::Table)
+                    .col(Item::Sku)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared("CREATE UNIQUE INDEX idx_item_barcode ON item (lpad(barcode, 14, '0'))")
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .drop_column(Item::Sku)
+                    .drop_column(Item::Barcode)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Sku,
+    Barcode,
+}
@@ -0,0 +1,157 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        // An item's base price is the fallback when no price list has one;
+        // the price and its currency are set and cleared together
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .add_column(decimal_len_null(Item::BasePrice, 19, 4))
+                    .add_column(char_len_null(Item::Currency, 3))
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "ALTER TABLE item ADD CONSTRAINT chk_item_base_price
+             CHECK ((base_price IS NULL) = (currency IS NULL) AND base_price >= 0)",
+        )
+        .await?;
+
+        // Every price in a list is in the list's currency
+        manager
+            .create_table(
+                Table::create()
+                    .table(PriceList::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PriceList::Id))
+                    .col(string(PriceList::Name))
+                    .col(char_len(PriceList::Currency, 3))
+                    .col(timestamp_with_time_zone(PriceList::CreatedAt))
+                    .col(timestamp_with_time_zone(PriceList::UpdatedAt))
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared("CREATE UNIQUE INDEX idx_price_list_name ON price_list (lower(name))")
+            .await?;
+
+        // A price holds from `valid_from` up to but not including `valid_to`,
+        // or indefinitely without one. Entries are kept after they lapse, so
+        // they double as the price history.
+        manager
+            .create_table(
+                Table::create()
+                    .table(PriceEntry::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PriceEntry::Id))
+                    .col(integer(PriceEntry::PriceListId))
+                    .col(integer(PriceEntry::ItemId))
+                    .col(decimal_len(PriceEntry::Price, 19, 4).check(Expr::col(PriceEntry::Price).gte(0)))
+                    .col(timestamp_with_time_zone(PriceEntry::ValidFrom))
+                    .col(timestamp_with_time_zone_null(PriceEntry::ValidTo))
+                    .col(string(PriceEntry::CreatedBy))
+                    .col(timestamp_with_time_zone(PriceEntry::CreatedAt))
+                    .check(Expr::col(PriceEntry::ValidTo).gt(Expr::col(PriceEntry::ValidFrom)))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_price_entry_price_list")
+                            .from(PriceEntry::Table, PriceEntry::PriceListId)
+                            .to(PriceList::Table, PriceList::Id),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_price_entry_item")
+                            .from(PriceEntry::Table, PriceEntry::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_price_entry_item_list_from")
+                    .table(PriceEntry::Table)
+                    .col(PriceEntry::ItemId)
+                    .col(PriceEntry::PriceListId)
+                    .col(PriceEntry::ValidFrom)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_price_entry_price_list")
+                    .table(PriceEntry::Table)
+                    .col(PriceEntry::PriceListId)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+
+        manager
+            .drop_table(Table::drop().table(PriceEntry::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(PriceList::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .drop_column(Item::BasePrice)
+                    .drop_column(Item::Currency)
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PriceList {
+    Table,
+    Id,
+    Name,
+    Currency,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PriceEntry {
+    Table,
+    Id,
+    PriceListId,
+    ItemId,
+    Price,
+    ValidFrom,
+    ValidTo,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+    BasePrice,
+    Currency,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// Columns still stored as naive timestamps. Every value was written as naive
+// UTC, so it is converted as UTC rather than in the session time zone.
+const COLUMNS: &[(&str, &str)] = &[
+    ("product", "deleted_at"),
+    ("item", "deleted_at"),
+    ("stock_movement", "created_at"),
+    ("warehouse", "created_at"),
+    ("reservation", "expires_at"),
+    ("reservation", "created_at"),
+    ("reservation", "resolved_at"),
+    ("audit_log", "created_at"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (table, column) in COLUMNS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ALTER COLUMN {column} TYPE timestamptz USING {column} AT TIME ZONE 'UTC'"
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        for (table, column) in COLUMNS {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ALTER COLUMN {column} TYPE timestamp USING {column} AT TIME ZONE 'UTC'"
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::Utc;
+use entity::audit_log::{self, AuditAction, AuditEntityType};
+use sea_orm::{ActiveModelTrait, ConnectionTrait, Set};
+use serde::Serialize;
+
+use crate::error::AppResult;
+use crate::telemetry;
+
+// One change to an audited entity. `before` is empty for creations and
+// `after` for deletions.
+pub struct AuditEntry<'a, T> {
+    pub actor: &'a str,
+    pub action: AuditAction,
+    pub entity_type: AuditEntityType,
+    pub entity_key: String,
+    pub before: Option<&'a T>,
+    pub after: Option<&'a T>,
+}
+
+// Appends the entry using the mutation's own transaction, so the log holds
+// exactly the changes that were committed. The request ID links it to the
+// access logs.
+pub async fn record<C, T>(conn: &C, entry: AuditEntry<'_, T>) -> AppResult<()>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    let snapshot = |row: &T| serde_json::to_value(row).expect("entity rows serialize to JSON");
+
+    audit_log::ActiveModel {
+        actor: Set(entry.actor.to_string()),
+        action: Set(entry.action),
+        entity_type: Set(entry.entity_type),
+        entity_key: Set(entry.entity_key),
+        before: Set(entry.before.map(snapshot)),
+        after: Set(entry.after.map(snapshot)),
+        request_id: Set(telemetry::current_request_id()),
+        created_at: Set(Utc::now().fixed_offset()),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use std::{fs, sync::Arc};
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+use crate::config::{JwtAlgorithm, JwtSettings};
+use crate::error::AppError;
+
+// Roles are ordered: a higher role can do everything a lower one can.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Viewer,
+    Editor,
+    Admin,
+}
+
+impl Role {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "viewer" => Some(Role::Viewer),
+            "editor" => Some(Role::Editor),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+// `exp` is required and checked by `Validation`, so it is not kept here
+#[derive(Clone, Debug, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl Claims {
+    pub fn highest_role(&self) -> Option<Role> {
+        self.roles.iter().filter_map(|r| Role::parse(r)).max()
+    }
+}
+
+// HS256 keys shorter than the hash output are easy to brute force
+pub const MIN_SECRET_BYTES: usize = 32;
+
+// Key material and validation rules for incoming bearer tokens
+pub struct AuthConfig {
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl AuthConfig {
+    pub fn from_settings(settings: &JwtSettings) -> Result<Self, String> {
+        let (algorithm, decoding_key) = match settings.algorithm {
+            JwtAlgorithm::HS256 => {
+                let secret = settings
+                    .secret
+                    .as_deref()
+                    .ok_or_else(|| "a secret must be set for HS256 (JWT_SECRET)".to_string())?;
+                if secret.len() < MIN_SECRET_BYTES {
+                    return Err(format!(
+                        "the HS256 secret must be at least {} bytes long",
+                        MIN_SECRET_BYTES
+                    ));
+                }
+                (Algorithm::HS256, DecodingKey::from_secret(secret.as_bytes()))
+            }
+            JwtAlgorithm::RS256 => {
+                let pem = match (&settings.public_key, &settings.public_key_file) {
+                    (Some(pem), _) => pem.clone(),
+                    (None, Some(path)) => fs::read_to_string(path)
+                        .map_err(|e| format!("Failed to read {}: {}", path, e))?,
+                    (None, None) => {
+                        return Err("a public key must be set for RS256 \
+                                    (JWT_PUBLIC_KEY or JWT_PUBLIC_KEY_FILE)"
+                            .to_string())
+                    }
+                };
+                let key = DecodingKey::from_rsa_pem(pem.as_bytes())
+                    .map_err(|e| format!("Invalid RS256 public key: {}", e))?;
+                (Algorithm::RS256, key)
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &settings.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &settings.audience {
+            validation.set_audience(&[audience]);
+        }
+
+        Ok(AuthConfig {
+            decoding_key,
+            validation,
+        })
+    }
+
+    fn verify(&self, token: &str) -> Result<Claims, AppError> {
+        decode::<Claims>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| AppError::Unauthorized(format!("Invalid bearer token: {}", e)))
+    }
+}
+
+// Validates the bearer token when one is sent and stores its claims in the
+// request extensions. Requests without a token pass through unauthenticated;
+// routes that need an identity are guarded by `require_role`.
+pub async fn authenticate(
+    State(auth): State<Arc<AuthConfig>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(value) = req.headers().get(header::AUTHORIZATION) {
+        let token = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                AppError::Unauthorized("Authorization header must be a Bearer token".to_string())
+            })?;
+        let claims = auth.verify(token.trim())?;
+        req.extensions_mut().insert(claims);
+    }
+
+    Ok(next.run(req).await)
+}
+
+// Route layer that rejects requests whose token lacks the given role
+pub async fn require_role(
+    State(role): State<Role>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .ok_or_else(|| AppError::Unauthorized("A bearer token is required".to_string()))?;
+
+    if claims.highest_role().is_none_or(|held| held < role) {
+        return Err(AppError::Forbidden(format!(
+            "This operation requires the '{}' role",
+            role.as_str()
+        )));
+    }
+
+    Ok(next.run(req).await)
+}
+
+// Lets handlers take the authenticated caller as an argument
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Claims {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Claims>()
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized("A bearer token is required".to_string()))
+    }
+}
@@ -0,0 +1,564 @@
+use std::{fmt, fs, net::SocketAddr, path::{Path, PathBuf}, time::Duration};
+
+use axum::http::HeaderValue;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use dotenv::dotenv;
+use serde::Deserialize;
+
+use crate::auth::AuthConfig;
+use crate::sku;
+
+// Read when it exists and no other file is named with --config / CONFIG_FILE
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+// A century; longer retentions would reach before the earliest timestamp
+pub const MAX_RETENTION_DAYS: u64 = 36500;
+
+// Service settings. Each layer overrides the one before it: built-in
+// defaults, then the TOML file, then environment variables, then CLI flags.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerSettings,
+    pub database: DatabaseSettings,
+    pub cors: CorsSettings,
+    pub log: LogSettings,
+    pub jwt: JwtSettings,
+    pub reservations: ReservationSettings,
+    pub stock: StockSettings,
+    pub trash: TrashSettings,
+    pub import: ImportSettings,
+    pub sku: SkuSettings,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSettings {
+    pub listen_addr: SocketAddr,
+    // Requests still running after this long are answered with 408
+    pub request_timeout_secs: u64,
+    // After SIGINT/SIGTERM, in-flight requests get this long to finish
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        ServerSettings {
+            listen_addr: SocketAddr::from(([0, 0, 0, 0], 3001)),
+            request_timeout_secs: 30,
+            shutdown_timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseSettings {
+    pub url: Option<String>,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout_secs: u64,
+    pub acquire_timeout_secs: u64,
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        DatabaseSettings {
+            url: None,
+            max_connections: 10,
+            min_connections: 1,
+            connect_timeout_secs: 8,
+            acquire_timeout_secs: 8,
+            idle_timeout_secs: 600,
+        }
+    }
+}
+
+// Lifetimes of stock reservations and how often expired ones are swept
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReservationSettings {
+    // Used when a reservation is created without `ttl_secs`
+    pub default_ttl_secs: u64,
+    pub max_ttl_secs: u64,
+    pub sweep_interval_secs: u64,
+}
+
+impl Default for ReservationSettings {
+    fn default() -> Self {
+        ReservationSettings {
+            default_ttl_secs: 900,
+            max_ttl_secs: 3600,
+            sweep_interval_secs: 30,
+        }
+    }
+}
+
+// Soft deleted products and items are purged once they have been in the
+// trash for longer than the retention window
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrashSettings {
+    pub retention_days: u64,
+    pub purge_interval_secs: u64,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        TrashSettings {
+            retention_days: 30,
+            purge_interval_secs: 3600,
+        }
+    }
+}
+
+// Bulk imports commit this many rows per transaction; 0 commits the whole
+// file in one. Rows of one product always share a transaction.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImportSettings {
+    pub chunk_size: usize,
+}
+
+// Items created without a SKU get one from `pattern`; see `crate::sku` for
+// the placeholders
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SkuSettings {
+    pub pattern: String,
+}
+
+impl Default for SkuSettings {
+    fn default() -> Self {
+        SkuSettings {
+            pattern: "ITEM-{id:06}".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StockSettings {
+    pub negative_policy: NegativeStockPolicy,
+}
+
+// What a relative adjustment does when it would take more than a location
+// has free: `reject` fails it, `allow` books it anyway, `clamp` takes only
+// what is free. Units held by reservations are never adjusted away, so
+// `allow` still fails where stock is held; see `stock::adjustable_delta`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum NegativeStockPolicy {
+    #[default]
+    Reject,
+    Allow,
+    Clamp,
+}
+
+// An empty origin list disables cross-origin requests; "*" allows any origin
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsSettings {
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogSettings {
+    pub level: LogLevel,
+    pub format: LogFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+// `pretty` is meant for terminals, `json` for log shippers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+// HS256 needs `secret` of at least 32 bytes; RS256 needs `public_key` or
+// `public_key_file` (PEM). `issuer` and `audience` are enforced when set.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtSettings {
+    pub algorithm: JwtAlgorithm,
+    pub secret: Option<String>,
+    pub public_key: Option<String>,
+    pub public_key_file: Option<String>,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+pub enum JwtAlgorithm {
+    #[default]
+    #[value(name = "HS256")]
+    HS256,
+    #[value(name = "RS256")]
+    RS256,
+}
+
+// Command line flags. Every flag can also be given as the environment
+// variable named next to it; a flag wins over its variable.
+#[derive(Debug, Default, Parser)]
+#[command(name = "product_service", about = "Product and item inventory service")]
+pub struct Cli {
+    /// TOML configuration file
+    #[arg(long = "config", env = "CONFIG_FILE")]
+    pub config_file: Option<String>,
+
+    /// Address the HTTP server listens on
+    #[arg(long, env = "LISTEN_ADDR")]
+    pub listen_addr: Option<SocketAddr>,
+
+    /// Seconds before an unfinished request is answered with 408
+    #[arg(long, env = "REQUEST_TIMEOUT_SECS")]
+    pub request_timeout_secs: Option<u64>,
+
+    /// Seconds in-flight requests get to finish after SIGINT/SIGTERM
+    #[arg(long, env = "SHUTDOWN_TIMEOUT_SECS")]
+    pub shutdown_timeout_secs: Option<u64>,
+
+    /// Postgres connection URL
+    #[arg(long, env = "DATABASE_URL", hide_env_values = true)]
+    pub database_url: Option<String>,
+
+    /// Upper bound of the database connection pool
+    #[arg(long, env = "DB_MAX_CONNECTIONS")]
+    pub db_max_connections: Option<u32>,
+
+    /// Connections the pool keeps open when idle
+    #[arg(long, env = "DB_MIN_CONNECTIONS")]
+    pub db_min_connections: Option<u32>,
+
+    /// Seconds to wait when opening a database connection
+    #[arg(long, env = "DB_CONNECT_TIMEOUT_SECS")]
+    pub db_connect_timeout_secs: Option<u64>,
+
+    /// Seconds to wait for a free pooled connection
+    #[arg(long, env = "DB_ACQUIRE_TIMEOUT_SECS")]
+    pub db_acquire_timeout_secs: Option<u64>,
+
+    /// Seconds before an idle pooled connection is closed
+    #[arg(long, env = "DB_IDLE_TIMEOUT_SECS")]
+    pub db_idle_timeout_secs: Option<u64>,
+
+    /// Comma separated origins allowed to make cross-origin requests
+    #[arg(long, env = "CORS_ALLOWED_ORIGINS", value_delimiter = ',')]
+    pub cors_allowed_origins: Option<Vec<String>>,
+
+    /// Minimum level of log output
+    #[arg(long, env = "LOG_LEVEL", ignore_case = true)]
+    pub log_level: Option<LogLevel>,
+
+    /// Log output format
+    #[arg(long, env = "LOG_FORMAT", ignore_case = true)]
+    pub log_format: Option<LogFormat>,
+
+    /// Seconds a reservation holds stock when no TTL is requested
+    #[arg(long, env = "RESERVATION_DEFAULT_TTL_SECS")]
+    pub reservation_default_ttl_secs: Option<u64>,
+
+    /// Longest TTL a reservation may request, in seconds
+    #[arg(long, env = "RESERVATION_MAX_TTL_SECS")]
+    pub reservation_max_ttl_secs: Option<u64>,
+
+    /// Seconds between sweeps that expire lapsed reservations
+    #[arg(long, env = "RESERVATION_SWEEP_INTERVAL_SECS")]
+    pub reservation_sweep_interval_secs: Option<u64>,
+
+    /// Days a deleted product or item stays restorable before it is purged
+    #[arg(long, env = "TRASH_RETENTION_DAYS")]
+    pub trash_retention_days: Option<u64>,
+
+    /// Seconds between runs of the trash purge job
+    #[arg(long, env = "TRASH_PURGE_INTERVAL_SECS")]
+    pub trash_purge_interval_secs: Option<u64>,
+
+    /// How quantity adjustments that would go below zero are handled
+    #[arg(long, env = "NEGATIVE_STOCK_POLICY")]
+    pub negative_stock_policy: Option<NegativeStockPolicy>,
+
+    /// Rows committed per transaction by bulk imports; 0 for one transaction
+    #[arg(long, env = "IMPORT_CHUNK_SIZE")]
+    pub import_chunk_size: Option<usize>,
+
+    /// Pattern for generated SKUs, e.g. ITEM-{id:06}
+    #[arg(long, env = "SKU_PATTERN")]
+    pub sku_pattern: Option<String>,
+
+    /// Algorithm of incoming bearer tokens
+    #[arg(long, env = "JWT_ALGORITHM")]
+    pub jwt_algorithm: Option<JwtAlgorithm>,
+
+    /// Shared secret for HS256 tokens
+    #[arg(long, env = "JWT_SECRET", hide_env_values = true)]
+    pub jwt_secret: Option<String>,
+
+    /// PEM public key for RS256 tokens
+    #[arg(long, env = "JWT_PUBLIC_KEY", hide_env_values = true)]
+    pub jwt_public_key: Option<String>,
+
+    /// File holding the PEM public key for RS256 tokens
+    #[arg(long, env = "JWT_PUBLIC_KEY_FILE")]
+    pub jwt_public_key_file: Option<String>,
+
+    /// Required `iss` claim
+    #[arg(long, env = "JWT_ISSUER")]
+    pub jwt_issuer: Option<String>,
+
+    /// Required `aud` claim
+    #[arg(long, env = "JWT_AUDIENCE")]
+    pub jwt_audience: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Without a subcommand the service starts serving
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Import products and items from a CSV or NDJSON file, then exit
+    Import(ImportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// CSV or NDJSON file to import
+    pub file: PathBuf,
+
+    /// File format; guessed from the extension when omitted
+    #[arg(long)]
+    pub format: Option<ImportFormat>,
+
+    /// Validate and report without committing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Rows committed per transaction; overrides the configured value
+    #[arg(long)]
+    pub chunk_size: Option<usize>,
+
+    /// Name recorded as the actor in the audit log
+    #[arg(long, default_value = "import-cli")]
+    pub actor: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl Cli {
+    fn apply(self, config: &mut Config) {
+        fn set<T>(target: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+
+        set(&mut config.server.listen_addr, self.listen_addr);
+        set(&mut config.server.request_timeout_secs, self.request_timeout_secs);
+        set(&mut config.server.shutdown_timeout_secs, self.shutdown_timeout_secs);
+        set(&mut config.database.url, self.database_url.map(Some));
+        set(&mut config.database.max_connections, self.db_max_connections);
+        set(&mut config.database.min_connections, self.db_min_connections);
+        set(&mut config.database.connect_timeout_secs, self.db_connect_timeout_secs);
+        set(&mut config.database.acquire_timeout_secs, self.db_acquire_timeout_secs);
+        set(&mut config.database.idle_timeout_secs, self.db_idle_timeout_secs);
+        set(&mut config.cors.allowed_origins, self.cors_allowed_origins);
+        set(&mut config.log.level, self.log_level);
+        set(&mut config.log.format, self.log_format);
+        set(&mut config.reservations.default_ttl_secs, self.reservation_default_ttl_secs);
+        set(&mut config.reservations.max_ttl_secs, self.reservation_max_ttl_secs);
+        set(&mut config.reservations.sweep_interval_secs, self.reservation_sweep_interval_secs);
+        set(&mut config.trash.retention_days, self.trash_retention_days);
+        set(&mut config.trash.purge_interval_secs, self.trash_purge_interval_secs);
+        set(&mut config.stock.negative_policy, self.negative_stock_policy);
+        set(&mut config.import.chunk_size, self.import_chunk_size);
+        set(&mut config.sku.pattern, self.sku_pattern);
+        set(&mut config.jwt.algorithm, self.jwt_algorithm);
+        set(&mut config.jwt.secret, self.jwt_secret.map(Some));
+        set(&mut config.jwt.public_key, self.jwt_public_key.map(Some));
+        set(&mut config.jwt.public_key_file, self.jwt_public_key_file.map(Some));
+        set(&mut config.jwt.issuer, self.jwt_issuer.map(Some));
+        set(&mut config.jwt.audience, self.jwt_audience.map(Some));
+    }
+}
+
+// Every problem found while loading, so they can all be fixed in one go
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration:")?;
+        for problem in &self.0 {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    // Loads `.env`, parses the process arguments and environment, and
+    // validates the result. Malformed flags exit with clap's usage message.
+    // The subcommand, if any, is returned alongside.
+    pub fn load() -> Result<(Self, Option<Command>), ConfigError> {
+        dotenv().ok();
+        let mut cli = Cli::parse();
+        let command = cli.command.take();
+        Ok((Self::from_cli(cli)?, command))
+    }
+
+    pub fn from_cli(cli: Cli) -> Result<Self, ConfigError> {
+        let mut config = match cli.config_file.as_deref() {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_file(DEFAULT_CONFIG_FILE)?,
+            None => Config::default(),
+        };
+        cli.apply(&mut config);
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| ConfigError(vec![format!("cannot read {}: {}", path, e)]))?;
+        toml::from_str(&contents)
+            .map_err(|e| ConfigError(vec![format!("{}: {}", path, e.to_string().trim_end())]))
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if self.server.request_timeout_secs == 0 {
+            problems.push("server.request_timeout_secs must be greater than 0".to_string());
+        }
+
+        let database = &self.database;
+        match database.url.as_deref() {
+            None => problems.push(
+                "database.url must be set (DATABASE_URL, --database-url or [database] url)"
+                    .to_string(),
+            ),
+            Some(url) if !url.starts_with("postgres://") && !url.starts_with("postgresql://") => {
+                problems.push("database.url must be a postgres:// URL".to_string())
+            }
+            Some(_) => {}
+        }
+        if database.max_connections == 0 {
+            problems.push("database.max_connections must be greater than 0".to_string());
+        }
+        if database.min_connections > database.max_connections {
+            problems.push(format!(
+                "database.min_connections ({}) must not exceed database.max_connections ({})",
+                database.min_connections, database.max_connections
+            ));
+        }
+        for (name, secs) in [
+            ("connect_timeout_secs", database.connect_timeout_secs),
+            ("acquire_timeout_secs", database.acquire_timeout_secs),
+        ] {
+            if secs == 0 {
+                problems.push(format!("database.{} must be greater than 0", name));
+            }
+        }
+
+        let origins = &self.cors.allowed_origins;
+        if origins.iter().any(|o| o == "*") {
+            if origins.len() > 1 {
+                problems.push("cors.allowed_origins cannot mix \"*\" with other origins".to_string());
+            }
+        } else {
+            for origin in origins {
+                let is_http = origin.starts_with("http://") || origin.starts_with("https://");
+                if !is_http || origin.ends_with('/') || HeaderValue::from_str(origin).is_err() {
+                    problems.push(format!(
+                        "cors.allowed_origins: '{}' is not an origin like https://example.com",
+                        origin
+                    ));
+                }
+            }
+        }
+
+        let reservations = &self.reservations;
+        for (name, secs) in [
+            ("default_ttl_secs", reservations.default_ttl_secs),
+            ("max_ttl_secs", reservations.max_ttl_secs),
+            ("sweep_interval_secs", reservations.sweep_interval_secs),
+        ] {
+            if secs == 0 {
+                problems.push(format!("reservations.{} must be greater than 0", name));
+            }
+        }
+        if reservations.default_ttl_secs > reservations.max_ttl_secs {
+            problems.push(format!(
+                "reservations.default_ttl_secs ({}) must not exceed reservations.max_ttl_secs ({})",
+                reservations.default_ttl_secs, reservations.max_ttl_secs
+            ));
+        }
+
+        if self.trash.retention_days == 0 {
+            problems.push("trash.retention_days must be greater than 0".to_string());
+        } else if self.trash.retention_days > MAX_RETENTION_DAYS {
+            problems.push(format!("trash.retention_days must not exceed {}", MAX_RETENTION_DAYS));
+        }
+        if self.trash.purge_interval_secs == 0 {
+            problems.push("trash.purge_interval_secs must be greater than 0".to_string());
+        }
+
+        if let Err(e) = sku::check_pattern(&self.sku.pattern) {
+            problems.push(format!("sku.pattern: {}", e));
+        }
+
+        if let Err(e) = AuthConfig::from_settings(&self.jwt) {
+            problems.push(format!("jwt: {}", e));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError(problems))
+        }
+    }
+}
+
+impl ServerSettings {
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+}
@@ -0,0 +1,26 @@
+use axum::{
+    extract::Request,
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+pub const DEPRECATION_HEADER: &str = "deprecation";
+pub const SUNSET_HEADER: &str = "sunset";
+
+// The v1 product paths and item list and create paths were superseded by
+// /api/v2 on 2026-10-18 (RFC 9745 date form) and stay mounted until the
+// sunset (RFC 8594)
+const V1_DEPRECATED_AT: &str = "@1792281600";
+const V1_SUNSET: &str = "Sun, 18 Apr 2027 00:00:00 GMT";
+const V1_SUCCESSOR: &str = "</api/v2/products>; rel=\"successor-version\"";
+
+// Marks every response from a deprecated v1 route, errors included
+pub async fn deprecated_v1(req: Request, next: Next) -> Response {
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    headers.insert(HeaderName::from_static(DEPRECATION_HEADER), HeaderValue::from_static(V1_DEPRECATED_AT));
+    headers.insert(HeaderName::from_static(SUNSET_HEADER), HeaderValue::from_static(V1_SUNSET));
+    headers.insert(header::LINK, HeaderValue::from_static(V1_SUCCESSOR));
+    response
+}
@@ -0,0 +1,222 @@
+use axum::{
+    extract::{
+        multipart::{MultipartError, MultipartRejection},
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::{DbErr, SqlErr};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::telemetry;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+// Every error a handler can return. Rendered as RFC 7807 problem+json with a
+// stable `code` that clients can branch on.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound { resource: &'static str, key: String },
+    Unauthorized(String),
+    Forbidden(String),
+    Validation(String),
+    Conflict(String),
+    PreconditionFailed(String),
+    InsufficientStock { item_id: i32, available: i32, requested: i32 },
+    InvalidBody(JsonRejection),
+    InvalidPath(PathRejection),
+    InvalidQuery(QueryRejection),
+    // Keeps the status axum chose, e.g. 413 when the upload is too large
+    InvalidMultipart { status: StatusCode, message: String },
+    Database(DbErr),
+}
+
+impl AppError {
+    pub fn not_found(resource: &'static str, key: impl ToString) -> Self {
+        AppError::NotFound {
+            resource,
+            key: key.to_string(),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Conflict(_) | AppError::InsufficientStock { .. } => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::InvalidBody(rejection) => rejection.status(),
+            AppError::InvalidPath(_) | AppError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            AppError::InvalidMultipart { status, .. } => *status,
+            AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "not_found",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Conflict(_) => "conflict",
+            AppError::PreconditionFailed(_) => "precondition_failed",
+            AppError::InsufficientStock { .. } => "insufficient_stock",
+            AppError::InvalidBody(_) => "invalid_body",
+            AppError::InvalidPath(_) => "invalid_path",
+            AppError::InvalidQuery(_) => "invalid_query",
+            AppError::InvalidMultipart { .. } => "invalid_multipart",
+            AppError::Database(_) => "database_error",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "Resource not found",
+            AppError::Unauthorized(_) => "Unauthorized",
+            AppError::Forbidden(_) => "Forbidden",
+            AppError::Validation(_) => "Validation failed",
+            AppError::Conflict(_) => "Conflict",
+            AppError::PreconditionFailed(_) => "Precondition failed",
+            AppError::InsufficientStock { .. } => "Insufficient stock",
+            AppError::InvalidBody(_) => "Invalid request body",
+            AppError::InvalidPath(_) => "Invalid path parameter",
+            AppError::InvalidQuery(_) => "Invalid query string",
+            AppError::InvalidMultipart { .. } => "Invalid multipart body",
+            AppError::Database(_) => "Internal server error",
+        }
+    }
+
+    pub fn detail(&self) -> String {
+        match self {
+            AppError::NotFound { resource, key } => format!("{} '{}' not found", resource, key),
+            AppError::Unauthorized(message)
+            | AppError::Forbidden(message)
+            | AppError::Validation(message)
+            | AppError::Conflict(message)
+            | AppError::PreconditionFailed(message) => message.clone(),
+            AppError::InsufficientStock { item_id, available, requested } => format!(
+                "item '{}' has {} units available but {} were requested",
+                item_id, available, requested
+            ),
+            AppError::InvalidBody(rejection) => rejection.body_text(),
+            AppError::InvalidPath(rejection) => rejection.body_text(),
+            AppError::InvalidQuery(rejection) => rejection.body_text(),
+            AppError::InvalidMultipart { message, .. } => message.clone(),
+            // Never leak SQL or driver details to clients
+            AppError::Database(_) => "An unexpected database error occurred".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub type_url: String,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    pub code: &'static str,
+    // Same value as the X-Request-Id response header, to quote in bug reports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+        match &self {
+            // The driver error is logged but never sent to the client
+            AppError::Database(e) => {
+                tracing::error!(code, status = status.as_u16(), error = ?e, "database error")
+            }
+            _ => tracing::info!(code, status = status.as_u16(), detail = %self.detail(), "request rejected"),
+        }
+
+        let problem = Problem {
+            type_url: format!("/problems/{}", code.replace('_', "-")),
+            title: self.title(),
+            status: status.as_u16(),
+            detail: self.detail(),
+            code,
+            request_id: telemetry::current_request_id(),
+        };
+
+        let mut response = (status, Json(problem)).into_response();
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
+
+impl From<DbErr> for AppError {
+    fn from(e: DbErr) -> Self {
+        match e.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => {
+                AppError::Conflict("A record with the same unique key already exists".to_string())
+            }
+            // Postgres names the parent side "update or delete on table ..."
+            Some(SqlErr::ForeignKeyConstraintViolation(message)) if message.starts_with("update or delete") => {
+                AppError::Conflict("The record is still referenced by other records".to_string())
+            }
+            Some(SqlErr::ForeignKeyConstraintViolation(_)) => {
+                AppError::Validation("A referenced record does not exist".to_string())
+            }
+            _ => match e {
+                DbErr::RecordNotFound(key) => AppError::NotFound {
+                    resource: "record",
+                    key,
+                },
+                e => AppError::Database(e),
+            },
+        }
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        AppError::InvalidBody(rejection)
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        AppError::InvalidPath(rejection)
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::InvalidQuery(rejection)
+    }
+}
+
+impl From<MultipartRejection> for AppError {
+    fn from(rejection: MultipartRejection) -> Self {
+        AppError::InvalidMultipart {
+            status: rejection.status(),
+            message: rejection.body_text(),
+        }
+    }
+}
+
+impl From<MultipartError> for AppError {
+    fn from(e: MultipartError) -> Self {
+        AppError::InvalidMultipart {
+            status: e.status(),
+            message: e.body_text(),
+        }
+    }
+}
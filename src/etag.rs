@@ -0,0 +1,56 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+
+use crate::error::{AppError, AppResult};
+
+// Strong entity tag for a row version, quoted as sent on the wire
+pub fn etag(version: i32) -> String {
+    format!("\"{}\"", version)
+}
+
+pub fn etag_header(version: i32) -> (header::HeaderName, HeaderValue) {
+    let value = HeaderValue::from_str(&etag(version)).expect("a quoted integer is a valid header value");
+    (header::ETAG, value)
+}
+
+// Fails with 412 when the request carries If-Match and none of its tags
+// matches the current version. If-Match uses strong comparison, so weak tags
+// never match; `*` matches any existing row.
+pub fn check_if_match(headers: &HeaderMap, version: i32) -> AppResult<()> {
+    let mut tags = match_tags(headers, header::IF_MATCH).peekable();
+    if tags.peek().is_none() {
+        return Ok(());
+    }
+
+    let current = etag(version);
+    if tags.any(|tag| tag == "*" || tag == current) {
+        return Ok(());
+    }
+    Err(AppError::PreconditionFailed(format!(
+        "the resource has changed; its current ETag is {}",
+        current
+    )))
+}
+
+// True when If-None-Match lists the current version (weak comparison) or
+// is `*`, in which case a GET is answered with 304
+pub fn is_not_modified(headers: &HeaderMap, version: i32) -> bool {
+    let current = etag(version);
+    match_tags(headers, header::IF_NONE_MATCH)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == current)
+}
+
+pub fn not_modified(version: i32) -> Response {
+    (StatusCode::NOT_MODIFIED, [etag_header(version)]).into_response()
+}
+
+// Entity tags from every instance of a list-valued header
+fn match_tags(headers: &HeaderMap, name: header::HeaderName) -> impl Iterator<Item = &str> {
+    headers
+        .get_all(name)
+        .into_iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+}
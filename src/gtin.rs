@@ -0,0 +1,45 @@
+// GTIN-8, GTIN-12 (UPC-A), GTIN-13 (EAN-13) and GTIN-14 barcodes. All are
+// digits ending in a mod-10 check digit, and a shorter one is the same code
+// as its zero-padded 14 digit form.
+
+pub const GTIN_LENGTHS: [usize; 4] = [8, 12, 13, 14];
+
+pub fn validate(code: &str) -> Result<(), String> {
+    if !code.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("barcode '{}' must contain only digits", code));
+    }
+    if !GTIN_LENGTHS.contains(&code.len()) {
+        return Err(format!(
+            "barcode '{}' has {} digits; a GTIN has 8, 12, 13 or 14",
+            code,
+            code.len()
+        ));
+    }
+
+    let (payload, check) = code.split_at(code.len() - 1);
+    let expected = check_digit(payload);
+    if check.as_bytes()[0] - b'0' != expected {
+        return Err(format!(
+            "barcode '{}' has check digit {} but should have {}",
+            code, check, expected
+        ));
+    }
+    Ok(())
+}
+
+// Digits are weighted 3 and 1 alternately from the right
+pub fn check_digit(payload: &str) -> u8 {
+    let sum: u32 = payload
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(i, b)| u32::from(b - b'0') * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+// The form barcodes are compared in, so an EAN-13 and the GTIN-14 with a
+// leading zero find the same item
+pub fn to_gtin14(code: &str) -> String {
+    format!("{:0>14}", code)
+}
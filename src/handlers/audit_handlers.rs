@@ -0,0 +1,82 @@
+use axum::{extract::OriginalUri, http::StatusCode, response::IntoResponse, Extension};
+use chrono::NaiveDateTime;
+use entity::audit_log::{AuditAction, AuditEntityType, Column, Entity as AuditLogEntity};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::error::{AppError, AppResult, Problem};
+use crate::extract::{Json, Query};
+use crate::models::audit_model::AuditLogModel;
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+
+// Newest entries first unless the caller sorts otherwise
+const DEFAULT_AUDIT_SORT: &str = "-created_at";
+
+// Query parameters accepted by the audit log endpoint. `from` is inclusive
+// and `to` exclusive, both in UTC.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub entity_type: Option<AuditEntityType>,
+    pub entity_key: Option<String>,
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/audit",
+    tag = "audit",
+    summary = "Search the audit log",
+    params(AuditListQuery),
+    responses(
+        (status = 200, description = "A page of audit entries", body = Page<AuditLogModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination, sort or time range", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_audit_log(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<AuditListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(AppError::Validation("from must be before to".to_string()));
+        }
+    }
+
+    let mut select = AuditLogEntity::find();
+    if let Some(entity_type) = query.entity_type {
+        select = select.filter(Column::EntityType.eq(entity_type));
+    }
+    if let Some(entity_key) = query.entity_key {
+        select = select.filter(Column::EntityKey.eq(entity_key));
+    }
+    if let Some(actor) = query.actor {
+        select = select.filter(Column::Actor.eq(actor));
+    }
+    if let Some(action) = query.action {
+        select = select.filter(Column::Action.eq(action));
+    }
+    if let Some(from) = query.from {
+        select = select.filter(Column::CreatedAt.gte(from.and_utc()));
+    }
+    if let Some(to) = query.to {
+        select = select.filter(Column::CreatedAt.lt(to.and_utc()));
+    }
+    let sort = query.sort.as_deref().unwrap_or(DEFAULT_AUDIT_SORT);
+    let select = apply_sort(select, Some(sort), Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    Ok((StatusCode::OK, Json(page.map(AuditLogModel::from))))
+}
@@ -0,0 +1,548 @@
+use std::collections::HashMap;
+
+use axum::{extract::OriginalUri, http::StatusCode, response::IntoResponse, Extension};
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::category::{self, Entity as CategoryEntity};
+use entity::product::{self, Entity as ProductEntity};
+use entity::product_category::{self, Entity as ProductCategoryEntity};
+use sea_orm::sea_query::{OnConflict, Query as SeaQuery};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, Set, Statement, TransactionTrait,
+    TryInsertResult,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::error::{AppError, AppResult, Problem};
+use crate::extract::{Json, Path, Query};
+use crate::handlers::product_hanlers::filter_products;
+use crate::models::category_model::{
+    CategoryDetailModel, CategoryModel, CategoryTreeModel, CreateCategoryModel, MoveCategoryModel,
+    RenameCategoryModel,
+};
+use crate::models::product_model::ProductModel;
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+
+// Categories form a tree through `parent_id`. The recursive queries use
+// UNION rather than UNION ALL, so they end even on a corrupted tree.
+const ANCESTORS_SQL: &str = "WITH RECURSIVE up(id, parent_id, depth) AS ( \
+         SELECT id, parent_id, 0 FROM category WHERE id = $1 \
+         UNION SELECT c.id, c.parent_id, up.depth + 1 FROM category c JOIN up ON c.id = up.parent_id \
+     ) SELECT id FROM up ORDER BY depth DESC";
+
+const SUBTREE_SQL: &str = "WITH RECURSIVE down(id) AS ( \
+         SELECT id FROM category WHERE id = $1 \
+         UNION SELECT c.id FROM category c JOIN down ON c.parent_id = down.id \
+     ) SELECT id FROM down";
+
+async fn find_category<C: ConnectionTrait>(conn: &C, id: i32) -> AppResult<category::Model> {
+    CategoryEntity::find_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("category", id))
+}
+
+async fn find_product<C: ConnectionTrait>(conn: &C, uuid: Uuid) -> AppResult<product::Model> {
+    ProductEntity::find_live()
+        .filter(product::Column::Uuid.eq(uuid))
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))
+}
+
+async fn category_ids<C: ConnectionTrait>(conn: &C, sql: &str, id: i32) -> AppResult<Vec<i32>> {
+    let rows = conn
+        .query_all(Statement::from_sql_and_values(DbBackend::Postgres, sql, [id.into()]))
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| row.try_get::<i32>("", "id"))
+        .collect::<Result<_, _>>()?)
+}
+
+// The category and every category above it, root first
+async fn ancestor_ids<C: ConnectionTrait>(conn: &C, id: i32) -> AppResult<Vec<i32>> {
+    category_ids(conn, ANCESTORS_SQL, id).await
+}
+
+// The category and every category below it
+async fn subtree_ids<C: ConnectionTrait>(conn: &C, id: i32) -> AppResult<Vec<i32>> {
+    category_ids(conn, SUBTREE_SQL, id).await
+}
+
+async fn audit_category<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    action: AuditAction,
+    before: Option<&category::Model>,
+    after: Option<&category::Model>,
+) -> AppResult<()> {
+    let key = after.or(before).expect("a change has a before or an after").id;
+    audit::record(txn, AuditEntry {
+        actor,
+        action,
+        entity_type: AuditEntityType::Category,
+        entity_key: key.to_string(),
+        before,
+        after,
+    })
+    .await
+}
+
+// Assignments are keyed by category and product, as `<id>/<uuid>`
+async fn audit_assignment<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    action: AuditAction,
+    uuid: Uuid,
+    assignment: &product_category::Model,
+) -> AppResult<()> {
+    let (before, after) = match action {
+        AuditAction::Delete => (Some(assignment), None),
+        _ => (None, Some(assignment)),
+    };
+    audit::record(txn, AuditEntry {
+        actor,
+        action,
+        entity_type: AuditEntityType::ProductCategory,
+        entity_key: format!("{}/{}", assignment.category_id, uuid),
+        before,
+        after,
+    })
+    .await
+}
+
+fn validate_name(name: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::Validation("name must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+// Nests the flat rows under their parents. Rows must be sorted by name.
+fn build_tree(categories: Vec<category::Model>) -> Vec<CategoryTreeModel> {
+    let mut children: HashMap<Option<i32>, Vec<category::Model>> = HashMap::new();
+    for category in categories {
+        children.entry(category.parent_id).or_default().push(category);
+    }
+
+    fn nest(
+        parent: Option<i32>,
+        children: &mut HashMap<Option<i32>, Vec<category::Model>>,
+    ) -> Vec<CategoryTreeModel> {
+        children
+            .remove(&parent)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| CategoryTreeModel {
+                id: c.id,
+                name: c.name,
+                children: nest(Some(c.id), children),
+            })
+            .collect()
+    }
+    nest(None, &mut children)
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/categories",
+    tag = "categories",
+    summary = "Create a category",
+    request_body = CreateCategoryModel,
+    responses(
+        (status = 201, description = "Category created", body = CategoryModel),
+        (status = 404, description = "Parent category not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The parent already has a category with this name", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_category(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Json(payload): Json<CreateCategoryModel>,
+) -> AppResult<impl IntoResponse> {
+    validate_name(&payload.name)?;
+    let txn = db.begin().await?;
+    if let Some(parent_id) = payload.parent_id {
+        find_category(&txn, parent_id).await?;
+    }
+
+    let inserted_category = category::ActiveModel {
+        parent_id: Set(payload.parent_id),
+        name: Set(payload.name),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    audit_category(&txn, &claims.sub, AuditAction::Create, None, Some(&inserted_category)).await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(CategoryModel::from(inserted_category))))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/categories",
+    tag = "categories",
+    summary = "Get the category tree",
+    responses(
+        (status = 200, description = "Top-level categories with everything below them", body = Vec<CategoryTreeModel>),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_category_tree(
+    Extension(db): Extension<DatabaseConnection>,
+) -> AppResult<impl IntoResponse> {
+    let categories = CategoryEntity::find()
+        .order_by_asc(category::Column::Name)
+        .order_by_asc(category::Column::Id)
+        .all(&db)
+        .await?;
+
+    Ok((StatusCode::OK, Json(build_tree(categories))))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/categories/{id}",
+    tag = "categories",
+    summary = "Get a category",
+    params(("id" = i32, Path, description = "Category id")),
+    responses(
+        (status = 200, description = "The category with its path from the root and its children", body = CategoryDetailModel),
+        (status = 404, description = "Category not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_category(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let category = find_category(&db, id).await?;
+
+    let mut ancestors = ancestor_ids(&db, id).await?;
+    ancestors.pop();
+    let mut by_id: HashMap<i32, category::Model> = CategoryEntity::find()
+        .filter(category::Column::Id.is_in(ancestors.iter().copied()))
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect();
+    let path = ancestors
+        .iter()
+        .filter_map(|id| by_id.remove(id))
+        .map(CategoryModel::from)
+        .collect();
+
+    let children = CategoryEntity::find()
+        .filter(category::Column::ParentId.eq(id))
+        .order_by_asc(category::Column::Name)
+        .order_by_asc(category::Column::Id)
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(CategoryModel::from)
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(CategoryDetailModel {
+            category: CategoryModel::from(category),
+            path,
+            children,
+        }),
+    ))
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/categories/{id}",
+    tag = "categories",
+    summary = "Rename a category",
+    params(("id" = i32, Path, description = "Category id")),
+    request_body = RenameCategoryModel,
+    responses(
+        (status = 200, description = "Category renamed", body = CategoryModel),
+        (status = 404, description = "Category not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "A sibling already has this name", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn rename_category(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+    Json(payload): Json<RenameCategoryModel>,
+) -> AppResult<impl IntoResponse> {
+    validate_name(&payload.name)?;
+
+    let txn = db.begin().await?;
+    let existing_category = find_category(&txn, id).await?;
+    let mut category = existing_category.clone().into_active_model();
+    category.name = Set(payload.name);
+    let updated_category = category.update(&txn).await?;
+    let (before, after) = (Some(&existing_category), Some(&updated_category));
+    audit_category(&txn, &claims.sub, AuditAction::Update, before, after).await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(CategoryModel::from(updated_category))))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/categories/{id}/move",
+    tag = "categories",
+    summary = "Move a category and its subtree under another parent",
+    params(("id" = i32, Path, description = "Category id")),
+    request_body = MoveCategoryModel,
+    responses(
+        (status = 200, description = "Category moved; its descendants moved with it", body = CategoryModel),
+        (status = 404, description = "Category or new parent not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The new parent already has a category with this name", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "The new parent is the category itself or one of its descendants", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn move_category(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+    Json(payload): Json<MoveCategoryModel>,
+) -> AppResult<impl IntoResponse> {
+    if payload.parent_id == Some(id) {
+        return Err(AppError::Validation(format!("category '{}' cannot be its own parent", id)));
+    }
+
+    // Two moves checked side by side could each pass and together close a
+    // loop, so moves take turns. Reads are not blocked.
+    let txn = db.begin().await?;
+    txn.execute_unprepared("LOCK TABLE category IN SHARE ROW EXCLUSIVE MODE")
+        .await?;
+
+    let category = find_category(&txn, id).await?;
+    if let Some(parent_id) = payload.parent_id {
+        find_category(&txn, parent_id).await?;
+        if ancestor_ids(&txn, parent_id).await?.contains(&id) {
+            return Err(AppError::Validation(format!(
+                "category '{}' is below category '{}', so it cannot become its parent",
+                parent_id, id
+            )));
+        }
+    }
+
+    let existing_category = category.clone();
+    let mut category = category.into_active_model();
+    category.parent_id = Set(payload.parent_id);
+    let moved_category = category.update(&txn).await?;
+    let (before, after) = (Some(&existing_category), Some(&moved_category));
+    audit_category(&txn, &claims.sub, AuditAction::Update, before, after).await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(CategoryModel::from(moved_category))))
+}
+
+
+#[utoipa::path(
+    delete,
+    path = "/api/categories/{id}",
+    tag = "categories",
+    summary = "Delete an empty category",
+    params(("id" = i32, Path, description = "Category id")),
+    responses(
+        (status = 204, description = "Category deleted; its products are unassigned, not deleted"),
+        (status = 404, description = "Category not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The category still has children", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_category(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    let category = find_category(&txn, id).await?;
+
+    let children = CategoryEntity::find()
+        .filter(category::Column::ParentId.eq(id))
+        .count(&txn)
+        .await?;
+    if children > 0 {
+        return Err(AppError::Conflict(format!(
+            "category '{}' still has child categories; move or delete them first",
+            id
+        )));
+    }
+
+    // A child created since the count makes the foreign key fail instead
+    CategoryEntity::delete_by_id(category.id).exec(&txn).await?;
+    audit_category(&txn, &claims.sub, AuditAction::Delete, Some(&category), None).await?;
+    txn.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/categories/{id}/products/{uuid}",
+    tag = "categories",
+    summary = "Assign a product to a category",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+    ),
+    responses(
+        (status = 204, description = "The product is in the category"),
+        (status = 404, description = "Category or product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn assign_product(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path((id, uuid)): Path<(i32, Uuid)>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    find_category(&txn, id).await?;
+    let product = find_product(&txn, uuid).await?;
+    let assignment = product_category::Model { product_id: product.id, category_id: id };
+
+    // Assigning twice is not an error, and only the first is logged
+    let inserted = ProductCategoryEntity::insert(assignment.clone().into_active_model())
+        .on_conflict(
+            OnConflict::columns([product_category::Column::ProductId, product_category::Column::CategoryId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(&txn)
+        .await?;
+    if let TryInsertResult::Inserted(_) = inserted {
+        audit_assignment(&txn, &claims.sub, AuditAction::Create, uuid, &assignment).await?;
+    }
+    txn.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+#[utoipa::path(
+    delete,
+    path = "/api/categories/{id}/products/{uuid}",
+    tag = "categories",
+    summary = "Remove a product from a category",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+    ),
+    responses(
+        (status = 204, description = "The product is not in the category"),
+        (status = 404, description = "Category or product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn unassign_product(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path((id, uuid)): Path<(i32, Uuid)>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    find_category(&txn, id).await?;
+    let product = find_product(&txn, uuid).await?;
+    let assignment = product_category::Model { product_id: product.id, category_id: id };
+
+    let deleted = ProductCategoryEntity::delete_many()
+        .filter(product_category::Column::ProductId.eq(product.id))
+        .filter(product_category::Column::CategoryId.eq(id))
+        .exec(&txn)
+        .await?;
+    if deleted.rows_affected > 0 {
+        audit_assignment(&txn, &claims.sub, AuditAction::Delete, uuid, &assignment).await?;
+    }
+    txn.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+// Query parameters accepted by the category product list endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CategoryProductListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub name: Option<String>,
+    pub sort: Option<String>,
+    // Also list products of every category below this one; on by default
+    pub descendants: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/categories/{id}/products",
+    tag = "categories",
+    summary = "List the products in a category",
+    params(
+        ("id" = i32, Path, description = "Category id"),
+        CategoryProductListQuery,
+    ),
+    responses(
+        (status = 200, description = "A page of products; each appears once even if it is in several matching categories", body = Page<ProductModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Category not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_category_products(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Path(id): Path<i32>,
+    Query(query): Query<CategoryProductListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    find_category(&db, id).await?;
+
+    let category_ids = match query.descendants.unwrap_or(true) {
+        true => subtree_ids(&db, id).await?,
+        false => vec![id],
+    };
+    // A subquery rather than a join, so products in several of the
+    // categories are not repeated
+    let in_categories = SeaQuery::select()
+        .column(product_category::Column::ProductId)
+        .from(ProductCategoryEntity)
+        .and_where(product_category::Column::CategoryId.is_in(category_ids))
+        .to_owned();
+
+    let select = filter_products(ProductEntity::find_live(), query.name.as_deref())
+        .filter(product::Column::Id.in_subquery(in_categories));
+    let select = apply_sort(select, query.sort.as_deref(), product::Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    Ok((StatusCode::OK, Json(page.map(ProductModel::from))))
+}
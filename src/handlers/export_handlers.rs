@@ -0,0 +1,253 @@
+use std::mem;
+
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError, Extension,
+};
+use entity::item::{self, Entity as ItemEntity};
+use entity::product::{self, Entity as ProductEntity};
+use futures_util::{stream, TryStreamExt};
+use sea_orm::{DatabaseConnection, Selector, SelectorTrait};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use utoipa::IntoParams;
+
+use crate::error::{AppResult, Problem};
+use crate::extract::Query;
+use crate::handlers::item_handlers::filter_items;
+use crate::handlers::product_hanlers::filter_products;
+use crate::models::export_model::{
+    ExportFormat, ExportJoin, ItemExportModel, JoinedItemExportModel, ProductExportModel,
+};
+use crate::pagination::apply_sort;
+
+// Encoded rows are sent once this many bytes have built up
+const CHUNK_BYTES: usize = 64 * 1024;
+
+// Chunks waiting for a slow client; the query pauses once these are queued
+const QUEUED_CHUNKS: usize = 4;
+
+enum RowWriter {
+    Ndjson(Vec<u8>),
+    // Only the first writer emits the header row; the ones that take over
+    // after each chunk leave it out
+    Csv(Box<csv::Writer<Vec<u8>>>),
+}
+
+impl RowWriter {
+    fn new(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Ndjson => RowWriter::Ndjson(Vec::new()),
+            ExportFormat::Csv => RowWriter::Csv(Box::new(csv::Writer::from_writer(Vec::new()))),
+        }
+    }
+
+    fn write<R: Serialize>(&mut self, row: &R) -> Result<(), BoxError> {
+        match self {
+            RowWriter::Ndjson(buffer) => {
+                serde_json::to_writer(&mut *buffer, row)?;
+                buffer.push(b'\n');
+            }
+            RowWriter::Csv(writer) => writer.serialize(row)?,
+        }
+        Ok(())
+    }
+
+    // Bytes written so far; the CSV writer may hold a little more internally
+    fn buffered(&self) -> usize {
+        match self {
+            RowWriter::Ndjson(buffer) => buffer.len(),
+            RowWriter::Csv(writer) => writer.get_ref().len(),
+        }
+    }
+
+    fn take(&mut self) -> Result<Vec<u8>, BoxError> {
+        match self {
+            RowWriter::Ndjson(buffer) => Ok(mem::take(buffer)),
+            RowWriter::Csv(writer) => {
+                let next = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+                Ok(mem::replace(&mut **writer, next).into_inner().map_err(|e| e.into_error())?)
+            }
+        }
+    }
+}
+
+// Runs the query with `stream()` on its own task and hands encoded chunks to
+// the response body through a bounded channel, so memory stays flat however
+// many rows there are. The status is sent before the first row, so a
+// database error mid-export can only cut the body short.
+fn stream_export<S, R>(
+    db: DatabaseConnection,
+    selector: Selector<S>,
+    format: ExportFormat,
+    to_row: fn(S::Item) -> R,
+    file_stem: &str,
+) -> Response
+where
+    S: SelectorTrait + Send + 'static,
+    S::Item: Send,
+    R: Serialize + 'static,
+{
+    let (tx, rx) = mpsc::channel::<Result<Vec<u8>, BoxError>>(QUEUED_CHUNKS);
+    tokio::spawn(async move {
+        if let Err(e) = write_rows(&db, selector, format, to_row, &tx).await {
+            tracing::error!(error = %e, "export aborted");
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    let body = Body::from_stream(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }));
+    let disposition = format!("attachment; filename=\"{}.{}\"", file_stem, format.extension());
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+async fn write_rows<S, R>(
+    db: &DatabaseConnection,
+    selector: Selector<S>,
+    format: ExportFormat,
+    to_row: fn(S::Item) -> R,
+    tx: &mpsc::Sender<Result<Vec<u8>, BoxError>>,
+) -> Result<(), BoxError>
+where
+    S: SelectorTrait + Send,
+    S::Item: Send,
+    R: Serialize,
+{
+    let mut rows = selector.stream(db).await?;
+    let mut writer = RowWriter::new(format);
+    while let Some(row) = rows.try_next().await? {
+        writer.write(&to_row(row))?;
+        // A closed channel means the client went away
+        if writer.buffered() >= CHUNK_BYTES && tx.send(Ok(writer.take()?)).await.is_err() {
+            return Ok(());
+        }
+    }
+    let rest = writer.take()?;
+    if !rest.is_empty() {
+        let _ = tx.send(Ok(rest)).await;
+    }
+    Ok(())
+}
+
+
+// Query parameters accepted by the product export endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    pub name: Option<String>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/export/products",
+    tag = "export",
+    summary = "Stream every product as NDJSON or CSV",
+    params(ProductExportQuery),
+    responses(
+        (status = 200, description = "One product per line; takes the list endpoint's filters", content(
+            (ProductExportModel = "application/x-ndjson"),
+            (String = "text/csv"),
+        )),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn export_products(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(query): Query<ProductExportQuery>,
+) -> AppResult<Response> {
+    let select = filter_products(ProductEntity::find_live(), query.name.as_deref());
+    let select = apply_sort(select, query.sort.as_deref(), product::Column::Id)?;
+
+    Ok(stream_export(
+        db,
+        select.into_model::<product::Model>(),
+        query.format,
+        ProductExportModel::from,
+        "products",
+    ))
+}
+
+
+// Query parameters accepted by the item export endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ItemExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    // `product` adds the product's UUID, name and description to each row
+    pub join: Option<ExportJoin>,
+    pub name: Option<String>,
+    pub product_id: Option<i32>,
+    pub min_quantity: Option<i32>,
+    pub max_quantity: Option<i32>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/export/items",
+    tag = "export",
+    summary = "Stream every item as NDJSON or CSV",
+    params(ItemExportQuery),
+    responses(
+        (status = 200, description = "One item per line; takes the list endpoint's filters", content(
+            (ItemExportModel = "application/x-ndjson"),
+            (JoinedItemExportModel = "application/x-ndjson"),
+            (String = "text/csv"),
+        )),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn export_items(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(query): Query<ItemExportQuery>,
+) -> AppResult<Response> {
+    let select = filter_items(
+        ItemEntity::find_live(),
+        query.name.as_deref(),
+        query.product_id,
+        query.min_quantity,
+        query.max_quantity,
+    );
+    let select = apply_sort(select, query.sort.as_deref(), item::Column::Id)?;
+
+    Ok(match query.join {
+        Some(ExportJoin::Product) => stream_export(
+            db,
+            select
+                .find_also_related(ProductEntity)
+                .into_model::<item::Model, product::Model>(),
+            query.format,
+            JoinedItemExportModel::from,
+            "items",
+        ),
+        None => stream_export(
+            db,
+            select.into_model::<item::Model>(),
+            query.format,
+            ItemExportModel::from,
+            "items",
+        ),
+    })
+}
@@ -0,0 +1,145 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+
+use axum::{http::StatusCode, response::IntoResponse, Extension};
+use migration::{Alias, MigratorTrait, Query};
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+use crate::extract::Json;
+use crate::models::health_model::{ComponentModel, LivenessModel, ReadinessModel};
+use crate::server::{Lifecycle, Phase};
+
+// Table where sea-orm-migration records applied migrations
+const MIGRATION_TABLE: &str = "seaql_migrations";
+
+// How often startup re-runs the checks while waiting to become ready
+const STARTUP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// Keeps the service in `Starting` until the database answers and every
+// migration is applied, then marks it ready. Stops early on shutdown.
+pub async fn mark_ready_when_healthy(db: DatabaseConnection, lifecycle: Lifecycle) {
+    while lifecycle.phase() == Phase::Starting {
+        let (database, migrations) = tokio::join!(check_database(&db), check_migrations(&db));
+        if database.status == "up" && migrations.status == "up" {
+            lifecycle.advance(Phase::Starting, Phase::Ready);
+            return;
+        }
+        tokio::time::sleep(STARTUP_CHECK_INTERVAL).await;
+    }
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    summary = "Liveness probe",
+    security(()),
+    responses(
+        (status = 200, description = "The process is running", body = LivenessModel),
+    )
+)]
+pub async fn liveness() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        Json(LivenessModel {
+            status: "ok".to_string(),
+        }),
+    )
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    summary = "Readiness probe",
+    security(()),
+    responses(
+        (status = 200, description = "Ready to serve traffic", body = ReadinessModel),
+        (status = 503, description = "Starting, shutting down or a component is down", body = ReadinessModel),
+    )
+)]
+pub async fn readiness(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(lifecycle): Extension<Lifecycle>,
+) -> impl IntoResponse {
+    let (database, migrations) = tokio::join!(check_database(&db), check_migrations(&db));
+    let all_up = database.status == "up" && migrations.status == "up";
+
+    let components = BTreeMap::from([
+        ("database".to_string(), database),
+        ("migrations".to_string(), migrations),
+    ]);
+
+    let (status_code, status) = match lifecycle.phase() {
+        Phase::Ready if all_up => (StatusCode::OK, "ready"),
+        Phase::Ready => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+        phase => (StatusCode::SERVICE_UNAVAILABLE, phase.as_str()),
+    };
+
+    (
+        status_code,
+        Json(ReadinessModel {
+            status: status.to_string(),
+            components,
+        }),
+    )
+}
+
+async fn check_database(db: &DatabaseConnection) -> ComponentModel {
+    let started = Instant::now();
+    let result = db.ping().await.map_err(|e| {
+        tracing::warn!(error = ?e, "readiness database ping failed");
+        "the database did not answer the ping".to_string()
+    });
+    component(started, result, Vec::new())
+}
+
+// Compares the migrations compiled into the binary with the ones recorded in
+// the database. Reads the table directly, because `MigratorTrait` helpers
+// would create it when it is missing.
+async fn check_migrations(db: &DatabaseConnection) -> ComponentModel {
+    let started = Instant::now();
+    let select = Query::select()
+        .column(Alias::new("version"))
+        .from(Alias::new(MIGRATION_TABLE))
+        .to_owned();
+
+    let rows = match db.query_all(db.get_database_backend().build(&select)).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::warn!(error = ?e, "readiness migration check failed");
+            let error = format!("cannot read the {} table", MIGRATION_TABLE);
+            return component(started, Err(error), Vec::new());
+        }
+    };
+    let applied: HashSet<String> = rows
+        .iter()
+        .filter_map(|row| row.try_get::<String>("", "version").ok())
+        .collect();
+
+    let pending: Vec<String> = migration::Migrator::migrations()
+        .iter()
+        .map(|m| m.name().to_string())
+        .filter(|name| !applied.contains(name))
+        .collect();
+
+    let result = match pending.len() {
+        0 => Ok(()),
+        n => Err(format!("{} migration(s) not applied", n)),
+    };
+    component(started, result, pending)
+}
+
+fn component(started: Instant, result: Result<(), String>, pending: Vec<String>) -> ComponentModel {
+    let (status, error) = match result {
+        Ok(()) => ("up", None),
+        Err(error) => ("down", Some(error)),
+    };
+    ComponentModel {
+        status: status.to_string(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+        pending,
+    }
+}
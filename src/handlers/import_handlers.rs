@@ -0,0 +1,92 @@
+use axum::{
+    extract::{multipart::MultipartRejection, Multipart},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::auth::Claims;
+use crate::config::{ImportFormat, ImportSettings, SkuSettings};
+use crate::error::{AppError, AppResult, Problem};
+use crate::extract::{Json, Query};
+use crate::import::{self, ImportOptions};
+use crate::models::import_model::{ImportReportModel, ImportUploadModel};
+
+// Query parameters accepted by the import endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    // `csv` or `ndjson`; guessed from the file name or content type when omitted
+    #[param(value_type = Option<String>)]
+    pub format: Option<ImportFormat>,
+    #[serde(default)]
+    pub dry_run: bool,
+    // Rows per transaction, overriding the configured value; 0 for one
+    pub chunk_size: Option<usize>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/import",
+    tag = "import",
+    summary = "Import products and items from a CSV or NDJSON file",
+    description = "Each row has `product_uuid`, `product_name`, `product_description`, `item_name` and \
+        `item_quantity`. Products are upserted by UUID, or by name for rows without one. Items are matched by name \
+        within their product.",
+    params(ImportQuery),
+    request_body(content = ImportUploadModel, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Every row was imported, or would be in a dry run", body = ImportReportModel),
+        (status = 422, description = "Some rows were rejected; see `errors`", body = ImportReportModel),
+        (status = 400, description = "Malformed multipart body or query string", body = Problem, content_type = "application/problem+json"),
+        (status = 413, description = "The upload is too large", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn import_catalog(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(settings): Extension<ImportSettings>,
+    Extension(skus): Extension<SkuSettings>,
+    claims: Claims,
+    Query(query): Query<ImportQuery>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> AppResult<impl IntoResponse> {
+    let mut multipart = multipart?;
+
+    let mut upload = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let format = query
+            .format
+            .or_else(|| field.file_name().and_then(ImportFormat::from_file_name))
+            .or_else(|| field.content_type().and_then(ImportFormat::from_content_type));
+        upload = Some((format, field.bytes().await?));
+        break;
+    }
+    let Some((format, data)) = upload else {
+        return Err(AppError::Validation("the multipart body has no `file` field".to_string()));
+    };
+    let format = format.ok_or_else(|| {
+        AppError::Validation("cannot tell the file format; pass format=csv or format=ndjson".to_string())
+    })?;
+
+    let options = ImportOptions {
+        dry_run: query.dry_run,
+        chunk_size: query.chunk_size.unwrap_or(settings.chunk_size),
+        skus,
+    };
+    let report = import::run_import(&db, &claims.sub, import::parse(format, &data), options).await?;
+
+    let status = if report.errors.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    Ok((status, Json(report)))
+}
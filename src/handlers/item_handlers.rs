@@ -1,232 +1,666 @@
-
-use axum::{http::StatusCode, response::IntoResponse, Extension, Json};
+use axum::{
+    extract::OriginalUri,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use chrono::Utc;
+use entity::audit_log::{AuditAction, AuditEntityType};
 use entity::item::{self, Column};
-use sea_orm::{ActiveModelTrait, ActiveValue::Set, DatabaseConnection, QueryFilter};
-use crate::models::item_model::ItemModel;
+use entity::product::{self, Entity as ProductEntity};
+use entity::stock_level::{self, Entity as StockLevelEntity};
+use entity::stock_movement::MovementKind;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, QueryFilter, QuerySelect, Select, Statement, TransactionTrait,
+};
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::config::{SkuSettings, StockSettings};
+use crate::error::{AppError, AppResult, Problem};
+use crate::etag::{check_if_match, etag_header, is_not_modified, not_modified};
+use crate::extract::{Json, Path, Query};
+use crate::gtin;
+use crate::handlers::stock_movement_handlers::{
+    apply_movement, lock_live_item, record_movement, validate_movement, NewMovement,
+};
+use crate::handlers::reservation_handlers::{held_quantity, release_item_reservations};
+use crate::handlers::warehouse_handlers::{check_stock_only_at, load_location_stock, resolve_location};
+use crate::models::item_model::{
+    AdjustItemModel, AdjustedItemModel, ItemModel, ItemWithStockModel, UpdateItemResponseModel,
+    UpdatedItemModel,
+};
+use crate::models::message_model::MessageModel;
+use crate::pagination::{apply_sort, contains_pattern, fetch_page, Page, PageParams};
+use crate::sku;
+use crate::stock;
 use entity::item::Entity as ItemEntity;
-use sea_orm::EntityTrait;
-use axum::extract::Path;
 use sea_orm::ColumnTrait;
+use sea_orm::sea_query::{extension::postgres::PgExpr, Expr, SimpleExpr};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 
 
+#[utoipa::path(
+    post,
+    path = "/api/item",
+    tag = "items",
+    summary = "Create an item",
+    request_body = ItemModel,
+    responses(
+        (status = 201, description = "Item created; initial stock is booked to the default location", body = ItemModel,
+            headers(("ETag" = String, description = "Current version of the item"))),
+        (status = 409, description = "The SKU or barcode is taken", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn create_item(
     Extension(db): Extension<DatabaseConnection>,
+    Extension(skus): Extension<SkuSettings>,
+    claims: Claims,
     Json(item_model): Json<ItemModel>,
-) -> impl IntoResponse {
-    let new_item = item::ActiveModel {
-        product_id: Set(item_model.ProductId),
-        name: Set(item_model.Name.clone()),
-        quantity: Set(item_model.Quantity),
-        ..Default::default()
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    // Share-lock the product so it cannot go to the trash without the item
+    ProductEntity::find_live()
+        .filter(product::Column::Id.eq(item_model.product_id))
+        .lock_shared()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| {
+            AppError::Validation(format!("product {} does not exist", item_model.product_id))
+        })?;
+    let new_item = NewItem {
+        sku: item_model.sku,
+        barcode: item_model.barcode,
+        ..NewItem::new(item_model.product_id, item_model.name, item_model.quantity)
     };
+    let inserted_item = insert_item(&txn, &claims.sub, &skus, new_item).await?;
+    txn.commit().await?;
 
-    match new_item.insert(&db).await {
-        Ok(inserted_item) => (
-            StatusCode::CREATED,
-            Json(ItemModel {
-                id: Some(inserted_item.id),
-                ProductId: inserted_item.product_id,
-                Name: inserted_item.name,
-                Quantity: inserted_item.quantity,
-            }),
-        )
-            .into_response(),
-        Err(e) => {
-            eprintln!("Error inserting item: {:?}", e); // Log the error for debugging
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": "Failed to create item",
-                    "details": e.to_string(),
-                })),
-            )
-                .into_response()
+    Ok((
+        StatusCode::CREATED,
+        [etag_header(inserted_item.version)],
+        Json(ItemModel::from(inserted_item)),
+    ))
+}
+
+// An item to insert; a missing SKU is generated from the configured pattern
+pub struct NewItem {
+    pub product_id: i32,
+    pub name: String,
+    pub quantity: i32,
+    pub sku: Option<String>,
+    pub barcode: Option<String>,
+    pub variant_key: Option<String>,
+}
+
+impl NewItem {
+    pub fn new(product_id: i32, name: String, quantity: i32) -> Self {
+        NewItem {
+            product_id,
+            name,
+            quantity,
+            sku: None,
+            barcode: None,
+            variant_key: None,
         }
     }
 }
 
+// Matches a barcode in any of its GTIN lengths, like the unique index
+fn barcode_matches(barcode: &str) -> SimpleExpr {
+    Expr::cust_with_values(r#"lpad("item"."barcode", 14, '0') = $1"#, [gtin::to_gtin14(barcode)])
+}
 
-pub async fn get_all_items(
-    Extension(db): Extension<DatabaseConnection>,
-) -> impl IntoResponse {
-    match ItemEntity::find().all(&db).await {
-        Ok(items) => {
-            let response_items: Vec<ItemModel> = items
-                .into_iter()
-                .map(|item| ItemModel {
-                    id: Some(item.id),
-                    ProductId: item.product_id,
-                    Name: item.name,
-                    Quantity: item.quantity,
-                })
-                .collect();
-
-            (StatusCode::OK, Json(response_items)).into_response()
+// Rejects a malformed SKU or barcode, or one that another item already has,
+// with a message naming that item. The unique indexes still catch writes
+// that race past this check.
+pub async fn check_item_codes<C: ConnectionTrait>(
+    conn: &C,
+    item_id: Option<i32>,
+    sku: Option<&str>,
+    barcode: Option<&str>,
+) -> AppResult<()> {
+    let others = || match item_id {
+        Some(id) => ItemEntity::find().filter(Column::Id.ne(id)),
+        None => ItemEntity::find(),
+    };
+    let taken = |code: &str, other: item::Model| {
+        let trashed = if other.deleted_at.is_some() { ", which is in the trash" } else { "" };
+        AppError::Conflict(format!("{} is already used by item '{}'{}", code, other.id, trashed))
+    };
+
+    if let Some(sku) = sku {
+        sku::validate(sku).map_err(AppError::Validation)?;
+        if let Some(other) = others().filter(Column::Sku.eq(sku)).one(conn).await? {
+            return Err(taken(&format!("SKU '{}'", sku), other));
         }
-        Err(e) => {
-            eprintln!("Error fetching items: {:?}", e); // Log the error for debugging
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": "Failed to fetch items",
-                    "details": e.to_string(),
-                })),
-            )
-                .into_response()
+    }
+    if let Some(barcode) = barcode {
+        gtin::validate(barcode).map_err(AppError::Validation)?;
+        if let Some(other) = others().filter(barcode_matches(barcode)).one(conn).await? {
+            return Err(taken(&format!("barcode '{}'", barcode), other));
         }
     }
+    Ok(())
+}
+
+// Inserts an item under a product the caller has already checked is live.
+// Shared by the v1 and v2 create endpoints, import and variant generation.
+pub async fn insert_item<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    skus: &SkuSettings,
+    new_item: NewItem,
+) -> AppResult<item::Model> {
+    if new_item.quantity < 0 {
+        return Err(AppError::Validation("quantity must not be negative".to_string()));
+    }
+
+    // The id is taken up front so a generated SKU can include it
+    let id: i32 = txn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT nextval(pg_get_serial_sequence('item', 'id'))::int AS id",
+        ))
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound("item id sequence".to_string()))?
+        .try_get("", "id")?;
+    let sku = match new_item.sku {
+        Some(sku) => sku,
+        None => sku::render(&skus.pattern, id, new_item.product_id)
+            .expect("the SKU pattern is checked when the config loads"),
+    };
+    check_item_codes(txn, None, Some(&sku), new_item.barcode.as_deref()).await?;
+
+    let quantity = new_item.quantity;
+    let new_item = item::ActiveModel {
+        id: Set(id),
+        product_id: Set(new_item.product_id),
+        name: Set(new_item.name),
+        quantity: Set(0),
+        sku: Set(Some(sku)),
+        barcode: Set(new_item.barcode),
+        variant_key: Set(new_item.variant_key),
+        ..Default::default()
+    };
+
+    // Insert with zero stock and book the initial quantity as a receipt, so
+    // the ledger accounts for every unit
+    let mut inserted_item = new_item.insert(txn).await?;
+    if quantity > 0 {
+        let movement = NewMovement {
+            kind: MovementKind::Receipt,
+            location_id: resolve_location(txn, None).await?,
+            delta: quantity,
+            reason_code: "initial_stock".to_string(),
+            actor: actor.to_string(),
+            reference: None,
+        };
+        (inserted_item, _) = apply_movement(txn, inserted_item.id, movement).await?;
+    }
+    audit::record(txn, AuditEntry {
+        actor,
+        action: AuditAction::Create,
+        entity_type: AuditEntityType::Item,
+        entity_key: inserted_item.id.to_string(),
+        before: None,
+        after: Some(&inserted_item),
+    })
+    .await?;
+    Ok(inserted_item)
+}
+
+
+// Query parameters accepted by the item list endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ItemListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub name: Option<String>,
+    pub product_id: Option<i32>,
+    pub min_quantity: Option<i32>,
+    pub max_quantity: Option<i32>,
+    pub sort: Option<String>,
+}
+
+// Filters shared by the item list and export endpoints
+pub fn filter_items(
+    mut select: Select<ItemEntity>,
+    name: Option<&str>,
+    product_id: Option<i32>,
+    min_quantity: Option<i32>,
+    max_quantity: Option<i32>,
+) -> Select<ItemEntity> {
+    if let Some(name) = name {
+        select = select.filter(Expr::col(Column::Name).ilike(contains_pattern(name)));
+    }
+    if let Some(product_id) = product_id {
+        select = select.filter(Column::ProductId.eq(product_id));
+    }
+    if let Some(min_quantity) = min_quantity {
+        select = select.filter(Column::Quantity.gte(min_quantity));
+    }
+    if let Some(max_quantity) = max_quantity {
+        select = select.filter(Column::Quantity.lte(max_quantity));
+    }
+    select
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/get_all_items",
+    tag = "items",
+    summary = "List items with stock per location",
+    params(ItemListQuery),
+    responses(
+        (status = 200, description = "A page of items", body = Page<ItemWithStockModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_all_items(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<ItemListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+
+    let select = filter_items(
+        ItemEntity::find_live(),
+        query.name.as_deref(),
+        query.product_id,
+        query.min_quantity,
+        query.max_quantity,
+    );
+    let select = apply_sort(select, query.sort.as_deref(), Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    let item_ids: Vec<i32> = page.data.iter().map(|item| item.id).collect();
+    let mut stock = load_location_stock(&db, &item_ids).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(page.map(|item| ItemWithStockModel {
+            locations: stock.remove(&item.id).unwrap_or_default(),
+            item: ItemModel::from(item),
+        })),
+    ))
 }
 
 
+#[utoipa::path(
+    get,
+    path = "/api/get_item/{id}",
+    tag = "items",
+    summary = "Get an item with stock per location",
+    params(
+        ("id" = i32, Path, description = "Item id"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response"),
+    ),
+    responses(
+        (status = 200, description = "The item", body = ItemWithStockModel,
+            headers(("ETag" = String, description = "Current version of the item"))),
+        (status = 304, description = "The item still matches If-None-Match"),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_item_by_id(
     Extension(db): Extension<DatabaseConnection>,
+    headers: HeaderMap,
     Path(id): Path<i32>,
-) -> impl IntoResponse {
-    match ItemEntity::find()
-        .filter(Column::Id.eq(id))
+) -> AppResult<Response> {
+    let item = ItemEntity::find_live_by_id(id)
         .one(&db)
-        .await
-    {
-        Ok(Some(item)) => {
-            // Convert the database item into the response model
-            let response_item = ItemModel {
-                id: Some(item.id),
-                ProductId: item.product_id,
-                Name: item.name,
-                Quantity: item.quantity,
-            };
-
-            (StatusCode::OK, Json(response_item)).into_response()
-        }
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({
-                "error": "Item not found",
-                "id": id
-            })),
-        )
-            .into_response(),
-        Err(e) => {
-            eprintln!("Error fetching item by ID: {:?}", e); // Log the error for debugging
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": "Failed to fetch item",
-                    "details": e.to_string(),
-                })),
-            )
-                .into_response()
-        }
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+    if is_not_modified(&headers, item.version) {
+        return Ok(not_modified(item.version));
     }
+
+    let locations = load_location_stock(&db, &[item.id])
+        .await?
+        .remove(&item.id)
+        .unwrap_or_default();
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(item.version)],
+        Json(ItemWithStockModel {
+            item: ItemModel::from(item),
+            locations,
+        }),
+    )
+        .into_response())
+}
+
+
+// Query parameters accepted by the barcode lookup endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ItemLookupQuery {
+    // Any GTIN length; a shorter code finds the same item as its padded form
+    pub barcode: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/items/lookup",
+    tag = "items",
+    summary = "Find an item by barcode",
+    params(ItemLookupQuery),
+    responses(
+        (status = 200, description = "The item", body = ItemWithStockModel,
+            headers(("ETag" = String, description = "Current version of the item"))),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "No item has this barcode", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Not a valid GTIN", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn lookup_item(
+    Extension(db): Extension<DatabaseConnection>,
+    Query(query): Query<ItemLookupQuery>,
+) -> AppResult<impl IntoResponse> {
+    gtin::validate(&query.barcode).map_err(AppError::Validation)?;
+    let item = ItemEntity::find_live()
+        .filter(barcode_matches(&query.barcode))
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("barcode", &query.barcode))?;
+
+    let locations = load_location_stock(&db, &[item.id])
+        .await?
+        .remove(&item.id)
+        .unwrap_or_default();
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(item.version)],
+        Json(ItemWithStockModel {
+            item: ItemModel::from(item),
+            locations,
+        }),
+    ))
 }
 
 
 // Define the structure for the update payload
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct UpdateItemPayload {
     pub name: Option<String>,
     pub quantity: Option<i32>,
+    pub sku: Option<String>,
+    // GTIN-8, -12, -13 or -14 with a valid check digit
+    pub barcode: Option<String>,
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/item/{id}",
+    tag = "items",
+    summary = "Update an item",
+    params(
+        ("id" = i32, Path, description = "Item id"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item still has this ETag"),
+    ),
+    request_body = UpdateItemPayload,
+    responses(
+        (status = 200, description = "Item updated", body = UpdateItemResponseModel,
+            headers(("ETag" = String, description = "New version of the item"))),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "The item no longer matches If-Match", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Not enough stock at the default location, stock held at other locations, or the SKU or barcode is taken", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn update_item_by_id(
     Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    headers: HeaderMap,
     Path(id): Path<i32>,
     Json(payload): Json<UpdateItemPayload>,
-) -> impl IntoResponse {
+) -> AppResult<impl IntoResponse> {
+    if payload.quantity.is_some_and(|quantity| quantity < 0) {
+        return Err(AppError::Validation("quantity must not be negative".to_string()));
+    }
+
+    let txn = db.begin().await?;
+
     // Find the existing item by ID
-    match ItemEntity::find_by_id(id).one(&db).await {
-        Ok(Some(existing_item)) => {
-            // Convert the fetched item into an ActiveModel for updating
-            let mut active_model: item::ActiveModel = existing_item.into();
-
-            // Update only the provided fields
-            if let Some(name) = payload.name {
-                active_model.name = Set(name);
-            }
-            if let Some(quantity) = payload.quantity {
-                active_model.quantity = Set(quantity);
-            }
-
-            // Save the updated ActiveModel
-            match active_model.update(&db).await {
-                Ok(updated_item) => (
-                    StatusCode::OK,
-                    Json(serde_json::json!({
-                        "message": "Item updated successfully",
-                        "updated_item": {
-                            "id": updated_item.id,
-                            "name": updated_item.name,
-                            "quantity": updated_item.quantity,
-                            "product_id": updated_item.product_id,
-                        }
-                    })),
-                ),
-                Err(e) => {
-                    eprintln!("Error updating item: {:?}", e); // Log error
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({
-                            "error": "Failed to update item",
-                            "details": e.to_string(),
-                        })),
-                    )
-                }
-            }
+    let mut updated_item = ItemEntity::find_live_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+    check_if_match(&headers, updated_item.version)?;
+    let existing_item = updated_item.clone();
+
+    // Update only the provided fields
+    if payload.name.is_some() || payload.sku.is_some() || payload.barcode.is_some() {
+        check_item_codes(&txn, Some(id), payload.sku.as_deref(), payload.barcode.as_deref()).await?;
+        let mut active_model: item::ActiveModel = updated_item.into();
+        if let Some(name) = payload.name {
+            active_model.name = Set(name);
         }
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({
-                "error": "Item not found",
-                "id": id,
-            })),
-        ),
-        Err(e) => {
-            eprintln!("Error fetching item by ID: {:?}", e); // Log error
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": "Failed to fetch item",
-                    "details": e.to_string(),
-                })),
-            )
+        if let Some(sku) = payload.sku {
+            active_model.sku = Set(Some(sku));
         }
+        if let Some(barcode) = payload.barcode {
+            active_model.barcode = Set(Some(barcode));
+        }
+        updated_item = active_model.update(&txn).await?;
     }
+
+    // A new absolute quantity is booked as an adjustment for the difference,
+    // taken from or added to the default location
+    if let Some(quantity) = payload.quantity.filter(|q| *q != updated_item.quantity) {
+        let location_id = resolve_location(&txn, None).await?;
+        check_stock_only_at(&txn, id, location_id).await?;
+        let movement = NewMovement {
+            kind: MovementKind::Adjustment,
+            location_id,
+            delta: quantity - updated_item.quantity,
+            reason_code: "manual_update".to_string(),
+            actor: claims.sub.clone(),
+            reference: None,
+        };
+        (updated_item, _) = apply_movement(&txn, id, movement).await?;
+    }
+
+    if updated_item != existing_item {
+        audit::record(&txn, AuditEntry {
+            actor: &claims.sub,
+            action: AuditAction::Update,
+            entity_type: AuditEntityType::Item,
+            entity_key: id.to_string(),
+            before: Some(&existing_item),
+            after: Some(&updated_item),
+        })
+        .await?;
+    }
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(updated_item.version)],
+        Json(UpdateItemResponseModel {
+            message: "Item updated successfully".to_string(),
+            updated_item: UpdatedItemModel {
+                id: updated_item.id,
+                name: updated_item.name,
+                quantity: updated_item.quantity,
+                product_id: updated_item.product_id,
+                sku: updated_item.sku,
+                barcode: updated_item.barcode,
+            },
+        }),
+    ))
 }
 
 
-pub async fn delete_item_by_id(
+// Adds a signed delta at one location and to the item total, the latter as
+// `quantity = quantity + delta`. The negative stock policy applies to the
+// location: what it has on hand less what live reservations hold there. The
+// item row is locked first, the lock reservations take too, so the held
+// quantity cannot change underneath.
+#[utoipa::path(
+    post,
+    path = "/api/item/{id}/adjust",
+    tag = "items",
+    summary = "Adjust an item's quantity by a signed delta",
+    params(("id" = i32, Path, description = "Item id")),
+    request_body = AdjustItemModel,
+    responses(
+        (status = 200, description = "Quantity adjusted", body = AdjustedItemModel,
+            headers(("ETag" = String, description = "New version of the item"))),
+        (status = 404, description = "Item or location not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Not enough unreserved stock at the location for the policy", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn adjust_item_quantity(
     Extension(db): Extension<DatabaseConnection>,
+    Extension(settings): Extension<StockSettings>,
+    claims: Claims,
     Path(id): Path<i32>,
-) -> impl IntoResponse {
-    match ItemEntity::delete_by_id(id).exec(&db).await {
-        Ok(delete_result) => {
-            if delete_result.rows_affected > 0 {
-                (
-                    StatusCode::OK,
-                    Json(serde_json::json!({
-                        "message": format!("Item with id {} deleted successfully", id),
-                    })),
-                )
-            } else {
-                (
-                    StatusCode::NOT_FOUND,
-                    Json(serde_json::json!({
-                        "error": "Item not found",
-                        "id": id
-                    })),
-                )
-            }
-        }
-        Err(e) => {
-            eprintln!("Error deleting item by ID: {:?}", e); // Log the error for debugging
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "error": "Failed to delete item",
-                    "details": e.to_string(),
-                })),
-            )
+    Json(payload): Json<AdjustItemModel>,
+) -> AppResult<impl IntoResponse> {
+    let policy = settings.negative_policy;
+    let txn = db.begin().await?;
+    let mut movement = NewMovement {
+        kind: MovementKind::Adjustment,
+        location_id: resolve_location(&txn, payload.location_id).await?,
+        delta: payload.delta,
+        reason_code: payload.reason_code.unwrap_or_else(|| "adjustment".to_string()),
+        actor: claims.sub.clone(),
+        reference: payload.reference,
+    };
+    validate_movement(&movement)?;
+
+    let existing_item = lock_live_item(&txn, id).await?;
+    let location_id = movement.location_id;
+    let on_hand = StockLevelEntity::find()
+        .filter(stock_level::Column::ItemId.eq(id))
+        .filter(stock_level::Column::LocationId.eq(location_id))
+        .one(&txn)
+        .await?
+        .map_or(0, |level| level.quantity);
+    let held = held_quantity(&txn, id, location_id).await?;
+    let applied_delta = stock::adjustable_delta(policy, on_hand, held, payload.delta).map_err(|available| {
+        AppError::InsufficientStock {
+            item_id: id,
+            available,
+            requested: -payload.delta,
         }
+    })?;
+
+    if existing_item.quantity.checked_add(applied_delta).is_none() {
+        return Err(AppError::Validation("delta overflows the item quantity".to_string()));
     }
-}
\ No newline at end of file
+
+    // A clamped adjustment of a location with nothing free changes nothing,
+    // so it leaves no movement or audit entry either
+    let adjusted_item = if applied_delta != 0 {
+        // The total moves by the delta in the statement itself, not by
+        // writing back the quantity read above
+        let adjusted_item = ItemEntity::update_many()
+            .col_expr(Column::Quantity, Expr::col(Column::Quantity).add(applied_delta))
+            .col_expr(Column::Version, Expr::col(Column::Version).add(1))
+            .col_expr(Column::UpdatedAt, Expr::current_timestamp().into())
+            .filter(Column::Id.eq(id))
+            .exec_with_returning(&txn)
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::not_found("item", id))?;
+        movement.delta = applied_delta;
+        // The policy has already weighed the level against the held units
+        record_movement(&txn, id, movement, adjusted_item.quantity, false).await?;
+        audit::record(&txn, AuditEntry {
+            actor: &claims.sub,
+            action: AuditAction::Adjust,
+            entity_type: AuditEntityType::Item,
+            entity_key: id.to_string(),
+            before: Some(&existing_item),
+            after: Some(&adjusted_item),
+        })
+        .await?;
+        adjusted_item
+    } else {
+        existing_item
+    };
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(adjusted_item.version)],
+        Json(AdjustedItemModel {
+            id,
+            quantity: adjusted_item.quantity,
+            requested_delta: payload.delta,
+            applied_delta,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/delete_item/{id}",
+    tag = "items",
+    summary = "Move an item to the trash",
+    params(("id" = i32, Path, description = "Item id")),
+    responses(
+        (status = 200, description = "Item moved to the trash; its held reservations are released", body = MessageModel),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_item_by_id(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    let existing_item = ItemEntity::find_live_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+
+    release_item_reservations(&txn, &[id]).await?;
+    let mut active_model: item::ActiveModel = existing_item.clone().into();
+    active_model.deleted_at = Set(Some(Utc::now().fixed_offset()));
+    let deleted_item = active_model.update(&txn).await?;
+    audit::record(&txn, AuditEntry {
+        actor: &claims.sub,
+        action: AuditAction::Delete,
+        entity_type: AuditEntityType::Item,
+        entity_key: id.to_string(),
+        before: Some(&existing_item),
+        after: Some(&deleted_item),
+    })
+    .await?;
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(MessageModel {
+            message: format!("Item with id {} deleted successfully", id),
+        }),
+    ))
+}
@@ -0,0 +1,79 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension,
+};
+use entity::item::{self, Entity as ItemEntity};
+use entity::product::Entity as ProductEntity;
+use sea_orm::{DatabaseConnection, PaginatorTrait, QuerySelect};
+use sea_orm::sea_query::Expr;
+use crate::error::AppResult;
+use crate::metrics::Metrics;
+
+// How stale the inventory gauges may get
+pub const INVENTORY_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    summary = "Prometheus metrics",
+    security(()),
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", body = String, content_type = "text/plain; version=0.0.4"),
+    )
+)]
+pub async fn get_metrics(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(metrics): Extension<Arc<Metrics>>,
+) -> impl IntoResponse {
+    // Only a Postgres pool has stats to report; asking any other connection,
+    // such as a mock or a disconnected one, for its pool panics
+    if matches!(db, DatabaseConnection::SqlxPostgresPoolConnection(_)) {
+        let pool = db.get_postgres_connection_pool();
+        metrics.db_pool_connections.set(pool.size() as i64);
+        metrics.db_pool_idle_connections.set(pool.num_idle() as i64);
+        metrics.db_pool_max_connections.set(pool.options().get_max_connections() as i64);
+    }
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics.render(),
+    )
+}
+
+// The inventory gauges need full-table aggregates, which are too costly to
+// run on every (unauthenticated) scrape, so they are refreshed on a timer.
+// A failed refresh keeps the previous values, so HTTP and pool metrics stay
+// visible while the database is down.
+pub async fn run_inventory_refresh(db: DatabaseConnection, metrics: Arc<Metrics>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = refresh_inventory_gauges(&db, &metrics).await {
+            tracing::warn!(error = ?e, "failed to refresh inventory metrics");
+        }
+    }
+}
+
+async fn refresh_inventory_gauges(db: &DatabaseConnection, metrics: &Metrics) -> AppResult<()> {
+    let products = ProductEntity::find_live().count(db).await?;
+    let items = ItemEntity::find_live().count(db).await?;
+    // SUM over an integer column is a bigint in Postgres, NULL when empty
+    let stock_quantity: Option<i64> = ItemEntity::find_live()
+        .select_only()
+        .column_as(Expr::col(item::Column::Quantity).sum(), "total")
+        .into_tuple()
+        .one(db)
+        .await?
+        .flatten();
+
+    metrics.products.set(products as i64);
+    metrics.items.set(items as i64);
+    metrics.stock_quantity.set(stock_quantity.unwrap_or(0));
+    Ok(())
+}
@@ -1,2 +1,15 @@
 pub mod product_hanlers;
-pub mod item_handlers;
\ No newline at end of file
+pub mod item_handlers;
+pub mod stock_movement_handlers;
+pub mod warehouse_handlers;
+pub mod health_handlers;
+pub mod metrics_handlers;
+pub mod reservation_handlers;
+pub mod trash_handlers;
+pub mod audit_handlers;
+pub mod product_v2_handlers;
+pub mod import_handlers;
+pub mod export_handlers;
+pub mod category_handlers;
+pub mod variant_handlers;
+pub mod price_handlers;
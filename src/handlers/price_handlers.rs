@@ -0,0 +1,691 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::OriginalUri,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension,
+};
+use chrono::{DateTime, FixedOffset, Utc};
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::item::{self, Entity as ItemEntity};
+use entity::price_entry::{self, Entity as PriceEntryEntity};
+use entity::price_list::{self, Entity as PriceListEntity};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::currency;
+use crate::error::{AppError, AppResult, Problem};
+use crate::etag::{check_if_match, etag_header};
+use crate::extract::{Json, Path, Query};
+use crate::models::price_model::{
+    BasePriceModel, CreatePriceEntryModel, CreatePriceListModel, EffectivePriceModel,
+    ItemBasePriceModel, PriceEntryModel, PriceListModel, PriceSource,
+};
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+
+// Newest first, so the current and scheduled prices lead the history
+const DEFAULT_PRICE_HISTORY_SORT: &str = "-valid_from";
+
+async fn find_price_list<C: ConnectionTrait>(conn: &C, id: i32) -> AppResult<price_list::Model> {
+    PriceListEntity::find_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("price list", id))
+}
+
+async fn find_item<C: ConnectionTrait>(conn: &C, id: i32) -> AppResult<item::Model> {
+    ItemEntity::find_live_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))
+}
+
+async fn audit_price_list<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    action: AuditAction,
+    before: Option<&price_list::Model>,
+    after: Option<&price_list::Model>,
+) -> AppResult<()> {
+    let key = after.or(before).expect("a change has a before or an after").id;
+    audit::record(txn, AuditEntry {
+        actor,
+        action,
+        entity_type: AuditEntityType::PriceList,
+        entity_key: key.to_string(),
+        before,
+        after,
+    })
+    .await
+}
+
+async fn audit_price_entry<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    action: AuditAction,
+    before: Option<&price_entry::Model>,
+    after: Option<&price_entry::Model>,
+) -> AppResult<()> {
+    let key = after.or(before).expect("a change has a before or an after").id;
+    audit::record(txn, AuditEntry {
+        actor,
+        action,
+        entity_type: AuditEntityType::PriceEntry,
+        entity_key: key.to_string(),
+        before,
+        after,
+    })
+    .await
+}
+
+fn parse_currency(code: &str) -> AppResult<String> {
+    currency::parse(code).map_err(AppError::Validation)
+}
+
+fn check_price(price: rust_decimal::Decimal, currency: &str) -> AppResult<()> {
+    currency::check_amount(price, currency).map_err(AppError::Validation)
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/price-lists",
+    tag = "pricing",
+    summary = "Create a price list",
+    request_body = CreatePriceListModel,
+    responses(
+        (status = 201, description = "Price list created", body = PriceListModel),
+        (status = 409, description = "A price list with this name already exists", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Empty name or unknown currency", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_price_list(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Json(payload): Json<CreatePriceListModel>,
+) -> AppResult<impl IntoResponse> {
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("name must not be empty".to_string()));
+    }
+    let currency = parse_currency(&payload.currency)?;
+
+    let txn = db.begin().await?;
+    let inserted_list = price_list::ActiveModel {
+        name: Set(name),
+        currency: Set(currency),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    audit_price_list(&txn, &claims.sub, AuditAction::Create, None, Some(&inserted_list)).await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(PriceListModel::from(inserted_list))))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/price-lists",
+    tag = "pricing",
+    summary = "List price lists",
+    responses(
+        (status = 200, description = "Every price list, sorted by name", body = Vec<PriceListModel>),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_price_lists(
+    Extension(db): Extension<DatabaseConnection>,
+) -> AppResult<impl IntoResponse> {
+    let lists: Vec<PriceListModel> = PriceListEntity::find()
+        .order_by_asc(price_list::Column::Name)
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(PriceListModel::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(lists)))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/price-lists/{id}",
+    tag = "pricing",
+    summary = "Get a price list",
+    params(("id" = i32, Path, description = "Price list id")),
+    responses(
+        (status = 200, description = "The price list", body = PriceListModel),
+        (status = 404, description = "Price list not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_price_list(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let list = find_price_list(&db, id).await?;
+    Ok((StatusCode::OK, Json(PriceListModel::from(list))))
+}
+
+
+#[utoipa::path(
+    delete,
+    path = "/api/price-lists/{id}",
+    tag = "pricing",
+    summary = "Delete a price list without prices",
+    params(("id" = i32, Path, description = "Price list id")),
+    responses(
+        (status = 204, description = "Price list deleted"),
+        (status = 404, description = "Price list not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The list still has prices", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_price_list(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    let list = find_price_list(&txn, id).await?;
+
+    // Lapsed prices count too; they are the list's history
+    let entries = PriceEntryEntity::find()
+        .filter(price_entry::Column::PriceListId.eq(id))
+        .count(&txn)
+        .await?;
+    if entries > 0 {
+        return Err(AppError::Conflict(format!(
+            "price list '{}' has {} price(s), including past ones",
+            list.name, entries
+        )));
+    }
+
+    PriceListEntity::delete_by_id(id).exec(&txn).await?;
+    audit_price_list(&txn, &claims.sub, AuditAction::Delete, Some(&list), None).await?;
+    txn.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/item/{id}/base-price",
+    tag = "pricing",
+    summary = "Set an item's base price",
+    params(
+        ("id" = i32, Path, description = "Item id"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item still has this ETag"),
+    ),
+    request_body = BasePriceModel,
+    responses(
+        (status = 200, description = "Base price set", body = ItemBasePriceModel,
+            headers(("ETag" = String, description = "New version of the item"))),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "The item no longer matches If-Match", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Negative price, unknown currency or too many decimal places", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn set_base_price(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    Json(payload): Json<BasePriceModel>,
+) -> AppResult<impl IntoResponse> {
+    let currency = parse_currency(&payload.currency)?;
+    check_price(payload.price, &currency)?;
+    save_base_price(&db, &claims.sub, &headers, id, Some((payload.price, currency))).await
+}
+
+
+#[utoipa::path(
+    delete,
+    path = "/api/item/{id}/base-price",
+    tag = "pricing",
+    summary = "Clear an item's base price",
+    params(
+        ("id" = i32, Path, description = "Item id"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item still has this ETag"),
+    ),
+    responses(
+        (status = 200, description = "Base price cleared", body = ItemBasePriceModel,
+            headers(("ETag" = String, description = "New version of the item"))),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "The item no longer matches If-Match", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn clear_base_price(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    save_base_price(&db, &claims.sub, &headers, id, None).await
+}
+
+// Base prices live on the item, so a change bumps its version and is
+// audited like any other item update. Setting the same price is a no-op.
+async fn save_base_price(
+    db: &DatabaseConnection,
+    actor: &str,
+    headers: &HeaderMap,
+    id: i32,
+    base_price: Option<(rust_decimal::Decimal, String)>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    let existing_item = ItemEntity::find_live_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+    check_if_match(headers, existing_item.version)?;
+
+    let (price, currency) = base_price.unzip();
+    let mut updated_item = existing_item.clone();
+    if price != existing_item.base_price || currency != existing_item.currency {
+        let mut active_model = existing_item.clone().into_active_model();
+        active_model.base_price = Set(price);
+        active_model.currency = Set(currency);
+        updated_item = active_model.update(&txn).await?;
+        audit::record(&txn, AuditEntry {
+            actor,
+            action: AuditAction::Update,
+            entity_type: AuditEntityType::Item,
+            entity_key: id.to_string(),
+            before: Some(&existing_item),
+            after: Some(&updated_item),
+        })
+        .await?;
+    }
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(updated_item.version)],
+        Json(ItemBasePriceModel::from(updated_item)),
+    ))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/price-lists/{id}/prices",
+    tag = "pricing",
+    summary = "Set or schedule an item's price in a price list",
+    params(("id" = i32, Path, description = "Price list id")),
+    request_body = CreatePriceEntryModel,
+    responses(
+        (status = 201, description = "Price entry created; the price running at its start is cut short, and resumes after it if it ends first", body = PriceEntryModel),
+        (status = 404, description = "Price list or item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Another entry starts at the same time or before this one ends", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid price or time range", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_price_entry(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(list_id): Path<i32>,
+    Json(payload): Json<CreatePriceEntryModel>,
+) -> AppResult<impl IntoResponse> {
+    let now = Utc::now().fixed_offset();
+    let valid_from = payload.valid_from.unwrap_or(now);
+    if payload.valid_to.is_some_and(|valid_to| valid_to <= valid_from) {
+        return Err(AppError::Validation("valid_to must be after valid_from".to_string()));
+    }
+    // Prices that have applied are history, which a new price cannot rewrite
+    if valid_from < now {
+        return Err(AppError::Validation("valid_from must not be in the past".to_string()));
+    }
+
+    let txn = db.begin().await?;
+    let list = find_price_list(&txn, list_id).await?;
+    check_price(payload.price, &list.currency)?;
+
+    // Locking the item serializes price changes for it
+    ItemEntity::find_live_by_id(payload.item_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", payload.item_id))?;
+
+    let entries = PriceEntryEntity::find()
+        .filter(price_entry::Column::ItemId.eq(payload.item_id))
+        .filter(price_entry::Column::PriceListId.eq(list_id))
+        .order_by_asc(price_entry::Column::ValidFrom)
+        .all(&txn)
+        .await?;
+
+    if let Some(same) = entries.iter().find(|e| e.valid_from == valid_from) {
+        return Err(AppError::Conflict(format!(
+            "price entry '{}' already starts at {}",
+            same.id, valid_from
+        )));
+    }
+
+    // Without an end the new price runs until the next scheduled one
+    let next = entries.iter().find(|e| e.valid_from > valid_from);
+    let valid_to = payload.valid_to.or(next.map(|e| e.valid_from));
+    if let Some(next) = next.filter(|next| valid_to.is_none_or(|to| to > next.valid_from)) {
+        return Err(AppError::Conflict(format!(
+            "the price would run past {}, when price entry '{}' starts",
+            next.valid_from, next.id
+        )));
+    }
+
+    // The price running when the new one starts is cut short there. If the
+    // new price ends first, the old one resumes afterwards as a new entry.
+    let running = entries
+        .iter()
+        .rfind(|e| e.valid_from < valid_from)
+        .filter(|e| e.valid_to.is_none_or(|to| to > valid_from));
+    if let Some(running) = running {
+        let mut active_model = running.clone().into_active_model();
+        active_model.valid_to = Set(Some(valid_from));
+        let cut_short = active_model.update(&txn).await?;
+        audit_price_entry(&txn, &claims.sub, AuditAction::Update, Some(running), Some(&cut_short)).await?;
+
+        if let Some(resume_at) = valid_to.filter(|to| running.valid_to.is_none_or(|end| end > *to)) {
+            let resumed = price_entry::ActiveModel {
+                price_list_id: Set(list_id),
+                item_id: Set(payload.item_id),
+                price: Set(running.price),
+                valid_from: Set(resume_at),
+                valid_to: Set(running.valid_to),
+                created_by: Set(claims.sub.clone()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            audit_price_entry(&txn, &claims.sub, AuditAction::Create, None, Some(&resumed)).await?;
+        }
+    }
+    let inserted_entry = price_entry::ActiveModel {
+        price_list_id: Set(list_id),
+        item_id: Set(payload.item_id),
+        price: Set(payload.price),
+        valid_from: Set(valid_from),
+        valid_to: Set(valid_to),
+        created_by: Set(claims.sub.clone()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    audit_price_entry(&txn, &claims.sub, AuditAction::Create, None, Some(&inserted_entry)).await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(PriceEntryModel::new(inserted_entry, &list.currency))))
+}
+
+
+#[utoipa::path(
+    delete,
+    path = "/api/price-lists/{id}/prices/{entry_id}",
+    tag = "pricing",
+    summary = "Cancel a scheduled price",
+    params(
+        ("id" = i32, Path, description = "Price list id"),
+        ("entry_id" = i32, Path, description = "Price entry id"),
+    ),
+    responses(
+        (status = 204, description = "Entry deleted; the entry before it runs on in its place"),
+        (status = 404, description = "Price list, entry or item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The price has already taken effect", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_price_entry(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path((list_id, entry_id)): Path<(i32, i32)>,
+) -> AppResult<impl IntoResponse> {
+    let find_entry = || {
+        PriceEntryEntity::find_by_id(entry_id).filter(price_entry::Column::PriceListId.eq(list_id))
+    };
+    let txn = db.begin().await?;
+    let item_id = find_entry()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("price entry", entry_id))?
+        .item_id;
+
+    // Locking the item serializes price changes for it. The entry is read
+    // again under the lock, as a change that held it may have merged or
+    // removed the entry since.
+    ItemEntity::find_live_by_id(item_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", item_id))?;
+    let entry = find_entry()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("price entry", entry_id))?;
+
+    // Prices that have applied are history and stay
+    if entry.valid_from <= Utc::now() {
+        return Err(AppError::Conflict(format!(
+            "price entry '{}' took effect at {}; schedule a new price instead",
+            entry.id, entry.valid_from
+        )));
+    }
+
+    // The entry before it, if it ran up to this one, carries on in its place.
+    // When that joins it to the same price resuming afterwards, as after
+    // cancelling a temporary price, the two become one entry again.
+    let neighbours = PriceEntryEntity::find()
+        .filter(price_entry::Column::ItemId.eq(entry.item_id))
+        .filter(price_entry::Column::PriceListId.eq(list_id));
+    let previous = neighbours
+        .clone()
+        .filter(price_entry::Column::ValidTo.eq(entry.valid_from))
+        .one(&txn)
+        .await?;
+    PriceEntryEntity::delete_by_id(entry_id).exec(&txn).await?;
+    audit_price_entry(&txn, &claims.sub, AuditAction::Delete, Some(&entry), None).await?;
+    if let Some(previous) = previous {
+        let mut valid_to = entry.valid_to;
+        if let Some(resume_at) = entry.valid_to {
+            let resumed = neighbours
+                .filter(price_entry::Column::ValidFrom.eq(resume_at))
+                .filter(price_entry::Column::Price.eq(previous.price))
+                .one(&txn)
+                .await?;
+            if let Some(resumed) = resumed {
+                valid_to = resumed.valid_to;
+                PriceEntryEntity::delete_by_id(resumed.id).exec(&txn).await?;
+                audit_price_entry(&txn, &claims.sub, AuditAction::Delete, Some(&resumed), None).await?;
+            }
+        }
+        let mut active_model = previous.clone().into_active_model();
+        active_model.valid_to = Set(valid_to);
+        let extended = active_model.update(&txn).await?;
+        audit_price_entry(&txn, &claims.sub, AuditAction::Update, Some(&previous), Some(&extended)).await?;
+    }
+    txn.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+// Query parameters accepted by the price history endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PriceHistoryQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub price_list: Option<i32>,
+    // Defaults to `-valid_from`
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/item/{id}/prices",
+    tag = "pricing",
+    summary = "List an item's past, current and scheduled prices",
+    params(
+        ("id" = i32, Path, description = "Item id"),
+        PriceHistoryQuery,
+    ),
+    responses(
+        (status = 200, description = "A page of price entries", body = Page<PriceEntryModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_price_history(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Path(id): Path<i32>,
+    Query(query): Query<PriceHistoryQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    find_item(&db, id).await?;
+
+    let mut select = PriceEntryEntity::find().filter(price_entry::Column::ItemId.eq(id));
+    if let Some(list_id) = query.price_list {
+        select = select.filter(price_entry::Column::PriceListId.eq(list_id));
+    }
+    let sort = query.sort.as_deref().unwrap_or(DEFAULT_PRICE_HISTORY_SORT);
+    let select = apply_sort(select, Some(sort), price_entry::Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+    let list_ids: Vec<i32> = page.data.iter().map(|e| e.price_list_id).collect();
+    let currencies: HashMap<i32, String> = PriceListEntity::find()
+        .filter(price_list::Column::Id.is_in(list_ids))
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(|list| (list.id, list.currency))
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(page.map(|entry| {
+            let currency = &currencies[&entry.price_list_id];
+            PriceEntryModel::new(entry, currency)
+        })),
+    ))
+}
+
+
+// Query parameters accepted by the effective price endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EffectivePriceQuery {
+    // Leave out for the base price
+    pub price_list: Option<i32>,
+    // RFC 3339 timestamp; defaults to now. Only price list entries answer
+    // for a given time, as the base price keeps no history.
+    pub at: Option<DateTime<FixedOffset>>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/item/{id}/price",
+    tag = "pricing",
+    summary = "Resolve the price of an item in a price list at a point in time",
+    params(
+        ("id" = i32, Path, description = "Item id"),
+        EffectivePriceQuery,
+    ),
+    responses(
+        (status = 200, description = "The list's price at that time; for the present, or without a list, the item's base price when the list has none and shares its currency", body = EffectivePriceModel),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Item or price list not found, or the item has no price then; the base price does not answer for a given `at`", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_effective_price(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Query(query): Query<EffectivePriceQuery>,
+) -> AppResult<impl IntoResponse> {
+    let at = query.at.unwrap_or_else(|| Utc::now().into());
+    let item = find_item(&db, id).await?;
+
+    let mut list_currency = None;
+    if let Some(list_id) = query.price_list {
+        let list = find_price_list(&db, list_id).await?;
+        list_currency = Some(list.currency.clone());
+        let entry = PriceEntryEntity::find()
+            .filter(price_entry::Column::ItemId.eq(id))
+            .filter(price_entry::Column::PriceListId.eq(list_id))
+            .filter(price_entry::Column::ValidFrom.lte(at))
+            .filter(
+                price_entry::Column::ValidTo
+                    .is_null()
+                    .or(price_entry::Column::ValidTo.gt(at)),
+            )
+            .order_by_desc(price_entry::Column::ValidFrom)
+            .one(&db)
+            .await?;
+        if let Some(entry) = entry {
+            return Ok((
+                StatusCode::OK,
+                Json(EffectivePriceModel {
+                    item_id: id,
+                    price: currency::display(entry.price, &list.currency),
+                    currency: list.currency,
+                    source: PriceSource::PriceList,
+                    at,
+                    price_list_id: Some(list_id),
+                    entry_id: Some(entry.id),
+                    valid_from: Some(entry.valid_from),
+                    valid_to: entry.valid_to,
+                }),
+            ));
+        }
+    }
+
+    // The base price has no history of its own, so it only answers for the
+    // present, and in place of a list's price only in the list's currency
+    let base = item.base_price.zip(item.currency).filter(|(_, currency)| {
+        query.at.is_none() && list_currency.is_none_or(|list| list == *currency)
+    });
+    let Some((price, currency)) = base else {
+        return Err(AppError::not_found("price for item", id));
+    };
+    Ok((
+        StatusCode::OK,
+        Json(EffectivePriceModel {
+            item_id: id,
+            price: currency::display(price, &currency),
+            currency,
+            source: PriceSource::Base,
+            at,
+            price_list_id: None,
+            entry_id: None,
+            valid_from: None,
+            valid_to: None,
+        }),
+    ))
+}
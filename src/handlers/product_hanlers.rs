@@ -1,234 +1,492 @@
-use axum::{http::StatusCode, response::IntoResponse, Extension, Json};
+use std::collections::HashMap;
+
+use axum::{
+    extract::OriginalUri,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
 use chrono::Utc;
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::item::{self, Entity as ItemEntity};
 use entity::product::{self, ActiveModel};
 use sea_orm::{ActiveModelTrait, Set};
 use uuid::Uuid;
-use crate::models::product_model::{CreateProductModel, ProductModel};
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::error::{AppError, AppResult, Problem};
+use crate::etag::{check_if_match, etag_header, is_not_modified, not_modified};
+use crate::extract::{Json, Path, Query};
+use crate::handlers::reservation_handlers::release_item_reservations;
+use crate::models::message_model::MessageModel;
+use crate::models::item_model::ProductItemModel;
+use crate::models::product_model::{
+    CreateProductModel, PatchProductModel, ProductAggregatesModel, ProductDetailModel, ProductModel,
+};
+use crate::pagination::{apply_sort, contains_pattern, fetch_page, Page, PageParams};
 use entity::product::Entity as ProductEntity;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter};
-use ::serde::Serialize;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, EntityTrait, FromQueryResult, LoaderTrait, QueryFilter,
+    QueryOrder, QuerySelect, Select, TransactionTrait,
+};
 use sea_orm::ColumnTrait;
-use axum::extract::Path;
+use sea_orm::sea_query::{extension::postgres::PgExpr, Expr};
+use serde::Deserialize;
+use utoipa::IntoParams;
 
 
+#[utoipa::path(
+    post,
+    path = "/api/product",
+    tag = "products",
+    summary = "Create a product",
+    request_body = CreateProductModel,
+    responses(
+        (status = 201, description = "Product created", body = ProductModel,
+            headers(("ETag" = String, description = "Current version of the product"))),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn create_product(
     Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
     Json(product_data): Json<CreateProductModel>
-) -> impl IntoResponse {
+) -> AppResult<impl IntoResponse> {
+    let inserted_product = insert_product(&db, &claims.sub, product_data).await?;
+
+    // Return the inserted product details as a response with StatusCode::CREATED
+    Ok((
+        StatusCode::CREATED,
+        [etag_header(inserted_product.version)],
+        Json(ProductModel::from(inserted_product)),
+    ))
+}
 
+// Shared by the v1 and v2 create endpoints
+pub async fn insert_product(
+    db: &DatabaseConnection,
+    actor: &str,
+    product_data: CreateProductModel,
+) -> AppResult<product::Model> {
     // Create a new ActiveModel to insert the product into the database
     let product_model: ActiveModel = product::ActiveModel {
         uuid: Set(Uuid::new_v4()),
-        name: Set(product_data.Name.to_owned()),
-        description: Set(product_data.Description.to_owned()),
-        created_at: Set(Utc::now().naive_utc()),
+        name: Set(product_data.name),
+        description: Set(product_data.description),
         ..Default::default()
     };
 
     // Insert the product into the database
-    let inserted_product = product_model.insert(&db).await.unwrap();
+    let txn = db.begin().await?;
+    let inserted_product = product_model.insert(&txn).await?;
+    audit::record(&txn, AuditEntry {
+        actor,
+        action: AuditAction::Create,
+        entity_type: AuditEntityType::Product,
+        entity_key: inserted_product.uuid.to_string(),
+        before: None,
+        after: Some(&inserted_product),
+    })
+    .await?;
+    txn.commit().await?;
+    Ok(inserted_product)
+}
 
-    // Close the database connection
-    db.close().await.unwrap();
 
-    // Create a ProductModel instance to return
-    let response_product = ProductModel {
-        uuid: inserted_product.uuid,
-        Name: inserted_product.name,
-        Description: inserted_product.description,
-        Created_at: inserted_product.created_at,
-    };
+// Query parameters accepted by the product list endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub name: Option<String>,
+    pub sort: Option<String>,
+    // Comma separated: `items`, `aggregates`
+    pub include: Option<String>,
+}
 
-    // Return the inserted product details as a response with StatusCode::CREATED
-    (StatusCode::CREATED, Json(response_product))
+// Query parameters accepted by the single product endpoints
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductIncludeQuery {
+    // Comma separated: `items`, `aggregates`
+    pub include: Option<String>,
 }
 
+// What `?include=` asked to embed in each product
+#[derive(Clone, Copy, Default)]
+pub struct ProductIncludes {
+    items: bool,
+    aggregates: bool,
+}
 
+impl ProductIncludes {
+    pub fn parse(include: Option<&str>) -> AppResult<Self> {
+        let mut includes = ProductIncludes::default();
+        for name in include.unwrap_or_default().split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "items" => includes.items = true,
+                "aggregates" => includes.aggregates = true,
+                other => {
+                    return Err(AppError::Validation(format!(
+                        "cannot include '{}'; expected items or aggregates",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(includes)
+    }
 
-// pub async fn get_all_products(
-//     Extension(db): Extension<DatabaseConnection>,
-// ) -> impl IntoResponse {
-    
-//     // Fetch all products from the database
-//     let products = ProductEntity::find().all(&db).await.unwrap();
+    pub fn is_empty(&self) -> bool {
+        !self.items && !self.aggregates
+    }
+}
 
-//     // Map the results to a vector of ProductModel
-//     let response_products: Vec<ProductModel> = products
-//         .into_iter()
-//         .map(|p| ProductModel {
-//             uuid: p.uuid,
-//             Name: p.name,
-//             Description: p.description,
-//             Created_at: p.created_at,
-//         })
-//         .collect();
+#[derive(FromQueryResult)]
+struct ItemAggregate {
+    product_id: i32,
+    item_count: i64,
+    total_quantity: i64,
+}
 
-//     // Return the list of products as JSON with StatusCode::OK
-//     (StatusCode::OK, Json(response_products))
-// }
-pub async fn get_all_products(
-    Extension(db): Extension<DatabaseConnection>,
-) -> impl IntoResponse {
-    match ProductEntity::find().all(&db).await {
-        Ok(products) => {
-            let response_products: Vec<ProductModel> = products
-                .into_iter()
-                .map(|p| ProductModel {
-                    uuid: p.uuid,
-                    Name: p.name,
-                    Description: p.description,
-                    Created_at: p.created_at,
-                })
-                .collect();
-            (StatusCode::OK, Json(response_products)).into_response()
-        }
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ProductResponse::Error {
-                    message: "Failed to fetch products".to_string(),
-                }),
+// Related data for a batch of products, loaded with one query per include
+// rather than one per product
+pub struct ProductExpansion {
+    items: Option<HashMap<i32, Vec<item::Model>>>,
+    aggregates: Option<HashMap<i32, ProductAggregatesModel>>,
+}
+
+impl ProductExpansion {
+    pub async fn load<C: ConnectionTrait>(
+        conn: &C,
+        products: &[product::Model],
+        includes: ProductIncludes,
+    ) -> AppResult<Self> {
+        let items = if includes.items {
+            let loaded = products.load_many(ItemEntity::find_live().order_by_asc(item::Column::Id), conn).await?;
+            Some(products.iter().map(|p| p.id).zip(loaded).collect())
+        } else {
+            None
+        };
+
+        let aggregates = if includes.aggregates {
+            let rows = ItemEntity::find_live()
+                .select_only()
+                .column(item::Column::ProductId)
+                .column_as(item::Column::Id.count(), "item_count")
+                .column_as(item::Column::Quantity.sum(), "total_quantity")
+                .filter(item::Column::ProductId.is_in(products.iter().map(|p| p.id)))
+                .group_by(item::Column::ProductId)
+                .into_model::<ItemAggregate>()
+                .all(conn)
+                .await?;
+            Some(
+                rows.into_iter()
+                    .map(|row| {
+                        let aggregates = ProductAggregatesModel {
+                            item_count: row.item_count,
+                            total_quantity: row.total_quantity,
+                        };
+                        (row.product_id, aggregates)
+                    })
+                    .collect(),
             )
-                .into_response()
+        } else {
+            None
+        };
+
+        Ok(ProductExpansion { items, aggregates })
+    }
+
+    pub fn apply(&mut self, product: product::Model) -> ProductDetailModel {
+        let items = self.items.as_mut().map(|items| {
+            items
+                .remove(&product.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|item| ProductItemModel::new(item, product.uuid))
+                .collect()
+        });
+        let aggregates = self
+            .aggregates
+            .as_ref()
+            .map(|aggregates| aggregates.get(&product.id).cloned().unwrap_or_default());
+        ProductDetailModel {
+            product: ProductModel::from(product),
+            items,
+            aggregates,
         }
     }
 }
 
+// Filters shared by the product list and export endpoints
+pub fn filter_products(mut select: Select<ProductEntity>, name: Option<&str>) -> Select<ProductEntity> {
+    if let Some(name) = name {
+        select = select.filter(Expr::col(product::Column::Name).ilike(contains_pattern(name)));
+    }
+    select
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/get_all_products",
+    tag = "products",
+    summary = "List products",
+    params(ProductListQuery),
+    responses(
+        (status = 200, description = "A page of products", body = Page<ProductDetailModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_all_products(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<ProductListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    let includes = ProductIncludes::parse(query.include.as_deref())?;
+
+    let select = filter_products(ProductEntity::find_live(), query.name.as_deref());
+    let select = apply_sort(select, query.sort.as_deref(), product::Column::Id)?;
 
+    let page = fetch_page(&db, select, params, &uri).await?;
+    let mut expansion = ProductExpansion::load(&db, &page.data, includes).await?;
 
-#[derive(Serialize)]
-#[serde(untagged)]
-pub enum ProductResponse {
-    Success(ProductModel),
-    Error { message: String },
+    Ok((StatusCode::OK, Json(page.map(|product| expansion.apply(product)))))
 }
 
+
+#[utoipa::path(
+    get,
+    path = "/api/get_product/{uuid}",
+    tag = "products",
+    summary = "Get a product",
+    params(
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+        ProductIncludeQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; ignored with include"),
+    ),
+    responses(
+        (status = 200, description = "The product", body = ProductDetailModel,
+            headers(("ETag" = String, description = "Current version of the product; not sent with include"))),
+        (status = 304, description = "The product still matches If-None-Match"),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Unknown include", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_product_by_uuid(
     Extension(db): Extension<DatabaseConnection>,
+    headers: HeaderMap,
     Path(uuid): Path<Uuid>,
-) -> impl IntoResponse {
+    Query(query): Query<ProductIncludeQuery>,
+) -> AppResult<Response> {
+    let includes = ProductIncludes::parse(query.include.as_deref())?;
 
-    let product = ProductEntity::find()
+    let product = ProductEntity::find_live()
         .filter(product::Column::Uuid.eq(uuid))
         .one(&db)
-        .await
-        .unwrap();
-
-    match product {
-        Some(p) => {
-            let response_product = ProductModel {
-                uuid: p.uuid,
-                Name: p.name,
-                Description: p.description,
-                Created_at: p.created_at,
-            };
-            (StatusCode::OK, Json(ProductResponse::Success(response_product)))
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))?;
+
+    // The version only covers the product's own fields, so it cannot
+    // validate a response that embeds its items
+    if includes.is_empty() {
+        if is_not_modified(&headers, product.version) {
+            return Ok(not_modified(product.version));
         }
-        None => (
-            StatusCode::NOT_FOUND,
-            Json(ProductResponse::Error {
-                message: "Product not found".to_string(),
-            }),
-        ),
+        let body = Json(ProductModel::from(product.clone()));
+        return Ok((StatusCode::OK, [etag_header(product.version)], body).into_response());
     }
-}
-
 
-
-
-#[derive(Serialize)]
-#[serde(untagged)]
-pub enum DeleteResponse {
-    Success { message: String },
-    Error { message: String },
+    let mut expansion = ProductExpansion::load(&db, std::slice::from_ref(&product), includes).await?;
+    Ok((StatusCode::OK, Json(expansion.apply(product))).into_response())
 }
 
+
+#[utoipa::path(
+    delete,
+    path = "/api/delete_product/{uuid}",
+    tag = "products",
+    summary = "Move a product to the trash",
+    params(("uuid" = Uuid, Path, description = "Product UUID")),
+    responses(
+        (status = 200, description = "Product and its items moved to the trash", body = MessageModel),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn delete_product(
     Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
     Path(uuid): Path<Uuid>,
-) -> impl IntoResponse {
-    
-    // Attempt to delete the product
-    let result = ProductEntity::delete_many()
-        .filter(entity::product::Column::Uuid.eq(uuid))
-        .exec(&db)
-        .await;
-
-    match result {
-        Ok(delete_result) => {
-            if delete_result.rows_affected > 0 {
-                // Product was successfully deleted
-                (
-                    StatusCode::OK,
-                    Json(DeleteResponse::Success {
-                        message: "Product deleted successfully".to_string(),
-                    }),
-                )
-            } else {
-                // Product not found
-                (
-                    StatusCode::NOT_FOUND,
-                    Json(DeleteResponse::Error {
-                        message: "Product not found".to_string(),
-                    }),
-                )
-            }
-        }
-        Err(e) => {
-            // Handle unexpected database error
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(DeleteResponse::Error {
-                    message: format!("Failed to delete product: {}", e),
-                }),
-            )
-        }
+) -> AppResult<impl IntoResponse> {
+    trash_product(&db, &claims.sub, uuid).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(MessageModel {
+            message: "Product deleted successfully".to_string(),
+        }),
+    ))
+}
+
+// Moves a live product and its items to the trash; shared by v1 and v2
+pub async fn trash_product(db: &DatabaseConnection, actor: &str, uuid: Uuid) -> AppResult<()> {
+    let txn = db.begin().await?;
+    let existing_product = ProductEntity::find_live()
+        .filter(product::Column::Uuid.eq(uuid))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))?;
+
+    // Its items go to the trash with it, stamped with the same time so that
+    // restoring the product brings back exactly these
+    let deleted_at = Utc::now().fixed_offset();
+    let items = ItemEntity::find_live()
+        .filter(item::Column::ProductId.eq(existing_product.id))
+        .all(&txn)
+        .await?;
+    let item_ids: Vec<i32> = items.iter().map(|item| item.id).collect();
+    ItemEntity::update_many()
+        .col_expr(item::Column::DeletedAt, Expr::value(deleted_at))
+        .col_expr(item::Column::Version, Expr::col(item::Column::Version).add(1))
+        .col_expr(item::Column::UpdatedAt, Expr::current_timestamp().into())
+        .filter(item::Column::Id.is_in(item_ids.iter().copied()))
+        .exec(&txn)
+        .await?;
+    release_item_reservations(&txn, &item_ids).await?;
+    for before in &items {
+        let after = item::Model {
+            deleted_at: Some(deleted_at),
+            version: before.version + 1,
+            ..before.clone()
+        };
+        audit::record(&txn, AuditEntry {
+            actor,
+            action: AuditAction::Delete,
+            entity_type: AuditEntityType::Item,
+            entity_key: before.id.to_string(),
+            before: Some(before),
+            after: Some(&after),
+        })
+        .await?;
     }
+
+    let mut product_model: ActiveModel = existing_product.clone().into();
+    product_model.deleted_at = Set(Some(deleted_at));
+    let deleted_product = product_model.update(&txn).await?;
+    audit::record(&txn, AuditEntry {
+        actor,
+        action: AuditAction::Delete,
+        entity_type: AuditEntityType::Product,
+        entity_key: uuid.to_string(),
+        before: Some(&existing_product),
+        after: Some(&deleted_product),
+    })
+    .await?;
+    txn.commit().await?;
+
+    Ok(())
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/product/{uuid}",
+    tag = "products",
+    summary = "Update a product",
+    params(
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the product still has this ETag"),
+    ),
+    request_body = CreateProductModel,
+    responses(
+        (status = 200, description = "Product updated", body = ProductModel,
+            headers(("ETag" = String, description = "New version of the product"))),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "The product no longer matches If-Match", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
 pub async fn update_product(
     Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    headers: HeaderMap,
     Path(uuid): Path<Uuid>,
     Json(update_data): Json<CreateProductModel>,
-) -> impl IntoResponse {
-    // Attempt to find the product by UUID
-    let product = ProductEntity::find()
+) -> AppResult<impl IntoResponse> {
+    let changes = PatchProductModel {
+        name: Some(update_data.name),
+        description: Some(update_data.description),
+    };
+    let updated_product = save_product_changes(&db, &claims.sub, &headers, uuid, changes).await?;
+
+    // Return the updated product
+    Ok((
+        StatusCode::OK,
+        [etag_header(updated_product.version)],
+        Json(ProductModel::from(updated_product)),
+    ))
+}
+
+// Writes the given fields to a live product once If-Match passes; fields left
+// out keep their value. Shared by PUT and the v2 PATCH.
+pub async fn save_product_changes(
+    db: &DatabaseConnection,
+    actor: &str,
+    headers: &HeaderMap,
+    uuid: Uuid,
+    changes: PatchProductModel,
+) -> AppResult<product::Model> {
+    let txn = db.begin().await?;
+
+    // Attempt to find the product by UUID, locked so the version cannot
+    // change between the If-Match check and the write
+    let existing_product = ProductEntity::find_live()
         .filter(product::Column::Uuid.eq(uuid))
-        .one(&db)
-        .await
-        .unwrap();
-
-    match product {
-        Some(existing_product) => {
-            // Create an ActiveModel for updating
-            let mut product_model: ActiveModel = existing_product.into();
-
-            // Update fields
-            product_model.name = Set(update_data.Name.to_owned());
-            product_model.description = Set(update_data.Description.to_owned());
-            product_model.created_at = Set(Utc::now().naive_utc());
-
-            // Save the updated product
-            let updated_product = product_model.update(&db).await.unwrap();
-
-            // Map to response model
-            let response_product = ProductModel {
-                uuid: updated_product.uuid,
-                Name: updated_product.name,
-                Description: updated_product.description,
-                Created_at: updated_product.created_at,
-            };
-
-            // Return the updated product
-            (StatusCode::OK, Json(ProductResponse::Success(response_product)))
-        }
-        None => (
-            // Product not found
-            StatusCode::NOT_FOUND,
-            Json(ProductResponse::Error {
-                message: "Product not found".to_string(),
-            }),
-        ),
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))?;
+    check_if_match(headers, existing_product.version)?;
+
+    // Create an ActiveModel for updating
+    let mut product_model: ActiveModel = existing_product.clone().into();
+
+    // Update the provided fields
+    if let Some(name) = changes.name {
+        product_model.name = Set(name);
+    }
+    if let Some(description) = changes.description {
+        product_model.description = Set(description);
     }
-}
\ No newline at end of file
+    if !product_model.is_changed() {
+        return Ok(existing_product);
+    }
+
+    // Save the updated product
+    let updated_product = product_model.update(&txn).await?;
+    audit::record(&txn, AuditEntry {
+        actor,
+        action: AuditAction::Update,
+        entity_type: AuditEntityType::Product,
+        entity_key: uuid.to_string(),
+        before: Some(&existing_product),
+        after: Some(&updated_product),
+    })
+    .await?;
+    txn.commit().await?;
+    Ok(updated_product)
+}
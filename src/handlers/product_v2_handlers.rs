@@ -0,0 +1,325 @@
+use axum::{
+    extract::OriginalUri,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use entity::item::{self, Entity as ItemEntity};
+use entity::product::{self, Entity as ProductEntity};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, QueryFilter, QuerySelect, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::auth::Claims;
+use crate::error::{AppError, AppResult, Problem};
+use crate::etag::etag_header;
+use crate::extract::{Json, Path, Query};
+use crate::config::SkuSettings;
+use crate::handlers::item_handlers::{filter_items, insert_item, NewItem};
+use crate::handlers::product_hanlers::{
+    get_all_products, get_product_by_uuid, insert_product, save_product_changes, trash_product,
+    update_product, ProductIncludeQuery, ProductListQuery,
+};
+use crate::models::item_model::{CreateProductItemModel, ProductItemModel};
+use crate::models::product_model::{
+    CreateProductModel, PatchProductModel, ProductDetailModel, ProductModel,
+};
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+
+// The v2 API addresses products by UUID only and nests their items under
+// them. Reads and full updates behave exactly like v1, so they delegate.
+
+async fn find_product<C: ConnectionTrait>(conn: &C, uuid: Uuid) -> AppResult<product::Model> {
+    ProductEntity::find_live()
+        .filter(product::Column::Uuid.eq(uuid))
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))
+}
+
+fn product_location(uuid: Uuid) -> (header::HeaderName, HeaderValue) {
+    let location = format!("/api/v2/products/{}", uuid);
+    (header::LOCATION, HeaderValue::from_str(&location).expect("a UUID path is a valid header value"))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/products",
+    tag = "v2",
+    summary = "List products",
+    params(ProductListQuery),
+    responses(
+        (status = 200, description = "A page of products", body = Page<ProductDetailModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_products(
+    db: Extension<DatabaseConnection>,
+    uri: OriginalUri,
+    query: Query<ProductListQuery>,
+) -> AppResult<impl IntoResponse> {
+    get_all_products(db, uri, query).await
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/products",
+    tag = "v2",
+    summary = "Create a product",
+    request_body = CreateProductModel,
+    responses(
+        (status = 201, description = "Product created", body = ProductModel,
+            headers(
+                ("ETag" = String, description = "Current version of the product"),
+                ("Location" = String, description = "Path of the new product"),
+            )),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_product(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Json(product_data): Json<CreateProductModel>,
+) -> AppResult<impl IntoResponse> {
+    let inserted_product = insert_product(&db, &claims.sub, product_data).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        [etag_header(inserted_product.version), product_location(inserted_product.uuid)],
+        Json(ProductModel::from(inserted_product)),
+    ))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/products/{uuid}",
+    tag = "v2",
+    summary = "Get a product",
+    params(
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+        ProductIncludeQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag from an earlier response; ignored with include"),
+    ),
+    responses(
+        (status = 200, description = "The product", body = ProductDetailModel,
+            headers(("ETag" = String, description = "Current version of the product; not sent with include"))),
+        (status = 304, description = "The product still matches If-None-Match"),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Unknown include", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_product(
+    db: Extension<DatabaseConnection>,
+    headers: HeaderMap,
+    uuid: Path<Uuid>,
+    query: Query<ProductIncludeQuery>,
+) -> AppResult<Response> {
+    get_product_by_uuid(db, headers, uuid, query).await
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/v2/products/{uuid}",
+    tag = "v2",
+    summary = "Replace a product",
+    params(
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the product still has this ETag"),
+    ),
+    request_body = CreateProductModel,
+    responses(
+        (status = 200, description = "Product updated", body = ProductModel,
+            headers(("ETag" = String, description = "New version of the product"))),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "The product no longer matches If-Match", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn replace_product(
+    db: Extension<DatabaseConnection>,
+    claims: Claims,
+    headers: HeaderMap,
+    uuid: Path<Uuid>,
+    body: Json<CreateProductModel>,
+) -> AppResult<impl IntoResponse> {
+    update_product(db, claims, headers, uuid, body).await
+}
+
+
+#[utoipa::path(
+    patch,
+    path = "/api/v2/products/{uuid}",
+    tag = "v2",
+    summary = "Change some fields of a product",
+    params(
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the product still has this ETag"),
+    ),
+    request_body = PatchProductModel,
+    responses(
+        (status = 200, description = "Product updated", body = ProductModel,
+            headers(("ETag" = String, description = "New version of the product"))),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "The product no longer matches If-Match", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn patch_product(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    headers: HeaderMap,
+    Path(uuid): Path<Uuid>,
+    Json(changes): Json<PatchProductModel>,
+) -> AppResult<impl IntoResponse> {
+    let updated_product = save_product_changes(&db, &claims.sub, &headers, uuid, changes).await?;
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(updated_product.version)],
+        Json(ProductModel::from(updated_product)),
+    ))
+}
+
+
+#[utoipa::path(
+    delete,
+    path = "/api/v2/products/{uuid}",
+    tag = "v2",
+    summary = "Move a product to the trash",
+    params(("uuid" = Uuid, Path, description = "Product UUID")),
+    responses(
+        (status = 204, description = "Product and its items moved to the trash"),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_product(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(uuid): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    trash_product(&db, &claims.sub, uuid).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+// Query parameters accepted by the v2 item list endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProductItemListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub name: Option<String>,
+    pub min_quantity: Option<i32>,
+    pub max_quantity: Option<i32>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/products/{uuid}/items",
+    tag = "v2",
+    summary = "List the items of a product",
+    params(
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+        ProductItemListQuery,
+    ),
+    responses(
+        (status = 200, description = "A page of the product's items", body = Page<ProductItemModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_product_items(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Path(uuid): Path<Uuid>,
+    Query(query): Query<ProductItemListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    let product = find_product(&db, uuid).await?;
+
+    let select = filter_items(
+        ItemEntity::find_live(),
+        query.name.as_deref(),
+        Some(product.id),
+        query.min_quantity,
+        query.max_quantity,
+    );
+    let select = apply_sort(select, query.sort.as_deref(), item::Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    Ok((StatusCode::OK, Json(page.map(|item| ProductItemModel::new(item, uuid)))))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/products/{uuid}/items",
+    tag = "v2",
+    summary = "Create an item under a product",
+    params(("uuid" = Uuid, Path, description = "Product UUID")),
+    request_body = CreateProductItemModel,
+    responses(
+        (status = 201, description = "Item created; initial stock is booked to the default location", body = ProductItemModel,
+            headers(("ETag" = String, description = "Current version of the item"))),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The SKU or barcode is taken", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_product_item(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(skus): Extension<SkuSettings>,
+    claims: Claims,
+    Path(uuid): Path<Uuid>,
+    Json(item_data): Json<CreateProductItemModel>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    // Share-lock the product so it cannot go to the trash without the item
+    let product = ProductEntity::find_live()
+        .filter(product::Column::Uuid.eq(uuid))
+        .lock_shared()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))?;
+    let new_item = NewItem {
+        sku: item_data.sku,
+        barcode: item_data.barcode,
+        ..NewItem::new(product.id, item_data.name, item_data.quantity)
+    };
+    let inserted_item = insert_item(&txn, &claims.sub, &skus, new_item).await?;
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        [etag_header(inserted_item.version)],
+        Json(ProductItemModel::new(inserted_item, uuid)),
+    ))
+}
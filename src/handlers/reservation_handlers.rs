@@ -0,0 +1,393 @@
+use std::time::Duration;
+
+use axum::{extract::OriginalUri, http::StatusCode, response::IntoResponse, Extension};
+use chrono::Utc;
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::item::Entity as ItemEntity;
+use entity::reservation::{self, Column, Entity as ReservationEntity, ReservationStatus};
+use entity::stock_level::{self, Entity as StockLevelEntity};
+use entity::stock_movement::MovementKind;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::config::ReservationSettings;
+use crate::error::{AppError, AppResult, Problem};
+use crate::extract::{Json, Path, Query};
+use crate::handlers::stock_movement_handlers::{
+    apply_movement, audit_stock_change, lock_live_item, NewMovement,
+};
+use crate::handlers::warehouse_handlers::resolve_location;
+use crate::models::item_model::ItemModel;
+use crate::models::reservation_model::{
+    ConfirmedReservationModel, CreateReservationModel, ReservationModel,
+};
+use crate::models::stock_movement_model::StockMovementModel;
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+use crate::reservations::{self, Resolution};
+
+// Units of an item held at a location by live reservations; see
+// `reservations::is_live`. Expired ones no longer count even before the
+// sweeper marks them, so stock frees up on time.
+pub async fn held_quantity<C: ConnectionTrait>(
+    conn: &C,
+    item_id: i32,
+    location_id: i32,
+) -> AppResult<i32> {
+    // SUM over an integer column is a bigint in Postgres, NULL when empty
+    let held: Option<i64> = ReservationEntity::find()
+        .select_only()
+        .column_as(Expr::col(Column::Quantity).sum(), "held")
+        .filter(Column::ItemId.eq(item_id))
+        .filter(Column::LocationId.eq(location_id))
+        .filter(Column::Status.eq(ReservationStatus::Held))
+        .filter(Column::ExpiresAt.gt(Utc::now().fixed_offset()))
+        .into_tuple()
+        .one(conn)
+        .await?
+        .flatten();
+
+    Ok(held.unwrap_or(0) as i32)
+}
+
+// Marks held reservations past their expiry as expired, the `Expire`
+// resolution in bulk, and returns how many were changed
+pub async fn sweep_expired(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    let now = Utc::now().fixed_offset();
+    let result = ReservationEntity::update_many()
+        .col_expr(Column::Status, Expr::value(ReservationStatus::Expired))
+        .col_expr(Column::ResolvedAt, Expr::value(now))
+        .filter(Column::Status.eq(ReservationStatus::Held))
+        .filter(Column::ExpiresAt.lte(now))
+        .exec(db)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+// Releases every held reservation of the given items, for when they are
+// deleted
+pub async fn release_item_reservations<C: ConnectionTrait>(
+    conn: &C,
+    item_ids: &[i32],
+) -> Result<u64, DbErr> {
+    let result = ReservationEntity::update_many()
+        .col_expr(Column::Status, Expr::value(ReservationStatus::Released))
+        .col_expr(Column::ResolvedAt, Expr::value(Utc::now().fixed_offset()))
+        .filter(Column::ItemId.is_in(item_ids.iter().copied()))
+        .filter(Column::Status.eq(ReservationStatus::Held))
+        .exec(conn)
+        .await?;
+    Ok(result.rows_affected)
+}
+
+// Runs `sweep_expired` every `interval` until the task is aborted
+pub async fn run_sweeper(db: DatabaseConnection, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match sweep_expired(&db).await {
+            Ok(0) => {}
+            Ok(expired) => tracing::info!(expired, "expired stale reservations"),
+            Err(e) => tracing::warn!(error = ?e, "reservation sweep failed"),
+        }
+    }
+}
+
+
+// Holds stock for a later issue. The item row is locked for the duration, the
+// same lock every stock movement takes, so concurrent reservations and
+// movements cannot both claim the last units.
+#[utoipa::path(
+    post,
+    path = "/api/item/{id}/reservations",
+    tag = "reservations",
+    summary = "Reserve stock of an item",
+    params(("id" = i32, Path, description = "Item id")),
+    request_body = CreateReservationModel,
+    responses(
+        (status = 201, description = "Stock reserved", body = ReservationModel),
+        (status = 404, description = "Item or location not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Not enough unreserved stock at the location", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_reservation(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(settings): Extension<ReservationSettings>,
+    claims: Claims,
+    Path(id): Path<i32>,
+    Json(payload): Json<CreateReservationModel>,
+) -> AppResult<impl IntoResponse> {
+    if payload.quantity <= 0 {
+        return Err(AppError::Validation("quantity must be positive".to_string()));
+    }
+    let ttl_secs = payload.ttl_secs.unwrap_or(settings.default_ttl_secs);
+    if ttl_secs == 0 || ttl_secs > settings.max_ttl_secs {
+        return Err(AppError::Validation(format!(
+            "ttl_secs must be between 1 and {}",
+            settings.max_ttl_secs
+        )));
+    }
+
+    let txn = db.begin().await?;
+    lock_live_item(&txn, id).await?;
+    let location_id = resolve_location(&txn, payload.location_id).await?;
+
+    let on_hand = StockLevelEntity::find()
+        .filter(stock_level::Column::ItemId.eq(id))
+        .filter(stock_level::Column::LocationId.eq(location_id))
+        .one(&txn)
+        .await?
+        .map_or(0, |level| level.quantity);
+    let available = reservations::available(on_hand, held_quantity(&txn, id, location_id).await?);
+    if payload.quantity > available {
+        return Err(AppError::InsufficientStock {
+            item_id: id,
+            available: available.max(0),
+            requested: payload.quantity,
+        });
+    }
+
+    let now = Utc::now().fixed_offset();
+    let inserted = reservation::ActiveModel {
+        item_id: Set(id),
+        location_id: Set(location_id),
+        quantity: Set(payload.quantity),
+        status: Set(ReservationStatus::Held),
+        actor: Set(claims.sub),
+        reference: Set(payload.reference),
+        expires_at: Set(now + chrono::Duration::seconds(ttl_secs as i64)),
+        created_at: Set(now),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    audit_reservation(&txn, &inserted.actor, AuditAction::Create, None, &inserted).await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(ReservationModel::from(inserted))))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/reservation/{id}",
+    tag = "reservations",
+    summary = "Get a reservation",
+    params(("id" = i32, Path, description = "Reservation id")),
+    responses(
+        (status = 200, description = "The reservation", body = ReservationModel),
+        (status = 404, description = "Reservation not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_reservation(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let found = ReservationEntity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("reservation", id))?;
+
+    Ok((StatusCode::OK, Json(ReservationModel::from(found))))
+}
+
+
+// Query parameters accepted by the reservation list endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReservationListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub status: Option<ReservationStatus>,
+    pub location_id: Option<i32>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/item/{id}/reservations",
+    tag = "reservations",
+    summary = "List the reservations of an item",
+    params(("id" = i32, Path, description = "Item id"), ReservationListQuery),
+    responses(
+        (status = 200, description = "A page of reservations", body = Page<ReservationModel>),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_item_reservations(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Path(id): Path<i32>,
+    Query(query): Query<ReservationListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+
+    ItemEntity::find_live_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+
+    let mut select = ReservationEntity::find().filter(Column::ItemId.eq(id));
+    if let Some(status) = query.status {
+        select = select.filter(Column::Status.eq(status));
+    }
+    if let Some(location_id) = query.location_id {
+        select = select.filter(Column::LocationId.eq(location_id));
+    }
+    let select = apply_sort(select, query.sort.as_deref(), Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    Ok((StatusCode::OK, Json(page.map(ReservationModel::from))))
+}
+
+
+// Turns a held reservation into an issue movement. The reservation is marked
+// confirmed first so its units stop counting as held when the movement checks
+// availability.
+#[utoipa::path(
+    post,
+    path = "/api/reservation/{id}/confirm",
+    tag = "reservations",
+    summary = "Confirm a reservation and issue its stock",
+    params(("id" = i32, Path, description = "Reservation id")),
+    responses(
+        (status = 200, description = "Reservation confirmed", body = ConfirmedReservationModel),
+        (status = 404, description = "Reservation not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Reservation is no longer held", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn confirm_reservation(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    // The item is locked before the reservation, the order deleting the item
+    // takes them in, and the reservation is checked again under both locks
+    let item_id = ReservationEntity::find_by_id(id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("reservation", id))?
+        .item_id;
+    let existing_item = lock_live_item(&txn, item_id).await?;
+    let (held, status) = lock_held_reservation(&txn, id, Resolution::Confirm).await?;
+    let (location_id, quantity) = (held.location_id, held.quantity);
+
+    let mut active: reservation::ActiveModel = held.clone().into();
+    active.status = Set(status);
+    active.resolved_at = Set(Some(Utc::now().fixed_offset()));
+    let confirmed = active.update(&txn).await?;
+    audit_reservation(&txn, &claims.sub, AuditAction::Update, Some(&held), &confirmed).await?;
+
+    let (updated_item, movement) = apply_movement(
+        &txn,
+        item_id,
+        NewMovement {
+            kind: MovementKind::Issue,
+            location_id,
+            delta: -quantity,
+            reason_code: "reservation_confirmed".to_string(),
+            actor: claims.sub.clone(),
+            reference: Some(format!("reservation-{}", id)),
+        },
+    )
+    .await?;
+    audit_stock_change(&txn, &claims.sub, &existing_item, &updated_item).await?;
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ConfirmedReservationModel {
+            reservation: ReservationModel::from(confirmed),
+            item: ItemModel::from(updated_item),
+            movement: StockMovementModel::from(movement),
+        }),
+    ))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/reservation/{id}/release",
+    tag = "reservations",
+    summary = "Release a reservation without issuing stock",
+    params(("id" = i32, Path, description = "Reservation id")),
+    responses(
+        (status = 200, description = "Reservation released", body = ReservationModel),
+        (status = 404, description = "Reservation not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Reservation is no longer held", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn release_reservation(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    let (held, status) = lock_held_reservation(&txn, id, Resolution::Release).await?;
+
+    let mut active: reservation::ActiveModel = held.clone().into();
+    active.status = Set(status);
+    active.resolved_at = Set(Some(Utc::now().fixed_offset()));
+    let released = active.update(&txn).await?;
+    audit_reservation(&txn, &claims.sub, AuditAction::Update, Some(&held), &released).await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(ReservationModel::from(released))))
+}
+
+async fn audit_reservation<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    action: AuditAction,
+    before: Option<&reservation::Model>,
+    after: &reservation::Model,
+) -> AppResult<()> {
+    audit::record(txn, AuditEntry {
+        actor,
+        action,
+        entity_type: AuditEntityType::Reservation,
+        entity_key: after.id.to_string(),
+        before,
+        after: Some(after),
+    })
+    .await
+}
+
+// Locks a reservation that is still held and unexpired and returns the
+// status `resolution` moves it to. Anything else has already been resolved,
+// or is about to be by the sweeper.
+async fn lock_held_reservation<C: ConnectionTrait>(
+    txn: &C,
+    id: i32,
+    resolution: Resolution,
+) -> AppResult<(reservation::Model, ReservationStatus)> {
+    let found = ReservationEntity::find_by_id(id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("reservation", id))?;
+
+    let status = reservations::resolve(found.status, found.expires_at, resolution, Utc::now().fixed_offset())
+        .map_err(|reason| AppError::Conflict(format!("reservation '{}' {}", id, reason)))?;
+    Ok((found, status))
+}
@@ -0,0 +1,375 @@
+use axum::{extract::OriginalUri, http::StatusCode, response::IntoResponse, Extension};
+use chrono::Utc;
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::item::{self, Entity as ItemEntity};
+use entity::stock_level::{self, Entity as StockLevelEntity};
+use entity::stock_movement::{self, Column, Entity as StockMovementEntity, MovementKind};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QuerySelect, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::error::{AppError, AppResult, Problem};
+use crate::extract::{Json, Path, Query};
+use crate::handlers::reservation_handlers::held_quantity;
+use crate::handlers::warehouse_handlers::resolve_location;
+use crate::models::item_model::ItemModel;
+use crate::models::stock_movement_model::{
+    AppliedMovementModel, AppliedTransferModel, CreateStockMovementModel, StockMovementModel,
+};
+use crate::models::warehouse_model::TransferStockModel;
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+use crate::reservations;
+
+pub struct NewMovement {
+    pub kind: MovementKind,
+    pub location_id: i32,
+    pub delta: i32,
+    pub reason_code: String,
+    pub actor: String,
+    pub reference: Option<String>,
+}
+
+// Locks the item row, applies the movement to the stock level at its location
+// and to the item total, and appends the ledger entry. Must run inside a
+// transaction so all writes land together. Holding the item lock serializes
+// every stock change for that item, including stock level inserts.
+pub async fn apply_movement<C: ConnectionTrait>(
+    txn: &C,
+    item_id: i32,
+    movement: NewMovement,
+) -> AppResult<(item::Model, stock_movement::Model)> {
+    validate_movement(&movement)?;
+
+    let existing_item = lock_live_item(txn, item_id).await?;
+
+    let overflow = || AppError::Validation("delta overflows the item quantity".to_string());
+    let quantity_after = existing_item.quantity.checked_add(movement.delta).ok_or_else(overflow)?;
+
+    let mut active_item: item::ActiveModel = existing_item.into();
+    active_item.quantity = Set(quantity_after);
+    let updated_item = active_item.update(txn).await?;
+
+    let inserted_movement = record_movement(txn, item_id, movement, quantity_after, true).await?;
+
+    Ok((updated_item, inserted_movement))
+}
+
+// Locks the item row, the lock every change to its stock takes first
+pub async fn lock_live_item<C: ConnectionTrait>(txn: &C, item_id: i32) -> AppResult<item::Model> {
+    ItemEntity::find_live_by_id(item_id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", item_id))
+}
+
+// Logs a change to the item's stock in the same transaction. `before` is the
+// item as locked ahead of the first movement, so a transfer's pair of
+// movements is one entry.
+pub async fn audit_stock_change<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    before: &item::Model,
+    after: &item::Model,
+) -> AppResult<()> {
+    audit::record(txn, AuditEntry {
+        actor,
+        action: AuditAction::Adjust,
+        entity_type: AuditEntityType::Item,
+        entity_key: after.id.to_string(),
+        before: Some(before),
+        after: Some(after),
+    })
+    .await
+}
+
+// Applies a movement to the stock level at its location and appends the
+// ledger entry. The caller has already locked the item row and changed its
+// total to `quantity_after`. With `enforce_available` the level may not drop
+// below the units held by live reservations.
+pub async fn record_movement<C: ConnectionTrait>(
+    txn: &C,
+    item_id: i32,
+    movement: NewMovement,
+    quantity_after: i32,
+    enforce_available: bool,
+) -> AppResult<stock_movement::Model> {
+    let existing_level = StockLevelEntity::find()
+        .filter(stock_level::Column::ItemId.eq(item_id))
+        .filter(stock_level::Column::LocationId.eq(movement.location_id))
+        .one(txn)
+        .await?;
+    let on_hand = existing_level.as_ref().map_or(0, |level| level.quantity);
+
+    let level_after = on_hand
+        .checked_add(movement.delta)
+        .ok_or_else(|| AppError::Validation("delta overflows the location quantity".to_string()))?;
+
+    // Stock held by live reservations cannot be taken out by other movements
+    if enforce_available && movement.delta < 0 {
+        let held = held_quantity(txn, item_id, movement.location_id).await?;
+        if level_after < held {
+            return Err(AppError::InsufficientStock {
+                item_id,
+                available: reservations::available(on_hand, held).max(0),
+                requested: -movement.delta,
+            });
+        }
+    }
+
+    match existing_level {
+        Some(level) => {
+            let mut active_level: stock_level::ActiveModel = level.into();
+            active_level.quantity = Set(level_after);
+            active_level.update(txn).await?;
+        }
+        None => {
+            stock_level::ActiveModel {
+                item_id: Set(item_id),
+                location_id: Set(movement.location_id),
+                quantity: Set(level_after),
+                ..Default::default()
+            }
+            .insert(txn)
+            .await?;
+        }
+    }
+
+    let inserted_movement = stock_movement::ActiveModel {
+        item_id: Set(item_id),
+        location_id: Set(movement.location_id),
+        kind: Set(movement.kind),
+        reason_code: Set(movement.reason_code),
+        delta: Set(movement.delta),
+        quantity_after: Set(quantity_after),
+        actor: Set(movement.actor),
+        reference: Set(movement.reference),
+        created_at: Set(Utc::now().fixed_offset()),
+        ..Default::default()
+    }
+    .insert(txn)
+    .await?;
+
+    Ok(inserted_movement)
+}
+
+pub fn validate_movement(movement: &NewMovement) -> AppResult<()> {
+    if movement.delta == 0 {
+        return Err(AppError::Validation("delta must not be zero".to_string()));
+    }
+    // Shortfalls are reported as the negated delta, which i32::MIN has not
+    if movement.delta == i32::MIN {
+        return Err(AppError::Validation(format!("delta must be greater than {}", i32::MIN)));
+    }
+    match movement.kind {
+        MovementKind::Receipt if movement.delta < 0 => {
+            return Err(AppError::Validation("a receipt must have a positive delta".to_string()));
+        }
+        MovementKind::Issue if movement.delta > 0 => {
+            return Err(AppError::Validation("an issue must have a negative delta".to_string()));
+        }
+        _ => {}
+    }
+    if movement.reason_code.trim().is_empty() {
+        return Err(AppError::Validation("reason_code must not be empty".to_string()));
+    }
+    if movement.actor.trim().is_empty() {
+        return Err(AppError::Validation("actor must not be empty".to_string()));
+    }
+    Ok(())
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/item/{id}/movements",
+    tag = "stock",
+    summary = "Apply a stock movement to an item",
+    params(("id" = i32, Path, description = "Item id")),
+    request_body = CreateStockMovementModel,
+    responses(
+        (status = 201, description = "Movement applied", body = AppliedMovementModel),
+        (status = 404, description = "Item or location not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Not enough stock at the location", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_stock_movement(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+    Json(payload): Json<CreateStockMovementModel>,
+) -> AppResult<impl IntoResponse> {
+    if payload.kind == MovementKind::Transfer {
+        return Err(AppError::Validation(
+            "transfers move stock between locations; use the transfers endpoint".to_string(),
+        ));
+    }
+
+    let txn = db.begin().await?;
+    let existing_item = lock_live_item(&txn, id).await?;
+    let movement = NewMovement {
+        kind: payload.kind,
+        location_id: resolve_location(&txn, payload.location_id).await?,
+        delta: payload.delta,
+        reason_code: payload.reason_code,
+        actor: claims.sub.clone(),
+        reference: payload.reference,
+    };
+    let (updated_item, inserted_movement) = apply_movement(&txn, id, movement).await?;
+    audit_stock_change(&txn, &claims.sub, &existing_item, &updated_item).await?;
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AppliedMovementModel {
+            item: ItemModel::from(updated_item),
+            movement: StockMovementModel::from(inserted_movement),
+        }),
+    ))
+}
+
+
+// Moves stock of one item between two locations. The item total is unchanged;
+// the ledger gets a matching pair of transfer entries sharing a reference.
+#[utoipa::path(
+    post,
+    path = "/api/item/{id}/transfers",
+    tag = "stock",
+    summary = "Move item stock between locations",
+    params(("id" = i32, Path, description = "Item id")),
+    request_body = TransferStockModel,
+    responses(
+        (status = 201, description = "Transfer applied", body = AppliedTransferModel),
+        (status = 404, description = "Item or location not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Not enough stock at the source location", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_stock_transfer(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+    Json(payload): Json<TransferStockModel>,
+) -> AppResult<impl IntoResponse> {
+    if payload.quantity <= 0 {
+        return Err(AppError::Validation("quantity must be positive".to_string()));
+    }
+    if payload.from_location_id == payload.to_location_id {
+        return Err(AppError::Validation(
+            "from_location_id and to_location_id must differ".to_string(),
+        ));
+    }
+
+    let reference = payload
+        .reference
+        .unwrap_or_else(|| format!("transfer-{}", Uuid::new_v4()));
+
+    let txn = db.begin().await?;
+    let existing_item = lock_live_item(&txn, id).await?;
+    let from_location_id = resolve_location(&txn, Some(payload.from_location_id)).await?;
+    let to_location_id = resolve_location(&txn, Some(payload.to_location_id)).await?;
+
+    let (_, outbound) = apply_movement(
+        &txn,
+        id,
+        NewMovement {
+            kind: MovementKind::Transfer,
+            location_id: from_location_id,
+            delta: -payload.quantity,
+            reason_code: payload.reason_code.clone(),
+            actor: claims.sub.clone(),
+            reference: Some(reference.clone()),
+        },
+    )
+    .await?;
+    let (updated_item, inbound) = apply_movement(
+        &txn,
+        id,
+        NewMovement {
+            kind: MovementKind::Transfer,
+            location_id: to_location_id,
+            delta: payload.quantity,
+            reason_code: payload.reason_code,
+            actor: claims.sub.clone(),
+            reference: Some(reference),
+        },
+    )
+    .await?;
+    audit_stock_change(&txn, &claims.sub, &existing_item, &updated_item).await?;
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AppliedTransferModel {
+            item: ItemModel::from(updated_item),
+            movements: vec![
+                StockMovementModel::from(outbound),
+                StockMovementModel::from(inbound),
+            ],
+        }),
+    ))
+}
+
+
+// Query parameters accepted by the movement history endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StockMovementListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub kind: Option<MovementKind>,
+    pub location_id: Option<i32>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/item/{id}/movements",
+    tag = "stock",
+    summary = "List the stock movements of an item",
+    params(("id" = i32, Path, description = "Item id"), StockMovementListQuery),
+    responses(
+        (status = 200, description = "A page of movements", body = Page<StockMovementModel>),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_stock_movements(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Path(id): Path<i32>,
+    Query(query): Query<StockMovementListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+
+    ItemEntity::find_live_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+
+    let mut select = StockMovementEntity::find().filter(Column::ItemId.eq(id));
+    if let Some(kind) = query.kind {
+        select = select.filter(Column::Kind.eq(kind));
+    }
+    if let Some(location_id) = query.location_id {
+        select = select.filter(Column::LocationId.eq(location_id));
+    }
+    let select = apply_sort(select, query.sort.as_deref(), Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    Ok((StatusCode::OK, Json(page.map(StockMovementModel::from))))
+}
@@ -0,0 +1,259 @@
+use std::time::Duration;
+
+use axum::{extract::OriginalUri, http::StatusCode, response::IntoResponse, Extension};
+use chrono::{DateTime, FixedOffset, Utc};
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::item::{self, Entity as ItemEntity};
+use entity::product::{self, Entity as ProductEntity};
+use migration::Query as SelectQuery;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QuerySelect, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::error::{AppError, AppResult, Problem};
+use crate::etag::etag_header;
+use crate::extract::{Json, Path, Query};
+use crate::models::item_model::ItemModel;
+use crate::models::product_model::ProductModel;
+use crate::models::trash_model::{TrashEntryModel, TrashResource};
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+
+// Newest deletions first unless the caller sorts otherwise
+const DEFAULT_TRASH_SORT: &str = "-deleted_at";
+
+// Query parameters accepted by the trash listing
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TrashListQuery {
+    pub resource: TrashResource,
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/trash",
+    tag = "trash",
+    summary = "List soft deleted products or items",
+    params(TrashListQuery),
+    responses(
+        (status = 200, description = "A page of deleted rows", body = Page<TrashEntryModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_trash(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<TrashListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    let sort = Some(query.sort.as_deref().unwrap_or(DEFAULT_TRASH_SORT));
+
+    let page = match query.resource {
+        TrashResource::Product => {
+            let select = ProductEntity::find().filter(product::Column::DeletedAt.is_not_null());
+            let select = apply_sort(select, sort, product::Column::Id)?;
+            fetch_page(&db, select, params, &uri).await?.map(TrashEntryModel::from)
+        }
+        TrashResource::Item => {
+            let select = ItemEntity::find().filter(item::Column::DeletedAt.is_not_null());
+            let select = apply_sort(select, sort, item::Column::Id)?;
+            fetch_page(&db, select, params, &uri).await?.map(TrashEntryModel::from)
+        }
+    };
+
+    Ok((StatusCode::OK, Json(page)))
+}
+
+
+// Brings a product back together with the items that were deleted along
+// with it. Items deleted on their own before that stay in the trash.
+#[utoipa::path(
+    post,
+    path = "/api/product/{uuid}/restore",
+    tag = "trash",
+    summary = "Restore a deleted product",
+    params(("uuid" = Uuid, Path, description = "Product UUID")),
+    responses(
+        (status = 200, description = "Product restored", body = ProductModel,
+            headers(("ETag" = String, description = "New version of the product"))),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The product is not in the trash", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn restore_product(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(uuid): Path<Uuid>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    let existing_product = ProductEntity::find()
+        .filter(product::Column::Uuid.eq(uuid))
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))?;
+    let Some(deleted_at) = existing_product.deleted_at else {
+        return Err(AppError::Conflict(format!("product '{}' is not in the trash", uuid)));
+    };
+
+    let restored_items = ItemEntity::update_many()
+        .col_expr(item::Column::DeletedAt, Expr::value(Option::<DateTime<FixedOffset>>::None))
+        .col_expr(item::Column::Version, Expr::col(item::Column::Version).add(1))
+        .col_expr(item::Column::UpdatedAt, Expr::current_timestamp().into())
+        .filter(item::Column::ProductId.eq(existing_product.id))
+        .filter(item::Column::DeletedAt.eq(deleted_at))
+        .exec_with_returning(&txn)
+        .await?;
+    for after in &restored_items {
+        let before = item::Model {
+            deleted_at: Some(deleted_at),
+            version: after.version - 1,
+            ..after.clone()
+        };
+        audit::record(&txn, AuditEntry {
+            actor: &claims.sub,
+            action: AuditAction::Restore,
+            entity_type: AuditEntityType::Item,
+            entity_key: after.id.to_string(),
+            before: Some(&before),
+            after: Some(after),
+        })
+        .await?;
+    }
+
+    let mut active_model: product::ActiveModel = existing_product.clone().into();
+    active_model.deleted_at = Set(None);
+    let restored = active_model.update(&txn).await?;
+    audit::record(&txn, AuditEntry {
+        actor: &claims.sub,
+        action: AuditAction::Restore,
+        entity_type: AuditEntityType::Product,
+        entity_key: uuid.to_string(),
+        before: Some(&existing_product),
+        after: Some(&restored),
+    })
+    .await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, [etag_header(restored.version)], Json(ProductModel::from(restored))))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/item/{id}/restore",
+    tag = "trash",
+    summary = "Restore a deleted item",
+    params(("id" = i32, Path, description = "Item id")),
+    responses(
+        (status = 200, description = "Item restored", body = ItemModel,
+            headers(("ETag" = String, description = "New version of the item"))),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "The item is not in the trash, or its product is", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn restore_item(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let txn = db.begin().await?;
+    let existing_item = ItemEntity::find_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+    if existing_item.deleted_at.is_none() {
+        return Err(AppError::Conflict(format!("item '{}' is not in the trash", id)));
+    }
+
+    // Share-lock the product so it cannot be deleted while the item returns
+    ProductEntity::find_live()
+        .filter(product::Column::Id.eq(existing_item.product_id))
+        .lock_shared()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| {
+            AppError::Conflict(format!(
+                "the product of item '{}' is in the trash; restore the product first",
+                id
+            ))
+        })?;
+
+    let mut active_model: item::ActiveModel = existing_item.clone().into();
+    active_model.deleted_at = Set(None);
+    let restored = active_model.update(&txn).await?;
+    audit::record(&txn, AuditEntry {
+        actor: &claims.sub,
+        action: AuditAction::Restore,
+        entity_type: AuditEntityType::Item,
+        entity_key: id.to_string(),
+        before: Some(&existing_item),
+        after: Some(&restored),
+    })
+    .await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, [etag_header(restored.version)], Json(ItemModel::from(restored))))
+}
+
+
+// Permanently deletes items and products that have been in the trash longer
+// than `retention`. Their stock levels, ledger entries and reservations go
+// with them through the foreign key cascades.
+pub async fn purge_trash(db: &DatabaseConnection, retention: Duration) -> Result<(u64, u64), DbErr> {
+    let cutoff = chrono::Duration::from_std(retention)
+        .ok()
+        .and_then(|retention| Utc::now().fixed_offset().checked_sub_signed(retention))
+        .ok_or_else(|| DbErr::Custom(format!("retention of {:?} is out of range", retention)))?;
+
+    let txn = db.begin().await?;
+    let items = ItemEntity::delete_many()
+        .filter(item::Column::DeletedAt.lt(cutoff))
+        .exec(&txn)
+        .await?;
+    // A product still referenced by a younger deleted item waits for it
+    let products = ProductEntity::delete_many()
+        .filter(product::Column::DeletedAt.lt(cutoff))
+        .filter(
+            product::Column::Id.not_in_subquery(
+                SelectQuery::select()
+                    .column(item::Column::ProductId)
+                    .from(item::Entity)
+                    .to_owned(),
+            ),
+        )
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+
+    Ok((products.rows_affected, items.rows_affected))
+}
+
+// Runs `purge_trash` every `interval` until the task is aborted
+pub async fn run_purger(db: DatabaseConnection, retention: Duration, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match purge_trash(&db, retention).await {
+            Ok((0, 0)) => {}
+            Ok((products, items)) => tracing::info!(products, items, "purged expired trash"),
+            Err(e) => tracing::warn!(error = ?e, "trash purge failed"),
+        }
+    }
+}
@@ -0,0 +1,582 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use axum::{
+    extract::OriginalUri,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension,
+};
+use entity::attribute::{self, Entity as AttributeEntity};
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::item::{self, Entity as ItemEntity};
+use entity::item_attribute_value::{self, Entity as ItemAttributeValueEntity};
+use entity::product::{self, Entity as ProductEntity};
+use sea_orm::sea_query::{Expr, Func};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::config::SkuSettings;
+use crate::error::{AppError, AppResult, Problem};
+use crate::etag::{check_if_match, etag_header};
+use crate::extract::{Json, Path, Query};
+use crate::handlers::item_handlers::{insert_item, NewItem};
+use crate::models::item_model::ProductItemModel;
+use crate::models::variant_model::{
+    AttributeModel, CreateAttributeModel, GenerateVariantsModel, GeneratedVariantsModel,
+    ItemAttributesModel, SetItemAttributesModel, VariantModel,
+};
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+use crate::variants::{cartesian, normalize_value, typed_value, variant_key, MAX_GENERATED_VARIANTS};
+
+async fn find_attribute<C: ConnectionTrait>(conn: &C, id: i32) -> AppResult<attribute::Model> {
+    AttributeEntity::find_by_id(id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| AppError::not_found("attribute", id))
+}
+
+// Looks attributes up by name, ignoring case like the unique index does.
+// Fails on the first name that does not exist.
+async fn find_attributes_by_name<C: ConnectionTrait>(
+    conn: &C,
+    names: &[&str],
+) -> AppResult<HashMap<String, attribute::Model>> {
+    let lowered: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+    let by_name: HashMap<String, attribute::Model> = AttributeEntity::find()
+        .filter(Expr::expr(Func::lower(Expr::col(attribute::Column::Name))).is_in(lowered))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|a| (a.name.to_lowercase(), a))
+        .collect();
+
+    match names.iter().find(|name| !by_name.contains_key(&name.to_lowercase())) {
+        Some(missing) => Err(AppError::not_found("attribute", missing)),
+        None => Ok(by_name),
+    }
+}
+
+// Two keys differing only in case name the same attribute
+fn reject_repeated_names<'a>(names: impl IntoIterator<Item = &'a str>) -> AppResult<()> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name.to_lowercase()) {
+            return Err(AppError::Validation(format!("attribute '{}' is given more than once", name)));
+        }
+    }
+    Ok(())
+}
+
+// Normalizes a value for the attribute and checks it is one of the allowed
+// values, if the attribute has a list
+fn attribute_value(attribute: &attribute::Model, value: &Value) -> AppResult<String> {
+    let normalized = normalize_value(attribute.data_type, value)
+        .map_err(|e| AppError::Validation(format!("attribute '{}': {}", attribute.name, e)))?;
+
+    let allowed: Option<Vec<&str>> = attribute
+        .allowed_values
+        .as_ref()
+        .and_then(Value::as_array)
+        .map(|values| values.iter().filter_map(Value::as_str).collect());
+    match allowed {
+        Some(allowed) if !allowed.contains(&normalized.as_str()) => Err(AppError::Validation(format!(
+            "attribute '{}' does not allow '{}'; allowed values are {}",
+            attribute.name,
+            normalized,
+            allowed.join(", ")
+        ))),
+        _ => Ok(normalized),
+    }
+}
+
+// Loads the attribute values of the given items in a single query, keyed by
+// item id and then by attribute name
+async fn load_item_attributes<C: ConnectionTrait>(
+    conn: &C,
+    item_ids: &[i32],
+) -> AppResult<HashMap<i32, BTreeMap<String, Value>>> {
+    let rows = ItemAttributeValueEntity::find()
+        .find_also_related(AttributeEntity)
+        .filter(item_attribute_value::Column::ItemId.is_in(item_ids.iter().copied()))
+        .all(conn)
+        .await?;
+
+    let mut by_item: HashMap<i32, BTreeMap<String, Value>> = HashMap::new();
+    for (value, attribute) in rows {
+        if let Some(attribute) = attribute {
+            by_item
+                .entry(value.item_id)
+                .or_default()
+                .insert(attribute.name, typed_value(attribute.data_type, &value.value));
+        }
+    }
+    Ok(by_item)
+}
+
+async fn insert_item_attributes<C: ConnectionTrait>(
+    conn: &C,
+    item_id: i32,
+    values: &[(i32, String)],
+) -> AppResult<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+    ItemAttributeValueEntity::insert_many(values.iter().map(|(attribute_id, value)| {
+        item_attribute_value::ActiveModel {
+            item_id: Set(item_id),
+            attribute_id: Set(*attribute_id),
+            value: Set(value.clone()),
+        }
+    }))
+    .exec(conn)
+    .await?;
+    Ok(())
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/attributes",
+    tag = "variants",
+    summary = "Define a variant attribute",
+    request_body = CreateAttributeModel,
+    responses(
+        (status = 201, description = "Attribute created", body = AttributeModel),
+        (status = 409, description = "An attribute with this name already exists", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request, or an allowed value does not match the type", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_attribute(
+    Extension(db): Extension<DatabaseConnection>,
+    Json(payload): Json<CreateAttributeModel>,
+) -> AppResult<impl IntoResponse> {
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::Validation("name must not be empty".to_string()));
+    }
+
+    let allowed_values = match payload.allowed_values {
+        Some(values) if values.is_empty() => {
+            return Err(AppError::Validation(
+                "allowed_values must not be empty; leave it out to accept any value".to_string(),
+            ))
+        }
+        Some(values) => {
+            let mut normalized: Vec<String> = Vec::with_capacity(values.len());
+            for value in &values {
+                let value = normalize_value(payload.data_type, value)
+                    .map_err(|e| AppError::Validation(format!("allowed_values: {}", e)))?;
+                if !normalized.contains(&value) {
+                    normalized.push(value);
+                }
+            }
+            Some(Value::from(normalized))
+        }
+        None => None,
+    };
+
+    let inserted_attribute = attribute::ActiveModel {
+        name: Set(name),
+        data_type: Set(payload.data_type),
+        allowed_values: Set(allowed_values),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(AttributeModel::from(inserted_attribute))))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/attributes",
+    tag = "variants",
+    summary = "List variant attributes",
+    responses(
+        (status = 200, description = "Every attribute, sorted by name", body = Vec<AttributeModel>),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_attributes(
+    Extension(db): Extension<DatabaseConnection>,
+) -> AppResult<impl IntoResponse> {
+    let attributes: Vec<AttributeModel> = AttributeEntity::find()
+        .order_by_asc(attribute::Column::Name)
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(AttributeModel::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(attributes)))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/attributes/{id}",
+    tag = "variants",
+    summary = "Get a variant attribute",
+    params(("id" = i32, Path, description = "Attribute id")),
+    responses(
+        (status = 200, description = "The attribute", body = AttributeModel),
+        (status = 404, description = "Attribute not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_attribute(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let attribute = find_attribute(&db, id).await?;
+    Ok((StatusCode::OK, Json(AttributeModel::from(attribute))))
+}
+
+
+#[utoipa::path(
+    delete,
+    path = "/api/attributes/{id}",
+    tag = "variants",
+    summary = "Delete an unused variant attribute",
+    params(("id" = i32, Path, description = "Attribute id")),
+    responses(
+        (status = 204, description = "Attribute deleted"),
+        (status = 404, description = "Attribute not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Items still have a value for the attribute", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_attribute(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let attribute = find_attribute(&db, id).await?;
+
+    // Items in the trash count too, since they can be restored
+    let in_use = ItemAttributeValueEntity::find()
+        .filter(item_attribute_value::Column::AttributeId.eq(id))
+        .count(&db)
+        .await?;
+    if in_use > 0 {
+        return Err(AppError::Conflict(format!(
+            "attribute '{}' is set on {} item(s); clear it from them first",
+            attribute.name, in_use
+        )));
+    }
+
+    AttributeEntity::delete_by_id(id).exec(&db).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/item/{id}/attributes",
+    tag = "variants",
+    summary = "Get the attribute values of an item",
+    params(("id" = i32, Path, description = "Item id")),
+    responses(
+        (status = 200, description = "The item's attribute values", body = ItemAttributesModel,
+            headers(("ETag" = String, description = "Current version of the item"))),
+        (status = 404, description = "Item not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_item_attributes(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let item = ItemEntity::find_live_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+    let attributes = load_item_attributes(&db, &[id]).await?.remove(&id).unwrap_or_default();
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(item.version)],
+        Json(ItemAttributesModel { item_id: id, attributes }),
+    ))
+}
+
+
+#[utoipa::path(
+    put,
+    path = "/api/item/{id}/attributes",
+    tag = "variants",
+    summary = "Replace the attribute values of an item",
+    params(
+        ("id" = i32, Path, description = "Item id"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the item still has this ETag"),
+    ),
+    request_body = SetItemAttributesModel,
+    responses(
+        (status = 200, description = "Attribute values replaced", body = ItemAttributesModel,
+            headers(("ETag" = String, description = "New version of the item"))),
+        (status = 404, description = "Item or attribute not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Another item of the product has the same values", body = Problem, content_type = "application/problem+json"),
+        (status = 412, description = "The item no longer matches If-Match", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "A value does not match its attribute", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn set_item_attributes(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+    Json(payload): Json<SetItemAttributesModel>,
+) -> AppResult<impl IntoResponse> {
+    reject_repeated_names(payload.attributes.keys().map(String::as_str))?;
+
+    let txn = db.begin().await?;
+    let existing_item = ItemEntity::find_live_by_id(id)
+        .lock_exclusive()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("item", id))?;
+    check_if_match(&headers, existing_item.version)?;
+
+    let names: Vec<&str> = payload.attributes.keys().map(String::as_str).collect();
+    let attributes = find_attributes_by_name(&txn, &names).await?;
+    let mut values: Vec<(i32, String)> = Vec::with_capacity(names.len());
+    for (name, value) in &payload.attributes {
+        let attribute = &attributes[&name.to_lowercase()];
+        values.push((attribute.id, attribute_value(attribute, value)?));
+    }
+
+    // The key covers every value, so an unchanged key means nothing to do
+    let key = variant_key(&values);
+    let mut updated_item = existing_item.clone();
+    if key != existing_item.variant_key {
+        if let Some(key) = &key {
+            let duplicate = ItemEntity::find_live()
+                .filter(item::Column::ProductId.eq(existing_item.product_id))
+                .filter(item::Column::VariantKey.eq(key.as_str()))
+                .filter(item::Column::Id.ne(id))
+                .one(&txn)
+                .await?;
+            if let Some(duplicate) = duplicate {
+                return Err(AppError::Conflict(format!(
+                    "item '{}' of the same product already has these attribute values",
+                    duplicate.id
+                )));
+            }
+        }
+
+        ItemAttributeValueEntity::delete_many()
+            .filter(item_attribute_value::Column::ItemId.eq(id))
+            .exec(&txn)
+            .await?;
+        insert_item_attributes(&txn, id, &values).await?;
+
+        let mut active_model = existing_item.clone().into_active_model();
+        active_model.variant_key = Set(key);
+        updated_item = active_model.update(&txn).await?;
+        audit::record(&txn, AuditEntry {
+            actor: &claims.sub,
+            action: AuditAction::Update,
+            entity_type: AuditEntityType::Item,
+            entity_key: id.to_string(),
+            before: Some(&existing_item),
+            after: Some(&updated_item),
+        })
+        .await?;
+    }
+
+    let attributes = load_item_attributes(&txn, &[id]).await?.remove(&id).unwrap_or_default();
+    txn.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        [etag_header(updated_item.version)],
+        Json(ItemAttributesModel { item_id: id, attributes }),
+    ))
+}
+
+
+// Query parameters accepted by the variant list endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VariantListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/products/{uuid}/variants",
+    tag = "variants",
+    summary = "List the items of a product with their attribute values",
+    params(
+        ("uuid" = Uuid, Path, description = "Product UUID"),
+        VariantListQuery,
+    ),
+    responses(
+        (status = 200, description = "A page of the product's items", body = Page<VariantModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 404, description = "Product not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_product_variants(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Path(uuid): Path<Uuid>,
+    Query(query): Query<VariantListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    let product = ProductEntity::find_live()
+        .filter(product::Column::Uuid.eq(uuid))
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))?;
+
+    let select = ItemEntity::find_live().filter(item::Column::ProductId.eq(product.id));
+    let select = apply_sort(select, query.sort.as_deref(), item::Column::Id)?;
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    let item_ids: Vec<i32> = page.data.iter().map(|item| item.id).collect();
+    let mut attributes = load_item_attributes(&db, &item_ids).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(page.map(|item| VariantModel {
+            attributes: attributes.remove(&item.id).unwrap_or_default(),
+            item: ProductItemModel::new(item, uuid),
+        })),
+    ))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/products/{uuid}/variants/generate",
+    tag = "variants",
+    summary = "Create an item for every combination of the given attribute values",
+    params(("uuid" = Uuid, Path, description = "Product UUID")),
+    request_body = GenerateVariantsModel,
+    responses(
+        (status = 200, description = "Items created for the new combinations; existing ones are skipped", body = GeneratedVariantsModel),
+        (status = 404, description = "Product or attribute not found", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Another request created one of the combinations at the same time", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request, a value does not match its attribute, or too many combinations", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn generate_variants(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(skus): Extension<SkuSettings>,
+    claims: Claims,
+    Path(uuid): Path<Uuid>,
+    Json(payload): Json<GenerateVariantsModel>,
+) -> AppResult<impl IntoResponse> {
+    if payload.axes.is_empty() {
+        return Err(AppError::Validation("axes must name at least one attribute".to_string()));
+    }
+    reject_repeated_names(payload.axes.iter().map(|axis| axis.attribute.as_str()))?;
+
+    let txn = db.begin().await?;
+    // Share-lock the product so it cannot go to the trash without the items
+    let product = ProductEntity::find_live()
+        .filter(product::Column::Uuid.eq(uuid))
+        .lock_shared()
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("product", uuid))?;
+
+    let names: Vec<&str> = payload.axes.iter().map(|axis| axis.attribute.as_str()).collect();
+    let attributes = find_attributes_by_name(&txn, &names).await?;
+
+    // Each axis becomes a list of (attribute, normalized value) with repeats
+    // dropped, in the order given
+    let mut axes: Vec<Vec<(&attribute::Model, String)>> = Vec::with_capacity(payload.axes.len());
+    for axis in &payload.axes {
+        let attribute = &attributes[&axis.attribute.to_lowercase()];
+        if axis.values.is_empty() {
+            return Err(AppError::Validation(format!(
+                "attribute '{}' needs at least one value",
+                attribute.name
+            )));
+        }
+        let mut values: Vec<(&attribute::Model, String)> = Vec::with_capacity(axis.values.len());
+        for value in &axis.values {
+            let value = attribute_value(attribute, value)?;
+            if !values.iter().any(|(_, seen)| *seen == value) {
+                values.push((attribute, value));
+            }
+        }
+        axes.push(values);
+    }
+    let combinations = cartesian(&axes, MAX_GENERATED_VARIANTS).ok_or_else(|| {
+        AppError::Validation(format!(
+            "the axes give more than {} combinations",
+            MAX_GENERATED_VARIANTS
+        ))
+    })?;
+
+    let mut existing_keys: HashSet<String> = ItemEntity::find_live()
+        .select_only()
+        .column(item::Column::VariantKey)
+        .filter(item::Column::ProductId.eq(product.id))
+        .filter(item::Column::VariantKey.is_not_null())
+        .into_tuple::<String>()
+        .all(&txn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut report = GeneratedVariantsModel {
+        created: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for combination in combinations {
+        let values: Vec<(i32, String)> =
+            combination.iter().map(|(attribute, value)| (attribute.id, value.clone())).collect();
+        let shown: BTreeMap<String, Value> = combination
+            .iter()
+            .map(|(attribute, value)| (attribute.name.clone(), typed_value(attribute.data_type, value)))
+            .collect();
+        let key = variant_key(&values).expect("every combination has a value per axis");
+        if !existing_keys.insert(key.clone()) {
+            report.skipped.push(shown);
+            continue;
+        }
+
+        let labels: Vec<&str> = combination.iter().map(|(_, value)| value.as_str()).collect();
+        let name = format!("{} - {}", product.name, labels.join(" / "));
+        let new_item = NewItem {
+            variant_key: Some(key),
+            ..NewItem::new(product.id, name, payload.quantity)
+        };
+        let inserted_item = insert_item(&txn, &claims.sub, &skus, new_item).await?;
+        insert_item_attributes(&txn, inserted_item.id, &values).await?;
+        report.created.push(VariantModel {
+            item: ProductItemModel::new(inserted_item, uuid),
+            attributes: shown,
+        });
+    }
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, Json(report)))
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use axum::{extract::OriginalUri, http::StatusCode, response::IntoResponse, Extension};
+use chrono::Utc;
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::location::{self, Entity as LocationEntity};
+use entity::stock_level::{self, Entity as StockLevelEntity};
+use entity::warehouse::{self, Entity as WarehouseEntity};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, JoinType,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::audit::{self, AuditEntry};
+use crate::auth::Claims;
+use crate::error::{AppError, AppResult, Problem};
+use crate::extract::{Json, Path, Query};
+use crate::models::warehouse_model::{
+    CreateLocationModel, CreateWarehouseModel, LocationModel, LocationStockModel, WarehouseModel,
+};
+use crate::pagination::{apply_sort, fetch_page, Page, PageParams};
+
+// Codes of the warehouse and bin that receive stock when no location is given
+pub const DEFAULT_WAREHOUSE_CODE: &str = "DEFAULT";
+pub const DEFAULT_LOCATION_CODE: &str = "DEFAULT";
+
+// Returns the given location after checking it exists, or the default
+// location when none was requested.
+pub async fn resolve_location<C: ConnectionTrait>(
+    conn: &C,
+    location_id: Option<i32>,
+) -> AppResult<i32> {
+    match location_id {
+        Some(id) => LocationEntity::find_by_id(id)
+            .one(conn)
+            .await?
+            .map(|l| l.id)
+            .ok_or_else(|| AppError::not_found("location", id)),
+        None => LocationEntity::find()
+            .join(JoinType::InnerJoin, location::Relation::Warehouse.def())
+            .filter(warehouse::Column::Code.eq(DEFAULT_WAREHOUSE_CODE))
+            .filter(location::Column::Code.eq(DEFAULT_LOCATION_CODE))
+            .one(conn)
+            .await?
+            .map(|l| l.id)
+            .ok_or_else(|| AppError::not_found("location", DEFAULT_LOCATION_CODE)),
+    }
+}
+
+// An absolute quantity is booked as the difference at the default location,
+// which only adds up while no other location holds any of the item's stock.
+// Otherwise the caller has to say where stock goes, through /adjust or a
+// movement.
+pub async fn check_stock_only_at<C: ConnectionTrait>(
+    conn: &C,
+    item_id: i32,
+    location_id: i32,
+) -> AppResult<()> {
+    let elsewhere = StockLevelEntity::find()
+        .filter(stock_level::Column::ItemId.eq(item_id))
+        .filter(stock_level::Column::LocationId.ne(location_id))
+        .filter(stock_level::Column::Quantity.ne(0))
+        .one(conn)
+        .await?;
+    if let Some(level) = elsewhere {
+        return Err(AppError::Conflict(format!(
+            "item '{}' has stock at location '{}', so its quantity cannot be set outright; \
+             use /api/item/{}/adjust or a stock movement with a location_id",
+            item_id, level.location_id, item_id
+        )));
+    }
+    Ok(())
+}
+
+// Loads the per-location stock of the given items in a single query, keyed
+// by item id.
+pub async fn load_location_stock<C: ConnectionTrait>(
+    conn: &C,
+    item_ids: &[i32],
+) -> AppResult<HashMap<i32, Vec<LocationStockModel>>> {
+    let rows = StockLevelEntity::find()
+        .select_only()
+        .column(stock_level::Column::ItemId)
+        .column(stock_level::Column::LocationId)
+        .column_as(location::Column::Code, "location_code")
+        .column(location::Column::WarehouseId)
+        .column_as(warehouse::Column::Code, "warehouse_code")
+        .column(stock_level::Column::Quantity)
+        .join(JoinType::InnerJoin, stock_level::Relation::Location.def())
+        .join(JoinType::InnerJoin, location::Relation::Warehouse.def())
+        .filter(stock_level::Column::ItemId.is_in(item_ids.iter().copied()))
+        .filter(stock_level::Column::Quantity.ne(0))
+        .order_by_asc(stock_level::Column::LocationId)
+        .into_model::<LocationStockModel>()
+        .all(conn)
+        .await?;
+
+    let mut by_item: HashMap<i32, Vec<LocationStockModel>> = HashMap::new();
+    for row in rows {
+        by_item.entry(row.item_id).or_default().push(row);
+    }
+    Ok(by_item)
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/warehouse",
+    tag = "warehouses",
+    summary = "Create a warehouse",
+    request_body = CreateWarehouseModel,
+    responses(
+        (status = 201, description = "Warehouse created", body = WarehouseModel),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Warehouse code already exists", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_warehouse(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Json(payload): Json<CreateWarehouseModel>,
+) -> AppResult<impl IntoResponse> {
+    if payload.code.trim().is_empty() {
+        return Err(AppError::Validation("code must not be empty".to_string()));
+    }
+
+    let txn = db.begin().await?;
+    let inserted_warehouse = warehouse::ActiveModel {
+        code: Set(payload.code),
+        name: Set(payload.name),
+        created_at: Set(Utc::now().fixed_offset()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    audit::record(&txn, AuditEntry {
+        actor: &claims.sub,
+        action: AuditAction::Create,
+        entity_type: AuditEntityType::Warehouse,
+        entity_key: inserted_warehouse.id.to_string(),
+        before: None,
+        after: Some(&inserted_warehouse),
+    })
+    .await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(WarehouseModel::from(inserted_warehouse))))
+}
+
+
+// Query parameters accepted by the warehouse list endpoint
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WarehouseListQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub sort: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/get_all_warehouses",
+    tag = "warehouses",
+    summary = "List warehouses",
+    params(WarehouseListQuery),
+    responses(
+        (status = 200, description = "A page of warehouses", body = Page<WarehouseModel>),
+        (status = 400, description = "Malformed query string", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid pagination or sort", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_all_warehouses(
+    Extension(db): Extension<DatabaseConnection>,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<WarehouseListQuery>,
+) -> AppResult<impl IntoResponse> {
+    let params = PageParams::new(query.page, query.limit)?;
+    let select = apply_sort(WarehouseEntity::find(), query.sort.as_deref(), warehouse::Column::Id)?;
+
+    let page = fetch_page(&db, select, params, &uri).await?;
+
+    Ok((StatusCode::OK, Json(page.map(WarehouseModel::from))))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/get_warehouse/{id}",
+    tag = "warehouses",
+    summary = "Get a warehouse",
+    params(("id" = i32, Path, description = "Warehouse id")),
+    responses(
+        (status = 200, description = "The warehouse", body = WarehouseModel),
+        (status = 404, description = "Warehouse not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_warehouse_by_id(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    let warehouse = WarehouseEntity::find_by_id(id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("warehouse", id))?;
+
+    Ok((StatusCode::OK, Json(WarehouseModel::from(warehouse))))
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/api/warehouse/{id}/locations",
+    tag = "warehouses",
+    summary = "Create a location in a warehouse",
+    params(("id" = i32, Path, description = "Warehouse id")),
+    request_body = CreateLocationModel,
+    responses(
+        (status = 201, description = "Location created", body = LocationModel),
+        (status = 404, description = "Warehouse not found", body = Problem, content_type = "application/problem+json"),
+        (status = 422, description = "Invalid request", body = Problem, content_type = "application/problem+json"),
+        (status = 409, description = "Location code already exists in the warehouse", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_location(
+    Extension(db): Extension<DatabaseConnection>,
+    claims: Claims,
+    Path(warehouse_id): Path<i32>,
+    Json(payload): Json<CreateLocationModel>,
+) -> AppResult<impl IntoResponse> {
+    if payload.code.trim().is_empty() {
+        return Err(AppError::Validation("code must not be empty".to_string()));
+    }
+
+    let txn = db.begin().await?;
+    WarehouseEntity::find_by_id(warehouse_id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::not_found("warehouse", warehouse_id))?;
+
+    let inserted_location = location::ActiveModel {
+        warehouse_id: Set(warehouse_id),
+        code: Set(payload.code),
+        name: Set(payload.name),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+    audit::record(&txn, AuditEntry {
+        actor: &claims.sub,
+        action: AuditAction::Create,
+        entity_type: AuditEntityType::Location,
+        entity_key: inserted_location.id.to_string(),
+        before: None,
+        after: Some(&inserted_location),
+    })
+    .await?;
+    txn.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(LocationModel::from(inserted_location))))
+}
+
+
+#[utoipa::path(
+    get,
+    path = "/api/warehouse/{id}/locations",
+    tag = "warehouses",
+    summary = "List the locations of a warehouse",
+    params(("id" = i32, Path, description = "Warehouse id")),
+    responses(
+        (status = 200, description = "The locations", body = Vec<LocationModel>),
+        (status = 404, description = "Warehouse not found", body = Problem, content_type = "application/problem+json"),
+        (status = 401, description = "Missing or invalid bearer token", body = Problem, content_type = "application/problem+json"),
+        (status = 403, description = "Token lacks the required role", body = Problem, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_locations(
+    Extension(db): Extension<DatabaseConnection>,
+    Path(warehouse_id): Path<i32>,
+) -> AppResult<impl IntoResponse> {
+    WarehouseEntity::find_by_id(warehouse_id)
+        .one(&db)
+        .await?
+        .ok_or_else(|| AppError::not_found("warehouse", warehouse_id))?;
+
+    let locations: Vec<LocationModel> = LocationEntity::find()
+        .filter(location::Column::WarehouseId.eq(warehouse_id))
+        .order_by_asc(location::Column::Id)
+        .all(&db)
+        .await?
+        .into_iter()
+        .map(LocationModel::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(locations)))
+}
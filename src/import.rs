@@ -0,0 +1,499 @@
+use std::collections::HashMap;
+use std::mem;
+
+use entity::audit_log::{AuditAction, AuditEntityType};
+use entity::item::{self, Entity as ItemEntity};
+use entity::product::{self, Entity as ProductEntity};
+use entity::stock_movement::MovementKind;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QuerySelect, Set, TransactionTrait,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEntry};
+use crate::config::{Config, ImportArgs, ImportFormat, SkuSettings};
+use crate::error::{AppError, AppResult};
+use crate::handlers::item_handlers::{insert_item, NewItem};
+use crate::handlers::stock_movement_handlers::{apply_movement, NewMovement};
+use crate::handlers::warehouse_handlers::{check_stock_only_at, resolve_location};
+use crate::models::import_model::{ImportReportModel, ImportRowErrorModel};
+
+// One line of an import file; CSV headers and NDJSON keys use these names.
+// A row names a product and optionally one of its items. Rows with the same
+// `product_uuid` (or, without one, the same `product_name`) are one product.
+#[derive(Debug, Deserialize)]
+pub struct ImportRow {
+    pub product_uuid: Option<Uuid>,
+    pub product_name: String,
+    #[serde(default)]
+    pub product_description: String,
+    pub item_name: Option<String>,
+    pub item_quantity: Option<i32>,
+}
+
+pub struct ImportOptions {
+    pub dry_run: bool,
+    // 0 imports everything in one transaction
+    pub chunk_size: usize,
+    // New items get SKUs from this pattern
+    pub skus: SkuSettings,
+}
+
+// A row with its 1-based line, or why it could not be read
+pub type ParsedRow = (u64, Result<ImportRow, String>);
+
+impl ImportFormat {
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let (_, extension) = name.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "ndjson" | "jsonl" => Some(ImportFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.split(';').next()?.trim() {
+            "text/csv" => Some(ImportFormat::Csv),
+            "application/x-ndjson" | "application/jsonl" => Some(ImportFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse(format: ImportFormat, data: &[u8]) -> Vec<ParsedRow> {
+    match format {
+        ImportFormat::Csv => parse_csv(data),
+        ImportFormat::Ndjson => parse_ndjson(data),
+    }
+}
+
+fn parse_csv(data: &[u8]) -> Vec<ParsedRow> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data);
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => return vec![(1, Err(csv_error_message(&e)))],
+    };
+
+    reader
+        .records()
+        .map(|record| match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, |p| p.line());
+                let row = record.deserialize(Some(&headers)).map_err(|e| csv_error_message(&e));
+                (line, row)
+            }
+            Err(e) => (e.position().map_or(0, |p| p.line()), Err(csv_error_message(&e))),
+        })
+        .collect()
+}
+
+// The line is reported separately, so only the cause is kept
+fn csv_error_message(e: &csv::Error) -> String {
+    match e.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => err.to_string(),
+        _ => e.to_string(),
+    }
+}
+
+fn parse_ndjson(data: &[u8]) -> Vec<ParsedRow> {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return vec![(1, Err("the file is not valid UTF-8".to_string()))];
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| ((i + 1) as u64, serde_json::from_str(line).map_err(|e| e.to_string())))
+        .collect()
+}
+
+struct ImportItem {
+    line: u64,
+    name: String,
+    quantity: Option<i32>,
+}
+
+// The rows of one product, checked against each other
+struct ProductGroup {
+    line: u64,
+    rows: usize,
+    uuid: Option<Uuid>,
+    name: String,
+    description: String,
+    items: Vec<ImportItem>,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum GroupKey {
+    Uuid(Uuid),
+    Name(String),
+}
+
+fn validate_row(row: &ImportRow) -> Result<(), String> {
+    if row.product_name.trim().is_empty() {
+        return Err("product_name must not be empty".to_string());
+    }
+    match (&row.item_name, row.item_quantity) {
+        (Some(name), _) if name.trim().is_empty() => Err("item_name must not be empty".to_string()),
+        (None, Some(_)) => Err("item_quantity needs an item_name".to_string()),
+        (_, Some(quantity)) if quantity < 0 => Err("item_quantity must not be negative".to_string()),
+        _ => Ok(()),
+    }
+}
+
+fn group_rows(rows: Vec<ParsedRow>, errors: &mut Vec<ImportRowErrorModel>) -> Vec<ProductGroup> {
+    let mut groups: Vec<ProductGroup> = Vec::new();
+    let mut index: HashMap<GroupKey, usize> = HashMap::new();
+
+    for (line, row) in rows {
+        let row = match row.and_then(|row| validate_row(&row).map(|_| row)) {
+            Ok(row) => row,
+            Err(message) => {
+                errors.push(ImportRowErrorModel { line, message });
+                continue;
+            }
+        };
+
+        let key = match row.product_uuid {
+            Some(uuid) => GroupKey::Uuid(uuid),
+            None => GroupKey::Name(row.product_name.clone()),
+        };
+        let group = match index.get(&key) {
+            Some(&i) => &mut groups[i],
+            None => {
+                index.insert(key, groups.len());
+                groups.push(ProductGroup {
+                    line,
+                    rows: 0,
+                    uuid: row.product_uuid,
+                    name: row.product_name.clone(),
+                    description: row.product_description.clone(),
+                    items: Vec::new(),
+                });
+                groups.last_mut().expect("a group was just pushed")
+            }
+        };
+
+        if group.name != row.product_name || group.description != row.product_description {
+            let message = format!("product fields differ from line {}", group.line);
+            errors.push(ImportRowErrorModel { line, message });
+            continue;
+        }
+        if let Some(name) = row.item_name {
+            if group.items.iter().any(|item| item.name == name) {
+                let message = format!("item '{}' appears more than once for this product", name);
+                errors.push(ImportRowErrorModel { line, message });
+                continue;
+            }
+            group.items.push(ImportItem { line, name, quantity: row.item_quantity });
+        }
+        group.rows += 1;
+    }
+    groups
+}
+
+// Products are never split across chunks, so a chunk can exceed the size
+fn chunk_groups(groups: Vec<ProductGroup>, chunk_size: usize) -> Vec<Vec<ProductGroup>> {
+    if chunk_size == 0 {
+        return if groups.is_empty() { Vec::new() } else { vec![groups] };
+    }
+    let mut chunks = Vec::new();
+    let mut current = Vec::new();
+    let mut rows = 0;
+    for group in groups {
+        rows += group.rows;
+        current.push(group);
+        if rows >= chunk_size {
+            chunks.push(mem::take(&mut current));
+            rows = 0;
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[derive(Default)]
+struct Counts {
+    products_created: usize,
+    products_updated: usize,
+    items_created: usize,
+    items_updated: usize,
+}
+
+// Validates every row, then upserts product by product. Nothing is written
+// when any row is invalid. Each chunk commits on its own; the first chunk
+// that fails is rolled back and the import stops there. Products are matched
+// by UUID, or by name without one, so re-running the fixed file finds the
+// committed chunks unchanged and picks up where it left off. A dry run rolls
+// back every chunk and goes on past failing ones, reporting the first
+// failure of each.
+pub async fn run_import(
+    db: &DatabaseConnection,
+    actor: &str,
+    rows: Vec<ParsedRow>,
+    options: ImportOptions,
+) -> AppResult<ImportReportModel> {
+    let mut report = ImportReportModel {
+        dry_run: options.dry_run,
+        rows: rows.len(),
+        ..Default::default()
+    };
+    let groups = group_rows(rows, &mut report.errors);
+    let chunks = chunk_groups(groups, options.chunk_size);
+    report.chunks = chunks.len();
+    if !report.errors.is_empty() {
+        return Ok(report);
+    }
+
+    for chunk in chunks {
+        let mut counts = Counts::default();
+        if let Err((line, e)) = import_chunk(db, actor, &options, chunk, &mut counts).await {
+            // Database failures are reported on their line like bad rows, so
+            // the caller still learns which chunks were committed
+            if let AppError::Database(e) = &e {
+                tracing::error!(line, error = ?e, "import chunk failed");
+            }
+            report.errors.push(ImportRowErrorModel { line, message: e.detail() });
+            if options.dry_run {
+                continue;
+            }
+            break;
+        }
+        if !options.dry_run {
+            report.chunks_committed += 1;
+        }
+        report.products_created += counts.products_created;
+        report.products_updated += counts.products_updated;
+        report.items_created += counts.items_created;
+        report.items_updated += counts.items_updated;
+    }
+    Ok(report)
+}
+
+// Imports one chunk in its own transaction. Failures to begin or commit are
+// put on the chunk's first line.
+async fn import_chunk(
+    db: &DatabaseConnection,
+    actor: &str,
+    options: &ImportOptions,
+    chunk: Vec<ProductGroup>,
+    counts: &mut Counts,
+) -> Result<(), (u64, AppError)> {
+    let first_line = chunk.first().map_or(0, |group| group.line);
+    let at_first_line = |e: DbErr| (first_line, AppError::from(e));
+
+    let txn = db.begin().await.map_err(at_first_line)?;
+    for group in chunk {
+        if let Err(failure) = import_group(&txn, actor, &options.skus, group, counts).await {
+            // Dropping the transaction rolls it back should this fail too
+            let _ = txn.rollback().await;
+            return Err(failure);
+        }
+    }
+    if options.dry_run {
+        txn.rollback().await.map_err(at_first_line)
+    } else {
+        txn.commit().await.map_err(at_first_line)
+    }
+}
+
+// Errors carry the line of the row that caused them
+async fn import_group<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    skus: &SkuSettings,
+    group: ProductGroup,
+    counts: &mut Counts,
+) -> Result<(), (u64, AppError)> {
+    let product_id = upsert_product(txn, actor, &group, counts)
+        .await
+        .map_err(|e| (group.line, e))?;
+    for item in group.items {
+        let line = item.line;
+        upsert_item(txn, actor, skus, product_id, item, counts)
+            .await
+            .map_err(|e| (line, e))?;
+    }
+    Ok(())
+}
+
+async fn upsert_product<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    group: &ProductGroup,
+    counts: &mut Counts,
+) -> AppResult<i32> {
+    let existing = match group.uuid {
+        Some(uuid) => {
+            ProductEntity::find()
+                .filter(product::Column::Uuid.eq(uuid))
+                .lock_exclusive()
+                .one(txn)
+                .await?
+        }
+        // Without a UUID the live product of that name is the one, so that
+        // importing the same file again changes nothing
+        None => {
+            let mut named = ProductEntity::find_live()
+                .filter(product::Column::Name.eq(&group.name))
+                .lock_exclusive()
+                .limit(2)
+                .all(txn)
+                .await?;
+            if named.len() > 1 {
+                return Err(AppError::Conflict(format!(
+                    "several products are named '{}'; give the product_uuid",
+                    group.name
+                )));
+            }
+            named.pop()
+        }
+    };
+
+    let Some(existing) = existing else {
+        let inserted = product::ActiveModel {
+            uuid: Set(group.uuid.unwrap_or_else(Uuid::new_v4)),
+            name: Set(group.name.clone()),
+            description: Set(group.description.clone()),
+            ..Default::default()
+        }
+        .insert(txn)
+        .await?;
+        audit::record(txn, AuditEntry {
+            actor,
+            action: AuditAction::Create,
+            entity_type: AuditEntityType::Product,
+            entity_key: inserted.uuid.to_string(),
+            before: None,
+            after: Some(&inserted),
+        })
+        .await?;
+        counts.products_created += 1;
+        return Ok(inserted.id);
+    };
+
+    if existing.deleted_at.is_some() {
+        return Err(AppError::Conflict(format!(
+            "product '{}' is in the trash; restore it before importing into it",
+            existing.uuid
+        )));
+    }
+    if existing.name == group.name && existing.description == group.description {
+        return Ok(existing.id);
+    }
+
+    let mut active: product::ActiveModel = existing.clone().into();
+    active.name = Set(group.name.clone());
+    active.description = Set(group.description.clone());
+    let updated = active.update(txn).await?;
+    audit::record(txn, AuditEntry {
+        actor,
+        action: AuditAction::Update,
+        entity_type: AuditEntityType::Product,
+        entity_key: updated.uuid.to_string(),
+        before: Some(&existing),
+        after: Some(&updated),
+    })
+    .await?;
+    counts.products_updated += 1;
+    Ok(updated.id)
+}
+
+// Items are matched by name within their product. A changed quantity is
+// booked as an adjustment at the default location, like an item update, and
+// is refused the same way once stock sits at other locations.
+async fn upsert_item<C: ConnectionTrait>(
+    txn: &C,
+    actor: &str,
+    skus: &SkuSettings,
+    product_id: i32,
+    item: ImportItem,
+    counts: &mut Counts,
+) -> AppResult<()> {
+    let existing = ItemEntity::find_live()
+        .filter(item::Column::ProductId.eq(product_id))
+        .filter(item::Column::Name.eq(&item.name))
+        .one(txn)
+        .await?;
+
+    let Some(existing) = existing else {
+        let new_item = NewItem::new(product_id, item.name, item.quantity.unwrap_or(0));
+        insert_item(txn, actor, skus, new_item).await?;
+        counts.items_created += 1;
+        return Ok(());
+    };
+
+    let Some(quantity) = item.quantity.filter(|q| *q != existing.quantity) else {
+        return Ok(());
+    };
+    let location_id = resolve_location(txn, None).await?;
+    check_stock_only_at(txn, existing.id, location_id).await?;
+    let movement = NewMovement {
+        kind: MovementKind::Adjustment,
+        location_id,
+        delta: quantity - existing.quantity,
+        reason_code: "import".to_string(),
+        actor: actor.to_string(),
+        reference: None,
+    };
+    let (updated, _) = apply_movement(txn, existing.id, movement).await?;
+    audit::record(txn, AuditEntry {
+        actor,
+        action: AuditAction::Update,
+        entity_type: AuditEntityType::Item,
+        entity_key: updated.id.to_string(),
+        before: Some(&existing),
+        after: Some(&updated),
+    })
+    .await?;
+    counts.items_updated += 1;
+    Ok(())
+}
+
+// Runs the `import` subcommand and returns the exit code: 0 when every row
+// was imported (or would be, in a dry run), 1 when some were not, 2 when the
+// file or database could not be opened
+pub async fn run_cli(config: &Config, args: ImportArgs) -> i32 {
+    let file_name = args.file.to_string_lossy().into_owned();
+    let Some(format) = args.format.or_else(|| ImportFormat::from_file_name(&file_name)) else {
+        eprintln!("cannot tell the format of {}; pass --format csv or --format ndjson", file_name);
+        return 2;
+    };
+    let data = match std::fs::read(&args.file) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("cannot read {}: {}", file_name, e);
+            return 2;
+        }
+    };
+    let db = match crate::connect(&config.database).await {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("cannot connect to the database: {}", e);
+            return 2;
+        }
+    };
+
+    let options = ImportOptions {
+        dry_run: args.dry_run,
+        chunk_size: args.chunk_size.unwrap_or(config.import.chunk_size),
+        skus: config.sku.clone(),
+    };
+    let result = run_import(&db, &args.actor, parse(format, &data), options).await;
+    let _ = db.close().await;
+
+    match result {
+        Ok(report) => {
+            println!("{}", serde_json::to_string_pretty(&report).expect("the report serializes"));
+            if report.errors.is_empty() { 0 } else { 1 }
+        }
+        Err(e) => {
+            eprintln!("import failed: {:?}", e);
+            1
+        }
+    }
+}
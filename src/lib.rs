@@ -1,24 +1,179 @@
-mod routes;
+pub mod routes;
+pub mod openapi;
+pub mod config;
+pub mod server;
+pub mod metrics;
+pub mod telemetry;
 mod models;
 mod handlers;
+pub mod auth;
+pub mod deprecation;
+mod audit;
+mod error;
+pub mod etag;
+pub mod import;
+pub mod variants;
+pub mod gtin;
+pub mod sku;
+pub mod currency;
+pub mod reservations;
+pub mod stock;
+mod extract;
+pub mod pagination;
 
-use axum::{Extension, Router};
-use sea_orm::Database;
-use dotenv::dotenv;
-use std::env;
+use auth::AuthConfig;
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use axum::{middleware, Extension, Router};
+use config::{Config, CorsSettings, DatabaseSettings};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
+use metrics::Metrics;
+use server::{DrainOutcome, Lifecycle, Phase};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::timeout::TimeoutLayer;
 
 
-pub async fn app() {
+pub async fn app(config: Config) {
 
-    dotenv().ok();
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let db = Database::connect(database_url).await.expect("Failed to connect to db");
+    telemetry::init(&config.log);
+
+    let db = connect(&config.database).await.expect("Failed to connect to db");
+
+    let tcp_listener = TcpListener::bind(config.server.listen_addr)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to bind {}: {}", config.server.listen_addr, e));
+
+    run(&config, db, tcp_listener, server::shutdown_signal()).await;
+}
+
+// Serves the API until `shutdown` resolves, drains in-flight requests within
+// the configured deadline and then closes the database pool.
+pub async fn run<F>(
+    config: &Config,
+    mut db: DatabaseConnection,
+    listener: TcpListener,
+    shutdown: F,
+) -> DrainOutcome
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let auth_config = Arc::new(AuthConfig::from_settings(&config.jwt).expect("Invalid JWT configuration"));
+
+    let lifecycle = Lifecycle::new();
+    let metrics = Arc::new(Metrics::new());
+    metrics.instrument(&mut db);
 
     let app = Router::new()
-        .merge(routes::item_routers::item_routes())
-        .merge(routes::product_routes::product_routes())
-        .layer(Extension(db));
+        .merge(routes::api_routes())
+        .merge(routes::health_routes::health_routes())
+        .merge(routes::metrics_routes::metrics_routes())
+        .merge(openapi::docs_routes())
+        .layer(middleware::from_fn_with_state(auth_config, auth::authenticate))
+        .layer(middleware::from_fn_with_state(metrics.clone(), metrics::track_http))
+        .layer(Extension(db.clone()))
+        .layer(Extension(lifecycle.clone()))
+        .layer(Extension(metrics.clone()))
+        .layer(Extension(config.reservations.clone()))
+        .layer(Extension(config.stock.clone()))
+        .layer(Extension(config.import.clone()))
+        .layer(Extension(config.sku.clone()))
+        .layer(TimeoutLayer::new(config.server.request_timeout()))
+        .layer(cors_layer(&config.cors))
+        .layer(middleware::from_fn(telemetry::trace_request));
+
+    // Readiness turns 503 as soon as the signal arrives, before draining
+    let shutdown = {
+        let lifecycle = lifecycle.clone();
+        async move {
+            shutdown.await;
+            lifecycle.set(Phase::ShuttingDown);
+        }
+    };
+
+    let startup = tokio::spawn(handlers::health_handlers::mark_ready_when_healthy(
+        db.clone(),
+        lifecycle.clone(),
+    ));
+    let sweeper = tokio::spawn(handlers::reservation_handlers::run_sweeper(
+        db.clone(),
+        Duration::from_secs(config.reservations.sweep_interval_secs),
+    ));
+    let purger = tokio::spawn(handlers::trash_handlers::run_purger(
+        db.clone(),
+        Duration::from_secs(config.trash.retention_days.saturating_mul(24 * 60 * 60)),
+        Duration::from_secs(config.trash.purge_interval_secs),
+    ));
+    let inventory_metrics = tokio::spawn(handlers::metrics_handlers::run_inventory_refresh(
+        db.clone(),
+        metrics,
+        handlers::metrics_handlers::INVENTORY_REFRESH_INTERVAL,
+    ));
+    tracing::info!(addr = %config.server.listen_addr, "listening");
+    let outcome = server::serve(listener, app, shutdown, config.server.shutdown_timeout()).await;
+    startup.abort();
+    sweeper.abort();
+    purger.abort();
+    inventory_metrics.abort();
+    if outcome == DrainOutcome::DeadlineElapsed {
+        tracing::warn!(
+            deadline_secs = config.server.shutdown_timeout_secs,
+            "shutdown deadline elapsed; unfinished requests were dropped"
+        );
+    }
+
+    if let Err(e) = db.close().await {
+        tracing::error!(error = %e, "failed to close the database pool");
+    }
+    tracing::info!(outcome = ?outcome, "stopped");
+    outcome
+}
+
+// Statements slower than this are logged at warn level
+const SLOW_STATEMENT: Duration = Duration::from_secs(1);
+
+pub async fn connect(settings: &DatabaseSettings) -> Result<DatabaseConnection, DbErr> {
+    // `Config` validation guarantees the URL is present
+    let url = settings.url.clone().unwrap_or_default();
+    let mut options = ConnectOptions::new(url);
+    options
+        .max_connections(settings.max_connections)
+        .min_connections(settings.min_connections)
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs))
+        .acquire_timeout(Duration::from_secs(settings.acquire_timeout_secs))
+        .idle_timeout(Duration::from_secs(settings.idle_timeout_secs))
+        .sqlx_logging(true)
+        .sqlx_logging_level(log::LevelFilter::Debug)
+        .sqlx_slow_statements_logging_settings(log::LevelFilter::Warn, SLOW_STATEMENT);
+    Database::connect(options).await
+}
+
+// Origins were checked by `Config` validation; an empty list adds no CORS
+// headers, so browsers keep blocking cross-origin calls.
+fn cors_layer(settings: &CorsSettings) -> CorsLayer {
+    let origins = &settings.allowed_origins;
+    let allow_origin = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::from(Any)
+    } else {
+        AllowOrigin::list(origins.iter().filter_map(|o| HeaderValue::from_str(o).ok()))
+    };
 
-    let tcp_listener = tokio::net::TcpListener::bind("0.0.0.0:3001").await.unwrap();
-    axum::serve(tcp_listener, app).await.unwrap();
-}
\ No newline at end of file
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::IF_MATCH,
+            header::IF_NONE_MATCH,
+        ])
+        .expose_headers([
+            header::ETAG,
+            header::LINK,
+            header::LOCATION,
+            HeaderName::from_static(deprecation::DEPRECATION_HEADER),
+            HeaderName::from_static(deprecation::SUNSET_HEADER),
+        ])
+}
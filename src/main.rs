@@ -1,8 +1,19 @@
 use product_service::app;
+use product_service::config::{Command, Config};
+use product_service::import;
+use std::process;
 
 #[tokio::main]
 async fn main() {
 
-    app().await;
+    let (config, command) = Config::load().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+
+    match command {
+        None => app(config).await,
+        Some(Command::Import(args)) => process::exit(import::run_cli(&config, args).await),
+    }
     
 }
@@ -0,0 +1,145 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use sea_orm::{metric::Info, DatabaseConnection};
+
+// Route label for requests that matched no route, so unknown paths cannot
+// create new series
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+// Every metric the service exports, in a registry of its own so several
+// servers can run in one process (as the integration tests do)
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    db_query_duration: HistogramVec,
+    pub db_pool_connections: IntGauge,
+    pub db_pool_idle_connections: IntGauge,
+    pub db_pool_max_connections: IntGauge,
+    pub products: IntGauge,
+    pub items: IntGauge,
+    pub stock_quantity: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled, by route and status code"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "HTTP request latency by route"),
+            &["method", "route"],
+        )
+        .unwrap();
+        let db_query_duration = HistogramVec::new(
+            HistogramOpts::new("db_query_duration_seconds", "Database statement latency")
+                .buckets(vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5]),
+            &["operation", "outcome"],
+        )
+        .unwrap();
+        let gauge = |name: &str, help: &str| IntGauge::new(name, help).unwrap();
+
+        let metrics = Metrics {
+            registry: Registry::new(),
+            http_requests,
+            http_duration,
+            db_query_duration,
+            db_pool_connections: gauge("db_pool_connections", "Open connections in the database pool"),
+            db_pool_idle_connections: gauge("db_pool_idle_connections", "Idle connections in the database pool"),
+            db_pool_max_connections: gauge("db_pool_max_connections", "Upper bound of the database pool"),
+            products: gauge("inventory_products", "Products in the catalog"),
+            items: gauge("inventory_items", "Items across all products"),
+            stock_quantity: gauge("inventory_stock_quantity", "Units in stock across all items"),
+        };
+
+        let collectors: [Box<dyn prometheus::core::Collector>; 9] = [
+            Box::new(metrics.http_requests.clone()),
+            Box::new(metrics.http_duration.clone()),
+            Box::new(metrics.db_query_duration.clone()),
+            Box::new(metrics.db_pool_connections.clone()),
+            Box::new(metrics.db_pool_idle_connections.clone()),
+            Box::new(metrics.db_pool_max_connections.clone()),
+            Box::new(metrics.products.clone()),
+            Box::new(metrics.items.clone()),
+            Box::new(metrics.stock_quantity.clone()),
+        ];
+        for collector in collectors {
+            metrics.registry.register(collector).unwrap();
+        }
+        metrics
+    }
+
+    // Times every statement SeaORM runs on `db` and its clones, labelled by
+    // the leading SQL keyword
+    pub fn instrument(self: &Arc<Self>, db: &mut DatabaseConnection) {
+        let metrics = self.clone();
+        db.set_metric_callback(move |info: &Info<'_>| {
+            let operation = statement_operation(&info.statement.sql);
+            let outcome = if info.failed { "error" } else { "ok" };
+            metrics
+                .db_query_duration
+                .with_label_values(&[operation, outcome])
+                .observe(info.elapsed.as_secs_f64());
+        });
+    }
+
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("Prometheus text encoding does not fail");
+        String::from_utf8(buffer).expect("Prometheus text output is UTF-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn statement_operation(sql: &str) -> &'static str {
+    let keyword = sql.split_whitespace().next().unwrap_or_default();
+    match keyword.to_ascii_uppercase().as_str() {
+        "SELECT" | "WITH" => "select",
+        "INSERT" => "insert",
+        "UPDATE" => "update",
+        "DELETE" => "delete",
+        "BEGIN" | "START" | "COMMIT" | "ROLLBACK" | "SAVEPOINT" | "RELEASE" => "transaction",
+        _ => "other",
+    }
+}
+
+// Counts and times every request by its route template (e.g.
+// `/api/get_product/:uuid`) rather than the raw path
+pub async fn track_http(State(metrics): State<Arc<Metrics>>, req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+
+    let response = next.run(req).await;
+
+    metrics
+        .http_requests
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .inc();
+    metrics
+        .http_duration
+        .with_label_values(&[&method, &route])
+        .observe(started.elapsed().as_secs_f64());
+    response
+}
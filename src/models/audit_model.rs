@@ -0,0 +1,37 @@
+use chrono::{DateTime, FixedOffset};
+use entity::audit_log::{AuditAction, AuditEntityType};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+// `before` and `after` are full row snapshots; `before` is null for a
+// creation
+#[derive(Serialize, Clone, ToSchema)]
+pub struct AuditLogModel {
+    pub id: i32,
+    pub actor: String,
+    pub action: AuditAction,
+    pub entity_type: AuditEntityType,
+    pub entity_key: String,
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<serde_json::Value>,
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::audit_log::Model> for AuditLogModel {
+    fn from(a: entity::audit_log::Model) -> Self {
+        AuditLogModel {
+            id: a.id,
+            actor: a.actor,
+            action: a.action,
+            entity_type: a.entity_type,
+            entity_key: a.entity_key,
+            before: a.before,
+            after: a.after,
+            request_id: a.request_id,
+            created_at: a.created_at,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct CategoryModel {
+    pub id: i32,
+    // Empty for top-level categories
+    pub parent_id: Option<i32>,
+    pub name: String,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::category::Model> for CategoryModel {
+    fn from(c: entity::category::Model) -> Self {
+        CategoryModel {
+            id: c.id,
+            parent_id: c.parent_id,
+            name: c.name,
+            created_at: c.created_at,
+            updated_at: c.updated_at,
+        }
+    }
+}
+
+// A category with its ancestors, root first, and its direct children
+#[derive(Serialize, ToSchema)]
+pub struct CategoryDetailModel {
+    #[serde(flatten)]
+    pub category: CategoryModel,
+    pub path: Vec<CategoryModel>,
+    pub children: Vec<CategoryModel>,
+}
+
+// One node of the full tree; siblings are sorted by name
+#[derive(Serialize, ToSchema)]
+pub struct CategoryTreeModel {
+    pub id: i32,
+    pub name: String,
+    #[schema(no_recursion)]
+    pub children: Vec<CategoryTreeModel>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateCategoryModel {
+    pub name: String,
+    // Leave out to create a top-level category
+    pub parent_id: Option<i32>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct RenameCategoryModel {
+    pub name: String,
+}
+
+// Moves the category together with everything below it
+#[derive(Deserialize, ToSchema)]
+pub struct MoveCategoryModel {
+    // null makes it a top-level category
+    pub parent_id: Option<i32>,
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Ndjson,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+// What item export rows are joined with
+#[derive(Clone, Copy, Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportJoin {
+    Product,
+}
+
+// One line of the product export; flat so CSV and NDJSON share the columns
+#[derive(Serialize, ToSchema)]
+pub struct ProductExportModel {
+    pub uuid: Uuid,
+    pub name: String,
+    pub description: String,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::product::Model> for ProductExportModel {
+    fn from(p: entity::product::Model) -> Self {
+        ProductExportModel {
+            uuid: p.uuid,
+            name: p.name,
+            description: p.description,
+            created_at: p.created_at,
+            updated_at: p.updated_at,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ItemExportModel {
+    pub id: i32,
+    pub product_id: i32,
+    pub name: String,
+    pub quantity: i32,
+    pub sku: Option<String>,
+    pub barcode: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::item::Model> for ItemExportModel {
+    fn from(item: entity::item::Model) -> Self {
+        ItemExportModel {
+            id: item.id,
+            product_id: item.product_id,
+            name: item.name,
+            quantity: item.quantity,
+            sku: item.sku,
+            barcode: item.barcode,
+            created_at: item.created_at,
+            updated_at: item.updated_at,
+        }
+    }
+}
+
+// An item export line with `join=product`. The product and item name and
+// quantity columns match the import format, so an export can be re-imported.
+#[derive(Serialize, ToSchema)]
+pub struct JoinedItemExportModel {
+    pub item_id: i32,
+    pub product_uuid: Option<Uuid>,
+    pub product_name: Option<String>,
+    pub product_description: Option<String>,
+    pub item_name: String,
+    pub item_quantity: i32,
+    pub item_sku: Option<String>,
+    pub item_barcode: Option<String>,
+    pub item_created_at: DateTime<FixedOffset>,
+    pub item_updated_at: DateTime<FixedOffset>,
+}
+
+impl From<(entity::item::Model, Option<entity::product::Model>)> for JoinedItemExportModel {
+    fn from((item, product): (entity::item::Model, Option<entity::product::Model>)) -> Self {
+        let (product_uuid, product_name, product_description) = match product {
+            Some(p) => (Some(p.uuid), Some(p.name), Some(p.description)),
+            None => (None, None, None),
+        };
+        JoinedItemExportModel {
+            item_id: item.id,
+            product_uuid,
+            product_name,
+            product_description,
+            item_name: item.name,
+            item_quantity: item.quantity,
+            item_sku: item.sku,
+            item_barcode: item.barcode,
+            item_created_at: item.created_at,
+            item_updated_at: item.updated_at,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct LivenessModel {
+    #[schema(example = "ok")]
+    pub status: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessModel {
+    // "ready" only when the service is running and every component is up;
+    // otherwise "starting", "shutting_down" or "unavailable"
+    #[schema(example = "ready")]
+    pub status: String,
+    pub components: BTreeMap<String, ComponentModel>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ComponentModel {
+    #[schema(example = "up")]
+    pub status: String,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    // Migrations defined by the binary but not yet applied to the database
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending: Vec<String>,
+}
@@ -0,0 +1,34 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+// Outcome of a bulk import. Counts cover the chunks that were committed, or
+// that would have been in a dry run.
+#[derive(Serialize, Debug, Default, ToSchema)]
+pub struct ImportReportModel {
+    pub dry_run: bool,
+    pub rows: usize,
+    pub chunks: usize,
+    pub chunks_committed: usize,
+    pub products_created: usize,
+    pub products_updated: usize,
+    pub items_created: usize,
+    pub items_updated: usize,
+    pub errors: Vec<ImportRowErrorModel>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ImportRowErrorModel {
+    // 1-based line in the uploaded file
+    pub line: u64,
+    pub message: String,
+}
+
+// Multipart body of the import endpoint; only describes it in the OpenAPI
+// document, the handler reads the field directly
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct ImportUploadModel {
+    // CSV with a header row, or one JSON object per line
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
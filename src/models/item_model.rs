@@ -1,22 +1,159 @@
+use chrono::{DateTime, FixedOffset};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::models::warehouse_model::LocationStockModel;
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct ItemModel {
     // #[serde(skip_deserializing, default)]
     pub id: Option<i32>,
-    pub ProductId: i32,
-    pub Name: String,
-    pub Quantity: i32,
-}
-
-#[derive(Clone, Serialize, Deserialize)]
-pub struct GetItemModel{
-    pub ProductId: i32,
-    pub Name: String,
-    pub Quantity: i32,
-}
-#[derive(Clone, Serialize, Deserialize)]
-pub struct UpdateItemModel{
-    pub Name: String,
-    pub Quantity: i32,
-}
\ No newline at end of file
+    #[serde(rename = "ProductId")]
+    pub product_id: i32,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Quantity")]
+    pub quantity: i32,
+    // Generated from the configured pattern when left out of a create
+    #[serde(rename = "Sku", default)]
+    pub sku: Option<String>,
+    // GTIN-8, -12, -13 or -14 with a valid check digit
+    #[serde(rename = "Barcode", default)]
+    pub barcode: Option<String>,
+    // Set by the server; ignored when the model is a request body
+    #[serde(rename = "BasePrice", default, skip_deserializing)]
+    #[schema(value_type = Option<String>, example = "12.50")]
+    pub base_price: Option<Decimal>,
+    #[serde(rename = "Currency", default, skip_deserializing)]
+    pub currency: Option<String>,
+    #[serde(rename = "Created_at", default, skip_deserializing)]
+    pub created_at: Option<DateTime<FixedOffset>>,
+    #[serde(rename = "Updated_at", default, skip_deserializing)]
+    pub updated_at: Option<DateTime<FixedOffset>>,
+}
+
+fn display_price(price: Option<Decimal>, currency: Option<&str>) -> Option<Decimal> {
+    Some(crate::currency::display(price?, currency?))
+}
+
+impl From<entity::item::Model> for ItemModel {
+    fn from(item: entity::item::Model) -> Self {
+        ItemModel {
+            id: Some(item.id),
+            product_id: item.product_id,
+            name: item.name,
+            quantity: item.quantity,
+            sku: item.sku,
+            barcode: item.barcode,
+            base_price: display_price(item.base_price, item.currency.as_deref()),
+            currency: item.currency,
+            created_at: Some(item.created_at),
+            updated_at: Some(item.updated_at),
+        }
+    }
+}
+
+// Item with its stock broken down by location; `Quantity` is the total
+#[derive(Serialize, Clone, ToSchema)]
+pub struct ItemWithStockModel {
+    #[serde(flatten)]
+    pub item: ItemModel,
+    #[serde(rename = "Locations")]
+    pub locations: Vec<LocationStockModel>,
+}
+
+// Body returned by the item update endpoint
+#[derive(Serialize, ToSchema)]
+pub struct UpdateItemResponseModel {
+    pub message: String,
+    pub updated_item: UpdatedItemModel,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UpdatedItemModel {
+    pub id: i32,
+    pub name: String,
+    pub quantity: i32,
+    pub product_id: i32,
+    pub sku: Option<String>,
+    pub barcode: Option<String>,
+}
+
+// Body accepted by the relative adjustment endpoint
+#[derive(Deserialize, ToSchema)]
+pub struct AdjustItemModel {
+    // Signed change to the quantity
+    pub delta: i32,
+    // Defaults to the default location
+    pub location_id: Option<i32>,
+    // Defaults to `adjustment`
+    pub reason_code: Option<String>,
+    pub reference: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdjustedItemModel {
+    pub id: i32,
+    pub quantity: i32,
+    pub requested_delta: i32,
+    // Differs from `requested_delta` when the clamp policy stopped at zero
+    pub applied_delta: i32,
+}
+
+// An item as the v2 API returns it, pointing at its product by UUID
+#[derive(Serialize, ToSchema)]
+pub struct ProductItemModel {
+    pub id: i32,
+    #[serde(rename = "ProductUuid")]
+    pub product_uuid: Uuid,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Quantity")]
+    pub quantity: i32,
+    #[serde(rename = "Sku")]
+    pub sku: Option<String>,
+    #[serde(rename = "Barcode")]
+    pub barcode: Option<String>,
+    #[serde(rename = "BasePrice")]
+    #[schema(value_type = Option<String>, example = "12.50")]
+    pub base_price: Option<Decimal>,
+    #[serde(rename = "Currency")]
+    pub currency: Option<String>,
+    #[serde(rename = "Created_at")]
+    pub created_at: DateTime<FixedOffset>,
+    #[serde(rename = "Updated_at")]
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl ProductItemModel {
+    pub fn new(item: entity::item::Model, product_uuid: Uuid) -> Self {
+        ProductItemModel {
+            id: item.id,
+            product_uuid,
+            name: item.name,
+            quantity: item.quantity,
+            sku: item.sku,
+            barcode: item.barcode,
+            base_price: display_price(item.base_price, item.currency.as_deref()),
+            currency: item.currency,
+            created_at: item.created_at,
+            updated_at: item.updated_at,
+        }
+    }
+}
+
+// Body accepted by the v2 item create endpoint; the product is in the path
+#[derive(Deserialize, ToSchema)]
+pub struct CreateProductItemModel {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Quantity", default)]
+    pub quantity: i32,
+    // Generated from the configured pattern when left out
+    #[serde(rename = "Sku", default)]
+    pub sku: Option<String>,
+    #[serde(rename = "Barcode", default)]
+    pub barcode: Option<String>,
+}
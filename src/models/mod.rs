@@ -1,2 +1,14 @@
 pub mod product_model;
-pub mod item_model;
\ No newline at end of file
+pub mod item_model;
+pub mod stock_movement_model;
+pub mod warehouse_model;
+pub mod message_model;
+pub mod health_model;
+pub mod reservation_model;
+pub mod trash_model;
+pub mod audit_model;
+pub mod import_model;
+pub mod export_model;
+pub mod category_model;
+pub mod variant_model;
+pub mod price_model;
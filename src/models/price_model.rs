@@ -0,0 +1,135 @@
+use chrono::{DateTime, FixedOffset};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::currency;
+
+// Prices are decimal strings such as "12.50", never floats; requests may
+// also send them as JSON numbers
+
+#[derive(Serialize, ToSchema)]
+pub struct PriceListModel {
+    pub id: i32,
+    pub name: String,
+    pub currency: String,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::price_list::Model> for PriceListModel {
+    fn from(list: entity::price_list::Model) -> Self {
+        PriceListModel {
+            id: list.id,
+            name: list.name,
+            currency: list.currency,
+            created_at: list.created_at,
+            updated_at: list.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreatePriceListModel {
+    pub name: String,
+    // ISO 4217 code every price in the list is in
+    pub currency: String,
+}
+
+// Body accepted by the base price endpoint
+#[derive(Deserialize, ToSchema)]
+pub struct BasePriceModel {
+    #[schema(value_type = String, example = "12.50")]
+    pub price: Decimal,
+    pub currency: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ItemBasePriceModel {
+    pub item_id: i32,
+    // Both empty when the item has no base price
+    #[schema(value_type = Option<String>, example = "12.50")]
+    pub base_price: Option<Decimal>,
+    pub currency: Option<String>,
+}
+
+impl From<entity::item::Model> for ItemBasePriceModel {
+    fn from(item: entity::item::Model) -> Self {
+        let (base_price, currency) = match (item.base_price, item.currency) {
+            (Some(price), Some(currency)) => (Some(currency::display(price, &currency)), Some(currency)),
+            _ => (None, None),
+        };
+        ItemBasePriceModel {
+            item_id: item.id,
+            base_price,
+            currency,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PriceEntryModel {
+    pub id: i32,
+    pub price_list_id: i32,
+    pub item_id: i32,
+    #[schema(value_type = String, example = "12.50")]
+    pub price: Decimal,
+    pub currency: String,
+    pub valid_from: DateTime<FixedOffset>,
+    // Exclusive; empty while the price holds until further notice
+    pub valid_to: Option<DateTime<FixedOffset>>,
+    pub created_by: String,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl PriceEntryModel {
+    pub fn new(entry: entity::price_entry::Model, currency: &str) -> Self {
+        PriceEntryModel {
+            id: entry.id,
+            price_list_id: entry.price_list_id,
+            item_id: entry.item_id,
+            price: currency::display(entry.price, currency),
+            currency: currency.to_string(),
+            valid_from: entry.valid_from,
+            valid_to: entry.valid_to,
+            created_by: entry.created_by,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+// Body accepted by the price entry endpoint. A price without `valid_to`
+// holds until the next scheduled entry for the item, if there is one.
+#[derive(Deserialize, ToSchema)]
+pub struct CreatePriceEntryModel {
+    pub item_id: i32,
+    #[schema(value_type = String, example = "12.50")]
+    pub price: Decimal,
+    // Defaults to now; may not be earlier
+    pub valid_from: Option<DateTime<FixedOffset>>,
+    pub valid_to: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    PriceList,
+    Base,
+}
+
+// The price an item had in a price list at `at`. `source` is `base` when
+// the list has no entry now and the item's base price, in the list's
+// currency, was used instead; the entry fields are empty in that case.
+#[derive(Serialize, ToSchema)]
+pub struct EffectivePriceModel {
+    pub item_id: i32,
+    #[schema(value_type = String, example = "12.50")]
+    pub price: Decimal,
+    pub currency: String,
+    pub source: PriceSource,
+    pub at: DateTime<FixedOffset>,
+    pub price_list_id: Option<i32>,
+    pub entry_id: Option<i32>,
+    pub valid_from: Option<DateTime<FixedOffset>>,
+    pub valid_to: Option<DateTime<FixedOffset>>,
+}
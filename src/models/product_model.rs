@@ -1,18 +1,69 @@
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Clone)]
+use crate::models::item_model::ProductItemModel;
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct ProductModel {
     pub uuid: Uuid,
-    pub Name: String,
-    pub Description: String,
-    pub Created_at: NaiveDateTime
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Description")]
+    pub description: String,
+    // RFC 3339 with the offset, like every timestamp the API returns
+    #[serde(rename = "Created_at")]
+    pub created_at: DateTime<FixedOffset>,
+    #[serde(rename = "Updated_at")]
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::product::Model> for ProductModel {
+    fn from(p: entity::product::Model) -> Self {
+        ProductModel {
+            uuid: p.uuid,
+            name: p.name,
+            description: p.description,
+            created_at: p.created_at,
+            updated_at: p.updated_at,
+        }
+    }
 }
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize, ToSchema)]
 pub struct CreateProductModel {
-    pub Name: String,
-    pub Description: String,
-}
\ No newline at end of file
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Description")]
+    pub description: String,
+}
+
+// Body of the v2 PATCH; fields left out keep their value
+#[derive(Serialize, Deserialize, Default, ToSchema)]
+pub struct PatchProductModel {
+    #[serde(rename = "Name")]
+    pub name: Option<String>,
+    #[serde(rename = "Description")]
+    pub description: Option<String>,
+}
+
+// A product with whatever `include` asked for; without it, this serializes
+// exactly like `ProductModel`
+#[derive(Serialize, ToSchema)]
+pub struct ProductDetailModel {
+    #[serde(flatten)]
+    pub product: ProductModel,
+    #[serde(rename = "Items", skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<ProductItemModel>>,
+    #[serde(rename = "Aggregates", skip_serializing_if = "Option::is_none")]
+    pub aggregates: Option<ProductAggregatesModel>,
+}
+
+// Computed over the product's live items
+#[derive(Serialize, Clone, Default, ToSchema)]
+pub struct ProductAggregatesModel {
+    pub item_count: i64,
+    pub total_quantity: i64,
+}
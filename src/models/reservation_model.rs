@@ -0,0 +1,55 @@
+use chrono::{DateTime, FixedOffset};
+use entity::reservation::ReservationStatus;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::item_model::ItemModel;
+use crate::models::stock_movement_model::StockMovementModel;
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReservationModel {
+    pub id: i32,
+    pub item_id: i32,
+    pub location_id: i32,
+    pub quantity: i32,
+    pub status: ReservationStatus,
+    pub actor: String,
+    pub reference: Option<String>,
+    pub expires_at: DateTime<FixedOffset>,
+    pub created_at: DateTime<FixedOffset>,
+    pub resolved_at: Option<DateTime<FixedOffset>>,
+}
+
+impl From<entity::reservation::Model> for ReservationModel {
+    fn from(r: entity::reservation::Model) -> Self {
+        ReservationModel {
+            id: r.id,
+            item_id: r.item_id,
+            location_id: r.location_id,
+            quantity: r.quantity,
+            status: r.status,
+            actor: r.actor,
+            reference: r.reference,
+            expires_at: r.expires_at,
+            created_at: r.created_at,
+            resolved_at: r.resolved_at,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateReservationModel {
+    pub quantity: i32,
+    // Defaults to the configured TTL; capped by the configured maximum
+    pub ttl_secs: Option<u64>,
+    // Defaults to the default location
+    pub location_id: Option<i32>,
+    pub reference: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ConfirmedReservationModel {
+    pub reservation: ReservationModel,
+    pub item: ItemModel,
+    pub movement: StockMovementModel,
+}
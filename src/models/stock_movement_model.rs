@@ -0,0 +1,58 @@
+use chrono::{DateTime, FixedOffset};
+use entity::stock_movement::MovementKind;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::item_model::ItemModel;
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct StockMovementModel {
+    pub id: i32,
+    pub item_id: i32,
+    pub location_id: i32,
+    pub kind: MovementKind,
+    pub reason_code: String,
+    pub delta: i32,
+    pub quantity_after: i32,
+    pub actor: String,
+    pub reference: Option<String>,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::stock_movement::Model> for StockMovementModel {
+    fn from(m: entity::stock_movement::Model) -> Self {
+        StockMovementModel {
+            id: m.id,
+            item_id: m.item_id,
+            location_id: m.location_id,
+            kind: m.kind,
+            reason_code: m.reason_code,
+            delta: m.delta,
+            quantity_after: m.quantity_after,
+            actor: m.actor,
+            reference: m.reference,
+            created_at: m.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateStockMovementModel {
+    pub kind: MovementKind,
+    pub location_id: Option<i32>,
+    pub delta: i32,
+    pub reason_code: String,
+    pub reference: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AppliedMovementModel {
+    pub item: ItemModel,
+    pub movement: StockMovementModel,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AppliedTransferModel {
+    pub item: ItemModel,
+    pub movements: Vec<StockMovementModel>,
+}
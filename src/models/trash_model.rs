@@ -0,0 +1,41 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashResource {
+    Product,
+    Item,
+}
+
+// One soft deleted row; `key` is the product UUID or the item id
+#[derive(Serialize, Clone, ToSchema)]
+pub struct TrashEntryModel {
+    pub resource: TrashResource,
+    pub key: String,
+    pub name: String,
+    pub deleted_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::product::Model> for TrashEntryModel {
+    fn from(p: entity::product::Model) -> Self {
+        TrashEntryModel {
+            resource: TrashResource::Product,
+            key: p.uuid.to_string(),
+            name: p.name,
+            deleted_at: p.deleted_at.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<entity::item::Model> for TrashEntryModel {
+    fn from(i: entity::item::Model) -> Self {
+        TrashEntryModel {
+            resource: TrashResource::Item,
+            key: i.id.to_string(),
+            name: i.name,
+            deleted_at: i.deleted_at.unwrap_or_default(),
+        }
+    }
+}
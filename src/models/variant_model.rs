@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
+use entity::attribute::AttributeType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::models::item_model::ProductItemModel;
+use crate::variants::typed_value;
+
+#[derive(Serialize, ToSchema)]
+pub struct AttributeModel {
+    pub id: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: AttributeType,
+    // Absent when any value of the type is accepted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<Value>>,
+    pub created_at: DateTime<FixedOffset>,
+    pub updated_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::attribute::Model> for AttributeModel {
+    fn from(a: entity::attribute::Model) -> Self {
+        let allowed_values = a.allowed_values.map(|allowed| {
+            allowed
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(|stored| typed_value(a.data_type, stored))
+                .collect()
+        });
+        AttributeModel {
+            id: a.id,
+            name: a.name,
+            data_type: a.data_type,
+            allowed_values,
+            created_at: a.created_at,
+            updated_at: a.updated_at,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateAttributeModel {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub data_type: AttributeType,
+    // Leave out to accept any value of the type
+    pub allowed_values: Option<Vec<Value>>,
+}
+
+// An item's attribute values keyed by attribute name
+#[derive(Serialize, ToSchema)]
+pub struct ItemAttributesModel {
+    pub item_id: i32,
+    pub attributes: BTreeMap<String, Value>,
+}
+
+// Replaces all of an item's attribute values; an empty map clears them
+#[derive(Deserialize, ToSchema)]
+pub struct SetItemAttributesModel {
+    pub attributes: BTreeMap<String, Value>,
+}
+
+// A v2 item together with the attribute values that make it a variant
+#[derive(Serialize, ToSchema)]
+pub struct VariantModel {
+    #[serde(flatten)]
+    pub item: ProductItemModel,
+    #[serde(rename = "Attributes")]
+    pub attributes: BTreeMap<String, Value>,
+}
+
+// One attribute and the values to combine; axes are named in the order the
+// generated item names list them
+#[derive(Deserialize, ToSchema)]
+pub struct VariantAxisModel {
+    pub attribute: String,
+    pub values: Vec<Value>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GenerateVariantsModel {
+    pub axes: Vec<VariantAxisModel>,
+    // Initial stock of every new item
+    #[serde(default)]
+    pub quantity: i32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct GeneratedVariantsModel {
+    pub created: Vec<VariantModel>,
+    // Combinations the product already had a live item for
+    pub skipped: Vec<BTreeMap<String, Value>>,
+}
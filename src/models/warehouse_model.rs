@@ -0,0 +1,75 @@
+use chrono::{DateTime, FixedOffset};
+use sea_orm::FromQueryResult;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct WarehouseModel {
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl From<entity::warehouse::Model> for WarehouseModel {
+    fn from(w: entity::warehouse::Model) -> Self {
+        WarehouseModel {
+            id: w.id,
+            code: w.code,
+            name: w.name,
+            created_at: w.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateWarehouseModel {
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct LocationModel {
+    pub id: i32,
+    pub warehouse_id: i32,
+    pub code: String,
+    pub name: String,
+}
+
+impl From<entity::location::Model> for LocationModel {
+    fn from(l: entity::location::Model) -> Self {
+        LocationModel {
+            id: l.id,
+            warehouse_id: l.warehouse_id,
+            code: l.code,
+            name: l.name,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateLocationModel {
+    pub code: String,
+    pub name: String,
+}
+
+// Quantity of one item held at one location
+#[derive(Serialize, Clone, FromQueryResult, ToSchema)]
+pub struct LocationStockModel {
+    #[serde(skip)]
+    pub item_id: i32,
+    pub location_id: i32,
+    pub location_code: String,
+    pub warehouse_id: i32,
+    pub warehouse_code: String,
+    pub quantity: i32,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TransferStockModel {
+    pub from_location_id: i32,
+    pub to_location_id: i32,
+    pub quantity: i32,
+    pub reason_code: String,
+    pub reference: Option<String>,
+}
@@ -0,0 +1,136 @@
+use axum::Router;
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::handlers::{
+    audit_handlers, category_handlers, export_handlers, health_handlers, import_handlers, item_handlers,
+    metrics_handlers, price_handlers, product_hanlers, product_v2_handlers, reservation_handlers,
+    stock_movement_handlers, trash_handlers, variant_handlers, warehouse_handlers,
+};
+
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+pub const DOCS_PATH: &str = "/api/docs";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Product Service API"),
+    paths(
+        product_hanlers::create_product,
+        product_hanlers::get_all_products,
+        product_hanlers::get_product_by_uuid,
+        product_hanlers::delete_product,
+        product_hanlers::update_product,
+        product_v2_handlers::list_products,
+        product_v2_handlers::create_product,
+        product_v2_handlers::get_product,
+        product_v2_handlers::replace_product,
+        product_v2_handlers::patch_product,
+        product_v2_handlers::delete_product,
+        product_v2_handlers::list_product_items,
+        product_v2_handlers::create_product_item,
+        item_handlers::create_item,
+        item_handlers::get_all_items,
+        item_handlers::get_item_by_id,
+        item_handlers::lookup_item,
+        item_handlers::update_item_by_id,
+        item_handlers::adjust_item_quantity,
+        item_handlers::delete_item_by_id,
+        stock_movement_handlers::create_stock_movement,
+        stock_movement_handlers::create_stock_transfer,
+        stock_movement_handlers::get_stock_movements,
+        reservation_handlers::create_reservation,
+        reservation_handlers::get_reservation,
+        reservation_handlers::get_item_reservations,
+        reservation_handlers::confirm_reservation,
+        reservation_handlers::release_reservation,
+        trash_handlers::get_trash,
+        trash_handlers::restore_product,
+        trash_handlers::restore_item,
+        warehouse_handlers::create_warehouse,
+        warehouse_handlers::get_all_warehouses,
+        warehouse_handlers::get_warehouse_by_id,
+        warehouse_handlers::create_location,
+        warehouse_handlers::get_locations,
+        category_handlers::create_category,
+        category_handlers::get_category_tree,
+        category_handlers::get_category,
+        category_handlers::rename_category,
+        category_handlers::move_category,
+        category_handlers::delete_category,
+        category_handlers::assign_product,
+        category_handlers::unassign_product,
+        category_handlers::get_category_products,
+        variant_handlers::create_attribute,
+        variant_handlers::get_attributes,
+        variant_handlers::get_attribute,
+        variant_handlers::delete_attribute,
+        variant_handlers::get_item_attributes,
+        variant_handlers::set_item_attributes,
+        variant_handlers::list_product_variants,
+        variant_handlers::generate_variants,
+        price_handlers::create_price_list,
+        price_handlers::get_price_lists,
+        price_handlers::get_price_list,
+        price_handlers::delete_price_list,
+        price_handlers::set_base_price,
+        price_handlers::clear_base_price,
+        price_handlers::create_price_entry,
+        price_handlers::delete_price_entry,
+        price_handlers::get_price_history,
+        price_handlers::get_effective_price,
+        audit_handlers::get_audit_log,
+        import_handlers::import_catalog,
+        export_handlers::export_products,
+        export_handlers::export_items,
+        health_handlers::liveness,
+        health_handlers::readiness,
+        metrics_handlers::get_metrics,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer_auth" = [])),
+    tags(
+        (name = "v2", description = "Products and their items, addressed by product UUID"),
+        (name = "products", description = "Product catalog (v1, deprecated in favour of v2)"),
+        (name = "items", description = "Items and their stock (v1 listing and creation are deprecated in favour of v2)"),
+        (name = "stock", description = "Stock movement ledger and transfers"),
+        (name = "reservations", description = "Stock held for pending orders"),
+        (name = "trash", description = "Deleted products and items awaiting purge"),
+        (name = "warehouses", description = "Warehouses and their locations"),
+        (name = "categories", description = "Category tree and the products filed under it"),
+        (name = "variants", description = "Typed attributes and the item variants built from them"),
+        (name = "pricing", description = "Base prices, price lists and scheduled price changes"),
+        (name = "import", description = "Bulk upload of products and items"),
+        (name = "export", description = "Streaming dumps of products and items"),
+        (name = "audit", description = "Who changed which product or item, and how"),
+        (name = "health", description = "Probes and metrics for operators"),
+    )
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+// Serves the generated document and a Swagger UI whose assets are compiled
+// into the binary, so the docs work without reaching a CDN.
+pub fn docs_routes() -> Router {
+    SwaggerUi::new(DOCS_PATH)
+        .url(OPENAPI_PATH, ApiDoc::openapi())
+        .into()
+}
@@ -0,0 +1,154 @@
+use std::str::FromStr;
+
+use axum::http::Uri;
+use sea_orm::{
+    ConnectionTrait, EntityTrait, FromQueryResult, Order, PaginatorTrait, QueryOrder, Select,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::{AppError, AppResult};
+
+pub const DEFAULT_LIMIT: u64 = 20;
+pub const MAX_LIMIT: u64 = 100;
+
+// Page/limit pair shared by every list endpoint. `page` is 1-based.
+#[derive(Clone, Copy, Debug)]
+pub struct PageParams {
+    pub page: u64,
+    pub limit: u64,
+}
+
+impl PageParams {
+    pub fn new(page: Option<u64>, limit: Option<u64>) -> AppResult<Self> {
+        let page = page.unwrap_or(1);
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+        if page == 0 {
+            return Err(AppError::Validation("page must be 1 or greater".to_string()));
+        }
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(AppError::Validation(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+
+        // The offset is computed from both and sent as a bigint
+        let offset = (page - 1).checked_mul(limit).filter(|offset| *offset <= i64::MAX as u64);
+        if offset.is_none() {
+            return Err(AppError::Validation(format!("page {} is out of range", page)));
+        }
+
+        Ok(PageParams { page, limit })
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub total: u64,
+    pub page: u64,
+    pub limit: u64,
+    pub total_pages: u64,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            data: self.data.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            limit: self.limit,
+            total_pages: self.total_pages,
+            next: self.next,
+            prev: self.prev,
+        }
+    }
+}
+
+// Applies a `sort` query value such as `name,-quantity` to the select. A
+// leading `-` sorts descending. The primary key is always appended as a
+// tiebreaker so pages stay stable between requests.
+pub fn apply_sort<E>(
+    mut select: Select<E>,
+    sort: Option<&str>,
+    tiebreaker: E::Column,
+) -> AppResult<Select<E>>
+where
+    E: EntityTrait,
+    E::Column: FromStr,
+{
+    for key in sort.unwrap_or_default().split(',').filter(|k| !k.is_empty()) {
+        let (name, order) = match key.strip_prefix('-') {
+            Some(name) => (name, Order::Desc),
+            None => (key, Order::Asc),
+        };
+        let column = E::Column::from_str(name)
+            .map_err(|_| AppError::Validation(format!("unknown sort column '{}'", name)))?;
+        select = select.order_by(column, order);
+    }
+
+    Ok(select.order_by(tiebreaker, Order::Asc))
+}
+
+pub async fn fetch_page<E, C>(
+    db: &C,
+    select: Select<E>,
+    params: PageParams,
+    uri: &Uri,
+) -> AppResult<Page<E::Model>>
+where
+    E: EntityTrait,
+    E::Model: FromQueryResult + Sized + Send + Sync,
+    C: ConnectionTrait,
+{
+    let paginator = select.paginate(db, params.limit);
+    let counts = paginator.num_items_and_pages().await?;
+    let data = paginator.fetch_page(params.page - 1).await?;
+
+    let (next, prev) = page_links(uri, params.page, counts.number_of_pages);
+
+    Ok(Page {
+        data,
+        total: counts.number_of_items,
+        page: params.page,
+        limit: params.limit,
+        total_pages: counts.number_of_pages,
+        next,
+        prev,
+    })
+}
+
+// Links to the next and previous pages, where those exist
+pub fn page_links(uri: &Uri, page: u64, total_pages: u64) -> (Option<String>, Option<String>) {
+    let next = (page < total_pages).then(|| page_link(uri, page + 1));
+    let prev = (page > 1).then(|| page_link(uri, page - 1));
+    (next, prev)
+}
+
+// Rebuilds the request URI with only the `page` parameter replaced, so the
+// link carries the same filters, sort and limit.
+fn page_link(uri: &Uri, page: u64) -> String {
+    let mut pairs: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !pair.starts_with("page="))
+        .collect();
+    let page_pair = format!("page={}", page);
+    pairs.push(&page_pair);
+
+    format!("{}?{}", uri.path(), pairs.join("&"))
+}
+
+// Escapes LIKE wildcards in user input and wraps it for a substring match.
+pub fn contains_pattern(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
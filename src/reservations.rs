@@ -0,0 +1,51 @@
+use chrono::{DateTime, FixedOffset};
+use entity::reservation::ReservationStatus;
+use sea_orm::ActiveEnum;
+
+// How a held reservation ends: confirmed or released by a caller, or expired
+// by the sweeper
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    Confirm,
+    Release,
+    Expire,
+}
+
+// A reservation holds stock while it is `held` and not yet past its expiry,
+// even before the sweeper gets to it. `held_quantity` and `sweep_expired`
+// filter on the same condition in SQL.
+pub fn is_live(
+    status: ReservationStatus,
+    expires_at: DateTime<FixedOffset>,
+    now: DateTime<FixedOffset>,
+) -> bool {
+    status == ReservationStatus::Held && expires_at > now
+}
+
+// Units at a location that are free to issue or reserve. Negative when the
+// level was already taken below what reservations hold.
+pub fn available(on_hand: i32, held: i32) -> i32 {
+    on_hand - held
+}
+
+// The status a reservation moves to, or why it cannot. Only held ones
+// change: callers may resolve them until they expire, and from then on only
+// the sweeper may.
+pub fn resolve(
+    status: ReservationStatus,
+    expires_at: DateTime<FixedOffset>,
+    resolution: Resolution,
+    now: DateTime<FixedOffset>,
+) -> Result<ReservationStatus, String> {
+    if status != ReservationStatus::Held {
+        return Err(format!("is {} and can no longer change", status.to_value()));
+    }
+    let live = is_live(status, expires_at, now);
+    match resolution {
+        Resolution::Confirm if live => Ok(ReservationStatus::Confirmed),
+        Resolution::Release if live => Ok(ReservationStatus::Released),
+        Resolution::Confirm | Resolution::Release => Err("has expired".to_string()),
+        Resolution::Expire if live => Err("has not expired yet".to_string()),
+        Resolution::Expire => Ok(ReservationStatus::Expired),
+    }
+}
@@ -0,0 +1,8 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::audit_handlers::get_audit_log;
+use axum::{middleware::from_fn_with_state, routing::get, Router};
+
+pub fn audit_routes() -> Router {
+    Router::new().route("/api/audit", get(get_audit_log))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role))
+}
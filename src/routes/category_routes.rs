@@ -0,0 +1,24 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::category_handlers::{
+    assign_product, create_category, delete_category, get_category, get_category_products,
+    get_category_tree, move_category, rename_category, unassign_product,
+};
+use axum::{middleware::from_fn_with_state, routing::{delete, get, post, put}, Router};
+
+pub fn category_routes() -> Router {
+    let read = Router::new().route("/api/categories", get(get_category_tree))
+                 .route("/api/categories/:id", get(get_category))
+                 .route("/api/categories/:id/products", get(get_category_products))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/categories", post(create_category))
+                 .route("/api/categories/:id", put(rename_category))
+                 .route("/api/categories/:id/move", post(move_category))
+                 .route("/api/categories/:id/products/:uuid", put(assign_product).delete(unassign_product))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    let admin = Router::new().route("/api/categories/:id", delete(delete_category))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role));
+
+    read.merge(write).merge(admin)
+}
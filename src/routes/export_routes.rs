@@ -0,0 +1,9 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::export_handlers::{export_items, export_products};
+use axum::{middleware::from_fn_with_state, routing::get, Router};
+
+pub fn export_routes() -> Router {
+    Router::new().route("/api/export/products", get(export_products))
+                 .route("/api/export/items", get(export_items))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role))
+}
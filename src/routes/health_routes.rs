@@ -0,0 +1,8 @@
+use crate::handlers::health_handlers::{liveness, readiness};
+use axum::{routing::get, Router};
+
+// Probes for the orchestrator; they take no token
+pub fn health_routes() -> Router {
+    Router::new().route("/healthz", get(liveness))
+                 .route("/readyz", get(readiness))
+}
@@ -0,0 +1,11 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::import_handlers::import_catalog;
+use axum::{extract::DefaultBodyLimit, middleware::from_fn_with_state, routing::post, Router};
+
+// Supplier files run to thousands of rows, well past axum's 2 MB default
+const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+pub fn import_routes() -> Router {
+    Router::new().route("/api/import", post(import_catalog).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role))
+}
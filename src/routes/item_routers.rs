@@ -1,12 +1,36 @@
-
-use crate::handlers::item_handlers::{create_item, get_all_items, get_item_by_id, delete_item_by_id, update_item_by_id};
-use axum::{routing::{delete, get, post, put}, Router};
+use crate::auth::{require_role, Role};
+use crate::deprecation::deprecated_v1;
+use crate::handlers::item_handlers::{adjust_item_quantity, create_item, get_all_items, get_item_by_id, delete_item_by_id, lookup_item, update_item_by_id};
+use crate::handlers::stock_movement_handlers::{create_stock_movement, create_stock_transfer, get_stock_movements};
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{delete, get, post, put}, Router};
 
 pub fn item_routes() -> Router {
-    Router::new().route("/api/item", post(create_item))
-                 .route("/api/get_all_items", get(get_all_items))
-                 .route("/api/get_item/:id", get(get_item_by_id))
-                 .route("/api/delete_item/:id", delete(delete_item_by_id))
-                 .route("/api/item/:id", put(update_item_by_id))
+    // Listing and creating items are superseded by the nested /api/v2 product
+    // items; single items, stock operations and barcode lookup have no v2
+    // successor yet
+    let list = Router::new().route("/api/get_all_items", get(get_all_items))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let create = Router::new().route("/api/item", post(create_item))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    let read = Router::new().route("/api/get_item/:id", get(get_item_by_id))
+                 .route("/api/item/:id/movements", get(get_stock_movements))
+                 .route("/api/items/lookup", get(lookup_item))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/item/:id", put(update_item_by_id))
+                 .route("/api/item/:id/adjust", post(adjust_item_quantity))
+                 .route("/api/item/:id/movements", post(create_stock_movement))
+                 .route("/api/item/:id/transfers", post(create_stock_transfer))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    let admin = Router::new().route("/api/delete_item/:id", delete(delete_item_by_id))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role));
 
-}
\ No newline at end of file
+    list.merge(create)
+        .route_layer(from_fn(deprecated_v1))
+        .merge(read)
+        .merge(write)
+        .merge(admin)
+}
@@ -0,0 +1,7 @@
+use crate::handlers::metrics_handlers::get_metrics;
+use axum::{routing::get, Router};
+
+// Scraped by Prometheus without a token
+pub fn metrics_routes() -> Router {
+    Router::new().route("/metrics", get(get_metrics))
+}
@@ -1,2 +1,33 @@
 pub mod product_routes;
-pub mod item_routers;
\ No newline at end of file
+pub mod item_routers;
+pub mod warehouse_routes;
+pub mod health_routes;
+pub mod metrics_routes;
+pub mod reservation_routes;
+pub mod trash_routes;
+pub mod audit_routes;
+pub mod v2_routes;
+pub mod import_routes;
+pub mod export_routes;
+pub mod category_routes;
+pub mod variant_routes;
+pub mod price_routes;
+
+use axum::Router;
+
+// Every documented API route, before the app-wide layers are applied
+pub fn api_routes() -> Router {
+    Router::new()
+        .merge(item_routers::item_routes())
+        .merge(product_routes::product_routes())
+        .merge(warehouse_routes::warehouse_routes())
+        .merge(reservation_routes::reservation_routes())
+        .merge(trash_routes::trash_routes())
+        .merge(audit_routes::audit_routes())
+        .merge(v2_routes::v2_routes())
+        .merge(import_routes::import_routes())
+        .merge(export_routes::export_routes())
+        .merge(category_routes::category_routes())
+        .merge(variant_routes::variant_routes())
+        .merge(price_routes::price_routes())
+}
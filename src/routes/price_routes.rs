@@ -0,0 +1,25 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::price_handlers::{
+    clear_base_price, create_price_entry, create_price_list, delete_price_entry, delete_price_list,
+    get_effective_price, get_price_history, get_price_list, get_price_lists, set_base_price,
+};
+use axum::{middleware::from_fn_with_state, routing::{delete, get, post, put}, Router};
+
+pub fn price_routes() -> Router {
+    let read = Router::new().route("/api/price-lists", get(get_price_lists))
+                 .route("/api/price-lists/:id", get(get_price_list))
+                 .route("/api/item/:id/price", get(get_effective_price))
+                 .route("/api/item/:id/prices", get(get_price_history))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/price-lists", post(create_price_list))
+                 .route("/api/price-lists/:id/prices", post(create_price_entry))
+                 .route("/api/price-lists/:id/prices/:entry_id", delete(delete_price_entry))
+                 .route("/api/item/:id/base-price", put(set_base_price).delete(clear_base_price))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    let admin = Router::new().route("/api/price-lists/:id", delete(delete_price_list))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role));
+
+    read.merge(write).merge(admin)
+}
@@ -1,12 +1,20 @@
-
+use crate::auth::{require_role, Role};
+use crate::deprecation::deprecated_v1;
 use crate::handlers::product_hanlers::{create_product, get_all_products, get_product_by_uuid, delete_product, update_product};
-use axum::{routing::{delete, get, post, put}, Router};
+use axum::{middleware::{from_fn, from_fn_with_state}, routing::{delete, get, post, put}, Router};
 
+// Superseded by the /api/v2 product routes
 pub fn product_routes() -> Router {
-    Router::new().route("/api/product", post(create_product))
-                 .route("/api/get_all_products", get(get_all_products))
+    let read = Router::new().route("/api/get_all_products", get(get_all_products))
                  .route("/api/get_product/:uuid", get(get_product_by_uuid))
-                 .route("/api/delete_product/:uuid", delete(delete_product))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/product", post(create_product))
                  .route("/api/product/:uuid", put(update_product))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    let admin = Router::new().route("/api/delete_product/:uuid", delete(delete_product))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role));
 
-}
\ No newline at end of file
+    read.merge(write).merge(admin).route_layer(from_fn(deprecated_v1))
+}
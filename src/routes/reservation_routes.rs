@@ -0,0 +1,19 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::reservation_handlers::{
+    confirm_reservation, create_reservation, get_item_reservations, get_reservation,
+    release_reservation,
+};
+use axum::{middleware::from_fn_with_state, routing::{get, post}, Router};
+
+pub fn reservation_routes() -> Router {
+    let read = Router::new().route("/api/reservation/:id", get(get_reservation))
+                 .route("/api/item/:id/reservations", get(get_item_reservations))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/item/:id/reservations", post(create_reservation))
+                 .route("/api/reservation/:id/confirm", post(confirm_reservation))
+                 .route("/api/reservation/:id/release", post(release_reservation))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    read.merge(write)
+}
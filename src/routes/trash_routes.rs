@@ -0,0 +1,11 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::trash_handlers::{get_trash, restore_item, restore_product};
+use axum::{middleware::from_fn_with_state, routing::{get, post}, Router};
+
+// Deleting is an admin action, so browsing and undoing it is too
+pub fn trash_routes() -> Router {
+    Router::new().route("/api/trash", get(get_trash))
+                 .route("/api/product/:uuid/restore", post(restore_product))
+                 .route("/api/item/:id/restore", post(restore_item))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role))
+}
@@ -0,0 +1,23 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::product_v2_handlers::{
+    create_product, create_product_item, delete_product, get_product, list_product_items,
+    list_products, patch_product, replace_product,
+};
+use axum::{middleware::from_fn_with_state, routing::{delete, get, post, put}, Router};
+
+pub fn v2_routes() -> Router {
+    let read = Router::new().route("/api/v2/products", get(list_products))
+                 .route("/api/v2/products/:uuid", get(get_product))
+                 .route("/api/v2/products/:uuid/items", get(list_product_items))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/v2/products", post(create_product))
+                 .route("/api/v2/products/:uuid", put(replace_product).patch(patch_product))
+                 .route("/api/v2/products/:uuid/items", post(create_product_item))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    let admin = Router::new().route("/api/v2/products/:uuid", delete(delete_product))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role));
+
+    read.merge(write).merge(admin)
+}
@@ -0,0 +1,24 @@
+use crate::auth::{require_role, Role};
+use crate::handlers::variant_handlers::{
+    create_attribute, delete_attribute, generate_variants, get_attribute, get_attributes,
+    get_item_attributes, list_product_variants, set_item_attributes,
+};
+use axum::{middleware::from_fn_with_state, routing::{delete, get, post, put}, Router};
+
+pub fn variant_routes() -> Router {
+    let read = Router::new().route("/api/attributes", get(get_attributes))
+                 .route("/api/attributes/:id", get(get_attribute))
+                 .route("/api/item/:id/attributes", get(get_item_attributes))
+                 .route("/api/v2/products/:uuid/variants", get(list_product_variants))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/attributes", post(create_attribute))
+                 .route("/api/item/:id/attributes", put(set_item_attributes))
+                 .route("/api/v2/products/:uuid/variants/generate", post(generate_variants))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    let admin = Router::new().route("/api/attributes/:id", delete(delete_attribute))
+                 .route_layer(from_fn_with_state(Role::Admin, require_role));
+
+    read.merge(write).merge(admin)
+}
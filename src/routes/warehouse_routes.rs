@@ -0,0 +1,17 @@
+
+use crate::auth::{require_role, Role};
+use crate::handlers::warehouse_handlers::{create_location, create_warehouse, get_all_warehouses, get_locations, get_warehouse_by_id};
+use axum::{middleware::from_fn_with_state, routing::{get, post}, Router};
+
+pub fn warehouse_routes() -> Router {
+    let read = Router::new().route("/api/get_all_warehouses", get(get_all_warehouses))
+                 .route("/api/get_warehouse/:id", get(get_warehouse_by_id))
+                 .route("/api/warehouse/:id/locations", get(get_locations))
+                 .route_layer(from_fn_with_state(Role::Viewer, require_role));
+
+    let write = Router::new().route("/api/warehouse", post(create_warehouse))
+                 .route("/api/warehouse/:id/locations", post(create_location))
+                 .route_layer(from_fn_with_state(Role::Editor, require_role));
+
+    read.merge(write)
+}
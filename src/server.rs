@@ -0,0 +1,178 @@
+use std::{
+    future::Future,
+    io,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::Router;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::{conn::auto::Builder, graceful::GracefulShutdown},
+    service::TowerToHyperService,
+};
+use tokio::{net::TcpListener, task::JoinSet};
+
+// Where the process is in its lifetime. It stays `Starting` until the
+// startup checks pass; readiness is only reported while `Ready`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Starting,
+    Ready,
+    ShuttingDown,
+}
+
+impl Phase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Starting => "starting",
+            Phase::Ready => "ready",
+            Phase::ShuttingDown => "shutting_down",
+        }
+    }
+}
+
+// Shared handle to the current `Phase`, cheap to clone into handlers
+#[derive(Clone, Debug)]
+pub struct Lifecycle(Arc<AtomicU8>);
+
+impl Lifecycle {
+    pub fn new() -> Self {
+        Lifecycle(Arc::new(AtomicU8::new(Phase::Starting as u8)))
+    }
+
+    pub fn phase(&self) -> Phase {
+        match self.0.load(Ordering::Acquire) {
+            0 => Phase::Starting,
+            1 => Phase::Ready,
+            _ => Phase::ShuttingDown,
+        }
+    }
+
+    pub fn set(&self, phase: Phase) {
+        self.0.store(phase as u8, Ordering::Release);
+    }
+
+    // Moves to `to` only if still in `from`, so a late startup cannot undo
+    // a shutdown that already began
+    pub fn advance(&self, from: Phase, to: Phase) -> bool {
+        self.0
+            .compare_exchange(from as u8, to as u8, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// How the server stopped after the shutdown signal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrainOutcome {
+    // Every in-flight request finished
+    Drained,
+    // The deadline passed and unfinished requests were dropped
+    DeadlineElapsed,
+}
+
+const ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+// Errors that concern only the connection being accepted; the listener
+// itself is fine, so the next accept can follow straight away
+fn is_connection_error(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+    )
+}
+
+// Serves until `shutdown` resolves, then stops accepting connections and
+// waits up to `deadline` for in-flight requests to finish. Connections still
+// open at the deadline are aborted, which releases any pooled database
+// connections they hold.
+pub async fn serve<F>(
+    listener: TcpListener,
+    router: Router,
+    shutdown: F,
+    deadline: Duration,
+) -> DrainOutcome
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let builder = Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let stream = match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) if is_connection_error(&e) => continue,
+                    // Usually out of file descriptors. Retrying at once would
+                    // spin until some are freed, so back off like hyper does.
+                    Err(e) => {
+                        tracing::error!(error = %e, "failed to accept connection; retrying in 1s");
+                        tokio::select! {
+                            _ = tokio::time::sleep(ACCEPT_BACKOFF) => continue,
+                            _ = &mut shutdown => break,
+                        }
+                    }
+                };
+                let service = TowerToHyperService::new(router.clone());
+                let connection = builder
+                    .serve_connection(TokioIo::new(stream), service)
+                    .into_owned();
+                let connection = graceful.watch(connection);
+                connections.spawn(async move {
+                    // Errors here are client disconnects and malformed requests
+                    let _ = connection.await;
+                });
+            }
+            // Reap finished connections so the set does not grow unbounded
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            _ = &mut shutdown => break,
+        }
+    }
+    drop(listener);
+
+    match tokio::time::timeout(deadline, graceful.shutdown()).await {
+        Ok(()) => DrainOutcome::Drained,
+        Err(_) => {
+            connections.shutdown().await;
+            DrainOutcome::DeadlineElapsed
+        }
+    }
+}
+
+// Resolves on SIGINT (Ctrl+C) or, on Unix, SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install the SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install the SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!(signal = "SIGINT", "shutting down"),
+        _ = terminate => tracing::info!(signal = "SIGTERM", "shutting down"),
+    }
+}
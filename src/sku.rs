@@ -0,0 +1,81 @@
+// SKU patterns are literal text with placeholders: `{id}` is the item id and
+// `{product_id}` its product's id. A width such as `{id:06}` pads with zeros.
+// Every pattern needs `{id}`, which keeps generated SKUs unique.
+
+// The width of the `item.sku` column
+pub const MAX_LENGTH: usize = 64;
+
+pub fn validate(sku: &str) -> Result<(), String> {
+    if sku.is_empty() || sku.len() > MAX_LENGTH || sku.chars().any(char::is_whitespace) {
+        return Err(format!("sku must be 1 to {} characters without spaces", MAX_LENGTH));
+    }
+    Ok(())
+}
+
+enum Segment<'a> {
+    Literal(&'a str),
+    Id(usize),
+    ProductId(usize),
+}
+
+fn parse(pattern: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(format!("unmatched '}}' in SKU pattern '{}'", pattern));
+        }
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end)
+            .ok_or_else(|| format!("unclosed '{{' in SKU pattern '{}'", pattern))?;
+        if start > 0 {
+            segments.push(Segment::Literal(&rest[..start]));
+        }
+
+        let placeholder = &rest[start + 1..end];
+        let (name, width) = match placeholder.split_once(':') {
+            Some((name, width)) => {
+                let width = width
+                    .strip_prefix('0')
+                    .and_then(|w| w.parse::<usize>().ok())
+                    .filter(|w| *w <= 20)
+                    .ok_or_else(|| format!("'{{{}}}' needs a width like {{{}:06}}", placeholder, name))?;
+                (name, width)
+            }
+            None => (placeholder, 0),
+        };
+        segments.push(match name {
+            "id" => Segment::Id(width),
+            "product_id" => Segment::ProductId(width),
+            _ => return Err(format!("unknown placeholder '{{{}}}' in SKU pattern", name)),
+        });
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest));
+    }
+    Ok(segments)
+}
+
+pub fn check_pattern(pattern: &str) -> Result<(), String> {
+    let segments = parse(pattern)?;
+    if !segments.iter().any(|s| matches!(s, Segment::Id(_))) {
+        return Err(format!("SKU pattern '{}' must contain {{id}}", pattern));
+    }
+    // The largest ids render the longest SKU the pattern can produce
+    let longest = render(pattern, i32::MAX, i32::MAX)?;
+    validate(&longest).map_err(|e| format!("SKU pattern '{}' renders invalid SKUs: {}", pattern, e))
+}
+
+pub fn render(pattern: &str, id: i32, product_id: i32) -> Result<String, String> {
+    let mut sku = String::new();
+    for segment in parse(pattern)? {
+        match segment {
+            Segment::Literal(text) => sku.push_str(text),
+            Segment::Id(width) => sku.push_str(&format!("{:0width$}", id, width = width)),
+            Segment::ProductId(width) => sku.push_str(&format!("{:0width$}", product_id, width = width)),
+        }
+    }
+    Ok(sku)
+}
@@ -0,0 +1,22 @@
+use crate::config::NegativeStockPolicy;
+use crate::reservations;
+
+// The part of `delta` an adjustment may book at a location holding
+// `on_hand` units, `held` of them by live reservations, or the units
+// available there when the policy refuses it. Held units are never adjusted
+// away: `reject` and `clamp` work on what is available rather than on the
+// level, and `allow` only takes a location below zero while nothing there is
+// held.
+pub fn adjustable_delta(policy: NegativeStockPolicy, on_hand: i32, held: i32, delta: i32) -> Result<i32, i32> {
+    if delta >= 0 {
+        return Ok(delta);
+    }
+    let free = reservations::available(on_hand, held).max(0);
+    let takes_held = held > 0 && (on_hand as i64 + delta as i64) < held as i64;
+    match policy {
+        NegativeStockPolicy::Reject if -(delta as i64) > free as i64 => Err(free),
+        NegativeStockPolicy::Allow if takes_held => Err(free),
+        NegativeStockPolicy::Clamp => Ok(delta.max(-free)),
+        _ => Ok(delta),
+    }
+}
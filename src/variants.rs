@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use entity::attribute::AttributeType;
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+// Most items one generate request may create
+pub const MAX_GENERATED_VARIANTS: usize = 1000;
+
+// Checks a value against the attribute's type and returns the text form it
+// is stored and compared in, so `"1.50"` and `1.5` are the same decimal.
+// Numbers may be sent as JSON numbers or as strings.
+pub fn normalize_value(data_type: AttributeType, value: &Value) -> Result<String, String> {
+    let normalized = match (data_type, value) {
+        (AttributeType::Text, Value::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        (AttributeType::Integer, Value::Number(n)) => n.as_i64().map(|i| i.to_string()),
+        (AttributeType::Integer, Value::String(s)) => s.trim().parse::<i64>().ok().map(|i| i.to_string()),
+        (AttributeType::Decimal, Value::Number(n)) => parse_decimal(&n.to_string()),
+        (AttributeType::Decimal, Value::String(s)) => parse_decimal(s.trim()),
+        (AttributeType::Boolean, Value::Bool(b)) => Some(b.to_string()),
+        _ => None,
+    };
+    normalized.ok_or_else(|| format!("{} is not {}", value, expected(data_type)))
+}
+
+fn parse_decimal(s: &str) -> Option<String> {
+    Decimal::from_str(s)
+        .or_else(|_| Decimal::from_scientific(s))
+        .ok()
+        .map(|d| d.normalize().to_string())
+}
+
+fn expected(data_type: AttributeType) -> &'static str {
+    match data_type {
+        AttributeType::Text => "a non-empty string",
+        AttributeType::Integer => "an integer",
+        AttributeType::Decimal => "a decimal number",
+        AttributeType::Boolean => "true or false",
+    }
+}
+
+// The stored text form as the API returns it. Decimals stay strings so no
+// precision is lost on the way through a JSON parser.
+pub fn typed_value(data_type: AttributeType, stored: &str) -> Value {
+    match data_type {
+        AttributeType::Integer => stored.parse::<i64>().map(Value::from).unwrap_or_else(|_| stored.into()),
+        AttributeType::Boolean => stored.parse::<bool>().map(Value::from).unwrap_or_else(|_| stored.into()),
+        AttributeType::Text | AttributeType::Decimal => stored.into(),
+    }
+}
+
+// Canonical form of a set of (attribute id, normalized value) pairs,
+// independent of their order. None for an item without attributes.
+pub fn variant_key(values: &[(i32, String)]) -> Option<String> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<&(i32, String)> = values.iter().collect();
+    sorted.sort();
+    Some(serde_json::to_string(&sorted).expect("pairs of numbers and strings serialize"))
+}
+
+// Every combination taking one value from each axis, with the first axis
+// varying slowest. Returns None when there would be more than `limit`.
+pub fn cartesian<T: Clone>(axes: &[Vec<T>], limit: usize) -> Option<Vec<Vec<T>>> {
+    if axes.is_empty() {
+        return Some(Vec::new());
+    }
+    let count = axes
+        .iter()
+        .try_fold(1usize, |count, axis| count.checked_mul(axis.len()))?;
+    if count > limit {
+        return None;
+    }
+
+    let mut combinations: Vec<Vec<T>> = vec![Vec::new()];
+    for axis in axes {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|prefix| {
+                axis.iter().map(move |value| {
+                    let mut combination = prefix.clone();
+                    combination.push(value.clone());
+                    combination
+                })
+            })
+            .collect();
+    }
+    Some(combinations)
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use axum::Router;
+use common::db::{call, call_as, create_item, router, test_db, unique, AUDIT_FAILS};
+use serde_json::{json, Value};
+
+// Audit entries for one entity, oldest first
+async fn audit_entries(router: &Router, entity_type: &str, key: &Value) -> Vec<Value> {
+    let key = key.as_str().map_or_else(|| key.to_string(), str::to_string);
+    let uri = format!("/api/audit?entity_type={}&entity_key={}&sort=id", entity_type, key);
+    let (status, page) = call(router, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    page["data"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn stock_changes_and_reservations_are_audited() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (_, item_id) = create_item(&router, 10).await;
+    let item_key = json!(item_id);
+
+    let (status, _) = call(
+        &router,
+        Method::POST,
+        &format!("/api/item/{}/movements", item_id),
+        Some(json!({ "kind": "receipt", "delta": 5, "reason_code": "delivery" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, reservation) = call(
+        &router,
+        Method::POST,
+        &format!("/api/item/{}/reservations", item_id),
+        Some(json!({ "quantity": 3 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) =
+        call(&router, Method::POST, &format!("/api/reservation/{}/confirm", reservation["id"]), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let adjustments: Vec<(i64, i64)> = audit_entries(&router, "item", &item_key)
+        .await
+        .iter()
+        .filter(|e| e["action"] == "adjust")
+        .map(|e| (e["before"]["quantity"].as_i64().unwrap(), e["after"]["quantity"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(adjustments, [(10, 15), (15, 12)]);
+
+    let statuses: Vec<(Value, Value)> = audit_entries(&router, "reservation", &reservation["id"])
+        .await
+        .iter()
+        .map(|e| (e["before"]["status"].clone(), e["after"]["status"].clone()))
+        .collect();
+    assert_eq!(statuses, [(Value::Null, json!("held")), (json!("held"), json!("confirmed"))]);
+}
+
+#[tokio::test]
+async fn a_movement_whose_audit_row_fails_is_rolled_back() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (_, item_id) = create_item(&router, 10).await;
+
+    let uri = format!("/api/item/{}/movements", item_id);
+    let movement = json!({ "kind": "issue", "delta": -4, "reason_code": "sale" });
+    let (status, _) = call_as(&router, AUDIT_FAILS, Method::POST, &uri, Some(movement)).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let (_, item) = call(&router, Method::GET, &format!("/api/get_item/{}", item_id), None).await;
+    assert_eq!(item["Quantity"], 10);
+    assert_eq!(item["Locations"][0]["quantity"], 10);
+    let (_, ledger) = call(&router, Method::GET, &uri, None).await;
+    assert!(ledger["data"].as_array().unwrap().iter().all(|m| m["actor"] != AUDIT_FAILS));
+    assert!(audit_entries(&router, "item", &json!(item_id)).await.iter().all(|e| e["action"] != "adjust"));
+}
+
+// Actions on one entity, oldest first
+async fn audited_actions(router: &Router, entity_type: &str, key: &Value) -> Vec<String> {
+    audit_entries(router, entity_type, key)
+        .await
+        .iter()
+        .map(|e| e["action"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn price_lists_and_entries_are_audited() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (_, item_id) = create_item(&router, 1).await;
+
+    let list = json!({ "name": unique("audit"), "currency": "EUR" });
+    let (status, list) = call(&router, Method::POST, "/api/price-lists", Some(list)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let prices = format!("/api/price-lists/{}/prices", list["id"]);
+    let mut entries = Vec::new();
+    let schedule = [
+        ("5", "2099-01-01T00:00:00Z", None),
+        ("4", "2099-02-01T00:00:00Z", Some("2099-03-01T00:00:00Z")),
+    ];
+    for (price, from, to) in schedule {
+        let entry = json!({ "item_id": item_id, "price": price, "valid_from": from, "valid_to": to });
+        let (status, entry) = call(&router, Method::POST, &prices, Some(entry)).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", entry);
+        entries.push(entry["id"].clone());
+    }
+
+    // Cancelling the sale rejoins the regular price around it
+    let (status, _) = call(&router, Method::DELETE, &format!("{}/{}", prices, entries[1]), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(audited_actions(&router, "price_entry", &entries[0]).await, ["create", "update", "update"]);
+    assert_eq!(audited_actions(&router, "price_entry", &entries[1]).await, ["create", "delete"]);
+
+    let (status, _) = call(&router, Method::DELETE, &format!("{}/{}", prices, entries[0]), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = call(&router, Method::DELETE, &format!("/api/price-lists/{}", list["id"]), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(audited_actions(&router, "price_list", &list["id"]).await, ["create", "delete"]);
+}
+
+#[tokio::test]
+async fn categories_and_assignments_are_audited() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (uuid, _) = create_item(&router, 1).await;
+
+    let (status, category) =
+        call(&router, Method::POST, "/api/categories", Some(json!({ "name": unique("audit") }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let uri = format!("/api/categories/{}", category["id"]);
+    let (status, _) = call(&router, Method::PUT, &uri, Some(json!({ "name": unique("audit") }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let assignment = format!("{}/products/{}", uri, uuid);
+    for method in [Method::PUT, Method::PUT, Method::DELETE, Method::DELETE] {
+        let (status, _) = call(&router, method, &assignment, None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    let (status, _) = call(&router, Method::DELETE, &uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    assert_eq!(audited_actions(&router, "category", &category["id"]).await, ["create", "update", "delete"]);
+    let key = json!(format!("{}/{}", category["id"], uuid));
+    let actions = audited_actions(&router, "product_category", &key).await;
+    assert_eq!(actions, ["create", "delete"], "repeats are not logged");
+}
+
+#[tokio::test]
+async fn warehouses_and_locations_are_audited() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+
+    let warehouse = json!({ "code": unique("WH"), "name": "Audited" });
+    let (status, warehouse) = call(&router, Method::POST, "/api/warehouse", Some(warehouse)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let uri = format!("/api/warehouse/{}/locations", warehouse["id"]);
+    let (status, location) = call(&router, Method::POST, &uri, Some(json!({ "code": "BIN", "name": "Bin" }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    assert_eq!(audited_actions(&router, "warehouse", &warehouse["id"]).await, ["create"]);
+    let entries = audit_entries(&router, "location", &location["id"]).await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["after"]["warehouse_id"], warehouse["id"]);
+}
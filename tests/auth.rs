@@ -0,0 +1,65 @@
+mod common;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::{TestServer, JWT_SECRET};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::json;
+
+// An HS256 token for `roles` that expires `expires_in` seconds from now
+fn token(secret: &str, roles: &[&str], expires_in: i64) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let claims = json!({ "sub": "auth-test", "roles": roles, "exp": now + expires_in });
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+}
+
+fn bearer(token: &str) -> String {
+    format!("Authorization: Bearer {}", token)
+}
+
+// Authentication and role checks run before any query, so an unreachable
+// database does not matter
+#[tokio::test]
+async fn requests_without_a_valid_token_are_unauthorized() {
+    let server = TestServer::start().await;
+
+    let response = server.get("/api/get_all_products", &[]).await;
+    assert_eq!(response.status, 401);
+    assert_eq!(response.json()["detail"], "A bearer token is required");
+
+    let expired = token(JWT_SECRET, &["viewer"], -3600);
+    let response = server.get("/api/get_all_products", &[&bearer(&expired)]).await;
+    assert_eq!(response.status, 401);
+    assert!(response.body.contains("ExpiredSignature"), "{}", response.body);
+
+    let forged = token("another-secret-of-at-least-32-bytes", &["admin"], 600);
+    let response = server.get("/api/get_all_products", &[&bearer(&forged)]).await;
+    assert_eq!(response.status, 401);
+    assert!(response.body.contains("InvalidSignature"), "{}", response.body);
+
+    let response = server.get("/api/get_all_products", &["Authorization: Basic YTpi"]).await;
+    assert_eq!(response.status, 401);
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn tokens_without_the_required_role_are_forbidden() {
+    let server = TestServer::start().await;
+
+    let viewer = token(JWT_SECRET, &["viewer"], 600);
+    let response = server.get("/api/audit", &[&bearer(&viewer)]).await;
+    assert_eq!(response.status, 403);
+    assert_eq!(response.json()["detail"], "This operation requires the 'admin' role");
+
+    let unknown = token(JWT_SECRET, &["superuser"], 600);
+    let response = server.get("/api/get_all_products", &[&bearer(&unknown)]).await;
+    assert_eq!(response.status, 403);
+
+    // The right role gets past the checks and on to the unreachable database
+    let admin = token(JWT_SECRET, &["admin"], 600);
+    let response = server.get("/api/audit", &[&bearer(&admin)]).await;
+    assert!(![401, 403].contains(&response.status), "{}", response.status);
+
+    server.stop().await;
+}
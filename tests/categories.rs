@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Extension, Router,
+};
+use product_service::{auth::Claims, routes};
+use sea_orm::{ConnectOptions, Database};
+use tower::ServiceExt;
+
+// The pool points at a closed port, so only checks made before the first
+// query can pass
+async fn router() -> Router {
+    let mut options = ConnectOptions::new("postgres://postgres@127.0.0.1:1/unused");
+    options
+        .connect_lazy(true)
+        .acquire_timeout(Duration::from_millis(200));
+    let db = Database::connect(options).await.unwrap();
+    routes::api_routes().layer(Extension(db))
+}
+
+async fn send(router: &Router, method: Method, uri: &str, body: &str) -> (StatusCode, String) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    request.extensions_mut().insert(Claims {
+        sub: "category-test".to_string(),
+        roles: vec!["editor".to_string()],
+    });
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn a_category_cannot_become_its_own_parent() {
+    let (status, body) = send(&router().await, Method::POST, "/api/categories/7/move", r#"{"parent_id":7}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body.contains("cannot be its own parent"), "{}", body);
+}
+
+#[tokio::test]
+async fn blank_names_are_rejected() {
+    let router = router().await;
+    for (method, uri) in [(Method::POST, "/api/categories"), (Method::PUT, "/api/categories/7")] {
+        let (status, _) = send(&router, method, uri, r#"{"name":"  "}"#).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", uri);
+    }
+}
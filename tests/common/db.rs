@@ -0,0 +1,134 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Extension, Router,
+};
+use migration::{Migrator, MigratorTrait};
+use product_service::{auth::Claims, config::Config, routes};
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, Statement};
+use serde_json::Value;
+use tower::ServiceExt;
+
+// Tests that need a real database run against TEST_DATABASE_URL and are
+// skipped when it is not set. Each test names its rows uniquely, so tests
+// can share the database and run in parallel.
+pub async fn test_db() -> Option<DatabaseConnection> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping");
+        return None;
+    };
+
+    // Test binaries run at the same time, so the schema is set up under an
+    // advisory lock held on a connection of their own
+    let mut options = ConnectOptions::new(url.clone());
+    options.max_connections(1).min_connections(1);
+    let lock = Database::connect(options).await.unwrap();
+    let backend = lock.get_database_backend();
+    lock.execute(Statement::from_string(backend, "SELECT pg_advisory_lock(4711)"))
+        .await
+        .unwrap();
+    Migrator::up(&lock, None).await.unwrap();
+    install_failing_audit_trigger(&lock).await;
+    lock.execute(Statement::from_string(backend, "SELECT pg_advisory_unlock(4711)"))
+        .await
+        .unwrap();
+    lock.close().await.unwrap();
+
+    Some(Database::connect(url).await.unwrap())
+}
+
+// The API routes with the extensions `run` adds, on default settings
+pub fn router(db: &DatabaseConnection) -> Router {
+    router_with(db, Config::default())
+}
+
+pub fn router_with(db: &DatabaseConnection, config: Config) -> Router {
+    routes::api_routes()
+        .layer(Extension(db.clone()))
+        .layer(Extension(config.reservations))
+        .layer(Extension(config.stock))
+        .layer(Extension(config.import))
+        .layer(Extension(config.sku))
+}
+
+// Sends a request as a user with every role and returns the status and the
+// JSON body, or Null when the body is empty
+pub async fn call(router: &Router, method: Method, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    call_as(router, "db-test", method, uri, body).await
+}
+
+pub async fn call_as(
+    router: &Router,
+    actor: &str,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    request.extensions_mut().insert(Claims {
+        sub: actor.to_string(),
+        roles: vec!["admin".to_string(), "editor".to_string(), "viewer".to_string()],
+    });
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, json)
+}
+
+// A product with one item holding `quantity` at the default location;
+// returns the product uuid and the item id
+pub async fn create_item(router: &Router, quantity: i32) -> (String, i64) {
+    let name = unique("product");
+    let (status, product) = call(
+        router,
+        Method::POST,
+        "/api/v2/products",
+        Some(serde_json::json!({ "Name": name, "Description": "test" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", product);
+    let uuid = product["uuid"].as_str().unwrap().to_string();
+
+    let (status, item) = call(
+        router,
+        Method::POST,
+        &format!("/api/v2/products/{}/items", uuid),
+        Some(serde_json::json!({ "Name": unique("item"), "Quantity": quantity })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", item);
+    (uuid, item["id"].as_i64().unwrap())
+}
+
+// Audit inserts by this actor, or for a product or item whose name starts
+// with it, fail the way a full disk or a dropped connection would
+pub const AUDIT_FAILS: &str = "audit-fails";
+
+async fn install_failing_audit_trigger(db: &DatabaseConnection) {
+    for sql in [
+        format!(
+            "CREATE OR REPLACE FUNCTION audit_test_fail() RETURNS trigger AS $$ BEGIN \
+             IF NEW.actor = '{0}' OR NEW.after->>'name' LIKE '{0}%' THEN \
+             RAISE EXCEPTION 'audit insert refused'; END IF; \
+             RETURN NEW; END $$ LANGUAGE plpgsql",
+            AUDIT_FAILS
+        ),
+        "DROP TRIGGER IF EXISTS audit_test_fail ON audit_log".to_string(),
+        "CREATE TRIGGER audit_test_fail BEFORE INSERT ON audit_log \
+         FOR EACH ROW EXECUTE FUNCTION audit_test_fail()"
+            .to_string(),
+    ] {
+        db.execute_unprepared(&sql).await.unwrap();
+    }
+}
+
+pub fn unique(prefix: &str) -> String {
+    format!("{}-{}", prefix, uuid::Uuid::new_v4().simple())
+}
@@ -0,0 +1,108 @@
+#![allow(dead_code)]
+
+pub mod db;
+
+use std::{net::SocketAddr, time::Duration};
+
+use product_service::{config::Config, run, server::DrainOutcome};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+    task::JoinHandle,
+};
+
+// Signs the tokens the auth tests send to `TestServer`
+pub const JWT_SECRET: &str = "a-test-secret-of-at-least-32-bytes";
+
+// A full server on an ephemeral port. Its pool points at a closed port, so
+// every database call fails quickly.
+pub struct TestServer {
+    pub addr: SocketAddr,
+    trigger: oneshot::Sender<()>,
+    handle: JoinHandle<DrainOutcome>,
+}
+
+impl TestServer {
+    pub async fn start() -> Self {
+        let mut options = ConnectOptions::new("postgres://postgres@127.0.0.1:1/unused");
+        options
+            .connect_lazy(true)
+            .acquire_timeout(Duration::from_millis(200));
+        let db = Database::connect(options).await.unwrap();
+        Self::start_with(Config::default(), db).await
+    }
+
+    // A full server on the given settings and database, background tasks
+    // included
+    pub async fn start_with(mut config: Config, db: DatabaseConnection) -> Self {
+        config.jwt.secret = Some(JWT_SECRET.to_string());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (trigger, shutdown) = oneshot::channel::<()>();
+        let handle = tokio::spawn(async move {
+            run(&config, db, listener, async { shutdown.await.unwrap() }).await
+        });
+
+        TestServer { addr, trigger, handle }
+    }
+
+    // Sends a GET with the given extra header lines and returns the raw
+    // response
+    pub async fn get(&self, path: &str, headers: &[&str]) -> Response {
+        let mut stream = TcpStream::connect(self.addr).await.unwrap();
+        let mut request = format!("GET {} HTTP/1.1\r\nHost: test\r\nConnection: close\r\n", path);
+        for header in headers {
+            request.push_str(header);
+            request.push_str("\r\n");
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await.unwrap();
+        Response::parse(&raw)
+    }
+
+    pub async fn stop(self) -> DrainOutcome {
+        self.trigger.send(()).unwrap();
+        self.handle.await.unwrap()
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    // Header names are lowercase
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    fn parse(raw: &str) -> Self {
+        let (head, body) = raw.split_once("\r\n\r\n").unwrap();
+        let mut lines = head.lines();
+        let status = lines.next().unwrap()[9..12].parse().unwrap();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        Response {
+            status,
+            headers,
+            body: body.to_string(),
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
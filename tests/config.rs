@@ -0,0 +1,147 @@
+use std::{env, fs, path::PathBuf};
+
+use clap::Parser;
+use product_service::auth::AuthConfig;
+use product_service::config::{
+    Cli, Config, JwtSettings, LogLevel, NegativeStockPolicy, MAX_RETENTION_DAYS,
+};
+
+fn write_config(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("product_service_{}_{}.toml", name, std::process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+// Flags are given explicitly for every setting a test asserts on, so values
+// from the surrounding environment cannot leak in.
+fn parse(args: &[&str]) -> Cli {
+    Cli::try_parse_from(std::iter::once("product_service").chain(args.iter().copied())).unwrap()
+}
+
+#[test]
+fn flags_override_file_which_overrides_defaults() {
+    let path = write_config(
+        "layers",
+        r#"
+            [server]
+            listen_addr = "127.0.0.1:8080"
+
+            [database]
+            url = "postgres://file@localhost/db"
+            max_connections = 4
+
+            [jwt]
+            secret = "a-file-secret-of-at-least-32-bytes"
+        "#,
+    );
+
+    let config = Config::from_cli(parse(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--listen-addr",
+        "127.0.0.1:9090",
+        "--log-level",
+        "debug",
+        "--negative-stock-policy",
+        "clamp",
+    ]))
+    .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(config.server.listen_addr.to_string(), "127.0.0.1:9090");
+    assert_eq!(config.database.max_connections, 4);
+    assert_eq!(config.log.level, LogLevel::Debug);
+    assert_eq!(config.stock.negative_policy, NegativeStockPolicy::Clamp);
+    assert_eq!(config.server.request_timeout_secs, 30);
+}
+
+#[test]
+fn validation_reports_every_problem() {
+    let path = write_config(
+        "invalid",
+        r#"
+            [database]
+            max_connections = 2
+            min_connections = 5
+
+            [cors]
+            allowed_origins = ["example.com"]
+
+            [reservations]
+            default_ttl_secs = 7200
+            max_ttl_secs = 3600
+
+            [trash]
+            retention_days = 0
+
+            [sku]
+            pattern = "SKU-{product_id}"
+        "#,
+    );
+
+    let err = Config::from_cli(parse(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--database-url",
+        "mysql://localhost/db",
+        "--jwt-algorithm",
+        "RS256",
+        "--jwt-public-key",
+        "not a pem",
+    ]))
+    .unwrap_err()
+    .to_string();
+    fs::remove_file(&path).unwrap();
+
+    assert!(err.contains("database.url must be a postgres:// URL"), "{}", err);
+    assert!(err.contains("database.min_connections (5)"), "{}", err);
+    assert!(err.contains("'example.com' is not an origin"), "{}", err);
+    assert!(err.contains("reservations.default_ttl_secs (7200)"), "{}", err);
+    assert!(err.contains("trash.retention_days must be greater than 0"), "{}", err);
+    assert!(err.contains("sku.pattern: SKU pattern 'SKU-{product_id}' must contain {id}"), "{}", err);
+    assert!(err.contains("Invalid RS256 public key"), "{}", err);
+}
+
+#[test]
+fn unknown_keys_in_the_file_are_rejected() {
+    let path = write_config("unknown", "[server]\nlisten_address = \"127.0.0.1:1\"\n");
+
+    let err = Config::from_cli(parse(&["--config", path.to_str().unwrap()]))
+        .unwrap_err()
+        .to_string();
+    fs::remove_file(&path).unwrap();
+
+    assert!(err.contains("unknown field `listen_address`"), "{}", err);
+}
+
+#[test]
+fn hs256_needs_a_long_enough_secret() {
+    let missing = AuthConfig::from_settings(&JwtSettings::default()).err().unwrap();
+    assert!(missing.contains("a secret must be set for HS256"), "{}", missing);
+
+    let err = Config::from_cli(parse(&[
+        "--database-url",
+        "postgres://localhost/db",
+        "--jwt-algorithm",
+        "HS256",
+        "--jwt-secret",
+        "local-development-secret",
+    ]))
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("jwt: the HS256 secret must be at least 32 bytes long"), "{}", err);
+}
+
+#[test]
+fn trash_retention_is_bounded() {
+    let days = (MAX_RETENTION_DAYS + 1).to_string();
+    let err = Config::from_cli(parse(&[
+        "--database-url",
+        "postgres://localhost/db",
+        "--trash-retention-days",
+        &days,
+    ]))
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("trash.retention_days must not exceed 36500"), "{}", err);
+}
@@ -0,0 +1,34 @@
+use axum::{body::Body, http::Request, Router};
+use product_service::{
+    auth::Claims,
+    deprecation::{DEPRECATION_HEADER, SUNSET_HEADER},
+    routes,
+};
+use tower::ServiceExt;
+
+// No database is attached, so handlers fail; the headers must still be set
+async fn headers_of(router: &Router, uri: &str) -> axum::http::HeaderMap {
+    let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    request.extensions_mut().insert(Claims {
+        sub: "deprecation-test".to_string(),
+        roles: vec!["viewer".to_string()],
+    });
+    router.clone().oneshot(request).await.unwrap().headers().clone()
+}
+
+#[tokio::test]
+async fn only_superseded_v1_routes_are_marked_deprecated() {
+    let router = routes::api_routes();
+
+    for uri in ["/api/get_all_products", "/api/get_all_items"] {
+        let headers = headers_of(&router, uri).await;
+        assert!(headers.contains_key(DEPRECATION_HEADER), "{} lacks Deprecation", uri);
+        assert!(headers.contains_key(SUNSET_HEADER), "{} lacks Sunset", uri);
+    }
+
+    // Single items have no v2 successor yet
+    for uri in ["/api/v2/products", "/api/item/1/movements", "/api/get_item/1"] {
+        let headers = headers_of(&router, uri).await;
+        assert!(!headers.contains_key(DEPRECATION_HEADER), "{} is marked deprecated", uri);
+    }
+}
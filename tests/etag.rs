@@ -0,0 +1,31 @@
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use product_service::etag::{check_if_match, etag, is_not_modified, not_modified};
+
+fn headers(name: header::HeaderName, value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(name, HeaderValue::from_static(value));
+    headers
+}
+
+#[test]
+fn if_match_uses_strong_comparison() {
+    assert!(check_if_match(&HeaderMap::new(), 3).is_ok());
+    assert!(check_if_match(&headers(header::IF_MATCH, "*"), 3).is_ok());
+    assert!(check_if_match(&headers(header::IF_MATCH, "\"1\", \"3\""), 3).is_ok());
+
+    let stale = check_if_match(&headers(header::IF_MATCH, "\"2\""), 3).unwrap_err();
+    assert_eq!(stale.status(), StatusCode::PRECONDITION_FAILED);
+    assert!(check_if_match(&headers(header::IF_MATCH, "W/\"3\""), 3).is_err());
+}
+
+#[test]
+fn if_none_match_uses_weak_comparison() {
+    assert!(!is_not_modified(&HeaderMap::new(), 3));
+    assert!(is_not_modified(&headers(header::IF_NONE_MATCH, "W/\"3\""), 3));
+    assert!(is_not_modified(&headers(header::IF_NONE_MATCH, "\"2\", \"3\""), 3));
+    assert!(!is_not_modified(&headers(header::IF_NONE_MATCH, "\"2\""), 3));
+
+    let response = not_modified(3);
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], etag(3).as_str());
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    Extension, Router,
+};
+use product_service::{auth::Claims, routes};
+use sea_orm::{ConnectOptions, Database};
+use tower::ServiceExt;
+
+// The pool points at a closed port, so the export query always fails
+async fn router() -> Router {
+    let mut options = ConnectOptions::new("postgres://postgres@127.0.0.1:1/unused");
+    options
+        .connect_lazy(true)
+        .acquire_timeout(Duration::from_millis(200));
+    let db = Database::connect(options).await.unwrap();
+    routes::api_routes().layer(Extension(db))
+}
+
+fn get(uri: &str) -> Request<Body> {
+    let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    request.extensions_mut().insert(Claims {
+        sub: "export-test".to_string(),
+        roles: vec!["viewer".to_string()],
+    });
+    request
+}
+
+#[tokio::test]
+async fn export_query_is_checked_before_streaming() {
+    let router = router().await;
+    for (uri, status) in [
+        ("/api/export/items?join=stock", StatusCode::BAD_REQUEST),
+        ("/api/export/products?format=xml", StatusCode::BAD_REQUEST),
+        ("/api/export/items?sort=nope", StatusCode::UNPROCESSABLE_ENTITY),
+    ] {
+        let response = router.clone().oneshot(get(uri)).await.unwrap();
+        assert_eq!(response.status(), status, "{}", uri);
+    }
+}
+
+// Headers go out before the first row, so a failing query can only cut the
+// body short; it must not look like a complete, empty export
+#[tokio::test]
+async fn database_errors_abort_the_body() {
+    let response = router()
+        .await
+        .oneshot(get("/api/export/items?join=product&format=csv"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers();
+    assert_eq!(headers[header::CONTENT_TYPE], "text/csv; charset=utf-8");
+    assert_eq!(headers[header::CONTENT_DISPOSITION], "attachment; filename=\"items.csv\"");
+    assert!(to_bytes(response.into_body(), usize::MAX).await.is_err());
+}
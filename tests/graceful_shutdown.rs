@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use axum::{routing::get, Router};
+use product_service::{config::Config, run, server::{serve, DrainOutcome}};
+use sea_orm::{ConnectOptions, Database};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+
+fn slow_router(delay: Duration) -> Router {
+    Router::new().route(
+        "/slow",
+        get(move || async move {
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    )
+}
+
+// Sends a request on a fresh connection and returns the raw response, which
+// is empty when the server dropped the connection.
+async fn send_request(stream: &mut TcpStream, path: &str) -> String {
+    let request = format!("GET {} HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response).await;
+    response
+}
+
+#[tokio::test]
+async fn in_flight_requests_finish_and_new_connections_are_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (trigger, shutdown) = oneshot::channel::<()>();
+    let router = slow_router(Duration::from_millis(500));
+    let server = tokio::spawn(serve(
+        listener,
+        router,
+        async { shutdown.await.unwrap() },
+        Duration::from_secs(5),
+    ));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let in_flight = tokio::spawn(async move { send_request(&mut stream, "/slow").await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    trigger.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(TcpStream::connect(addr).await.is_err(), "listener still accepts connections");
+
+    let response = in_flight.await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("done"), "{}", response);
+    assert_eq!(server.await.unwrap(), DrainOutcome::Drained);
+}
+
+#[tokio::test]
+async fn requests_still_running_at_the_deadline_are_dropped() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (trigger, shutdown) = oneshot::channel::<()>();
+    let router = slow_router(Duration::from_secs(30));
+    let server = tokio::spawn(serve(
+        listener,
+        router,
+        async { shutdown.await.unwrap() },
+        Duration::from_millis(200),
+    ));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let in_flight = tokio::spawn(async move { send_request(&mut stream, "/slow").await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let started = Instant::now();
+    trigger.send(()).unwrap();
+    let outcome = server.await.unwrap();
+
+    assert_eq!(outcome, DrainOutcome::DeadlineElapsed);
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(in_flight.await.unwrap(), "");
+}
+
+// The pool is lazy, so no database is needed to check that `run` closes it
+#[tokio::test]
+async fn run_closes_the_database_pool_after_draining() {
+    let mut config = Config::default();
+    config.jwt.secret = Some("a-test-secret-of-at-least-32-bytes".to_string());
+    config.server.shutdown_timeout_secs = 5;
+
+    let mut options = ConnectOptions::new("postgres://postgres@127.0.0.1:1/unused");
+    options.connect_lazy(true);
+    let db = Database::connect(options).await.unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (trigger, shutdown) = oneshot::channel::<()>();
+    let server = {
+        let db = db.clone();
+        tokio::spawn(async move {
+            run(&config, db, listener, async { shutdown.await.unwrap() }).await
+        })
+    };
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let response = send_request(&mut stream, "/api/openapi.json").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    trigger.send(()).unwrap();
+    assert_eq!(server.await.unwrap(), DrainOutcome::Drained);
+    assert!(db.get_postgres_connection_pool().is_closed());
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use common::TestServer;
+
+#[tokio::test]
+async fn readiness_reports_each_component_while_the_database_is_down() {
+    let server = TestServer::start().await;
+
+    let response = server.get("/healthz", &[]).await;
+    assert_eq!(response.status, 200);
+    assert_eq!(response.json()["status"], "ok");
+
+    let response = server.get("/readyz", &[]).await;
+    let body = response.json();
+    assert_eq!(response.status, 503);
+    assert_eq!(body["status"], "starting");
+    assert_eq!(body["components"]["database"]["status"], "down");
+    assert_eq!(body["components"]["migrations"]["status"], "down");
+
+    server.stop().await;
+}
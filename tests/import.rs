@@ -0,0 +1,133 @@
+mod common;
+
+use common::db::{test_db, unique, AUDIT_FAILS};
+use product_service::config::{ImportFormat, SkuSettings};
+use product_service::import::{parse, run_import, ImportOptions};
+use sea_orm::DatabaseConnection;
+
+const CSV: &str = "\
+product_uuid,product_name,product_description,item_name,item_quantity
+6f1c1f0e-6a55-4d7a-9d6e-8b0a3f3f1a11,Chair,Oak,Chair leg,4
+6f1c1f0e-6a55-4d7a-9d6e-8b0a3f3f1a11,Chair,Pine,Chair seat,1
+,Table,,Table top,-2
+not-a-uuid,Lamp,,,
+,,No name,,
+,Desk,,,3
+";
+
+#[test]
+fn rows_keep_their_file_lines() {
+    let rows = parse(ImportFormat::Csv, CSV.as_bytes());
+    let lines: Vec<u64> = rows.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, [2, 3, 4, 5, 6, 7]);
+    assert!(rows[3].1.is_err(), "a malformed UUID fails to parse");
+
+    let ndjson = "{\"product_name\":\"Chair\"}\n\n{\"product_name\":\n";
+    let rows = parse(ImportFormat::Ndjson, ndjson.as_bytes());
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].0, 3);
+    assert!(rows[0].1.is_ok() && rows[1].1.is_err());
+}
+
+#[test]
+fn format_is_guessed_from_name_or_content_type() {
+    assert_eq!(ImportFormat::from_file_name("supplier.CSV"), Some(ImportFormat::Csv));
+    assert_eq!(ImportFormat::from_file_name("supplier.jsonl"), Some(ImportFormat::Ndjson));
+    assert_eq!(ImportFormat::from_file_name("supplier"), None);
+    assert_eq!(ImportFormat::from_content_type("text/csv; charset=utf-8"), Some(ImportFormat::Csv));
+}
+
+// Invalid rows stop the import before the database is touched, so a
+// disconnected handle is enough
+#[tokio::test]
+async fn invalid_rows_are_reported_by_line_and_nothing_is_written() {
+    let options = ImportOptions { dry_run: false, chunk_size: 0, skus: SkuSettings::default() };
+    let report = run_import(
+        &DatabaseConnection::Disconnected,
+        "import-test",
+        parse(ImportFormat::Csv, CSV.as_bytes()),
+        options,
+    )
+    .await
+    .unwrap();
+
+    let lines: Vec<u64> = report.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, [3, 4, 5, 6, 7]);
+    assert_eq!(report.rows, 6);
+    assert_eq!(report.chunks_committed, 0);
+    assert_eq!(report.products_created, 0);
+}
+
+// A database failure in a later chunk is reported on its line, and the
+// chunks before it stay committed
+#[tokio::test]
+async fn a_failing_chunk_keeps_the_report_of_earlier_ones() {
+    let Some(db) = test_db().await else { return };
+    let csv = format!(
+        "product_name,item_name,item_quantity\n{},Leg,4\n{},Seat,1\n{},Top,2\n",
+        unique("import-chunk"),
+        unique(AUDIT_FAILS),
+        unique("import-chunk")
+    );
+    let options = ImportOptions { dry_run: false, chunk_size: 1, skus: SkuSettings::default() };
+    let report = run_import(&db, "import-test", parse(ImportFormat::Csv, csv.as_bytes()), options)
+        .await
+        .unwrap();
+
+    assert_eq!(report.chunks, 3);
+    assert_eq!(report.chunks_committed, 1);
+    assert_eq!(report.products_created, 1);
+    assert_eq!(report.items_created, 1);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].line, 3);
+    assert_eq!(report.errors[0].message, "An unexpected database error occurred");
+}
+
+// Products without a UUID are found by name, so the fixed file can simply be
+// imported again
+#[tokio::test]
+async fn re_running_a_fixed_file_skips_what_was_committed() {
+    let Some(db) = test_db().await else { return };
+    let (first, bad, last) = (unique("import-rerun"), unique(AUDIT_FAILS), unique("import-rerun"));
+    let import = |csv: String| {
+        let db = db.clone();
+        async move {
+            let options = ImportOptions { dry_run: false, chunk_size: 1, skus: SkuSettings::default() };
+            run_import(&db, "import-test", parse(ImportFormat::Csv, csv.as_bytes()), options)
+                .await
+                .unwrap()
+        }
+    };
+
+    let header = "product_name,item_name,item_quantity\n";
+    let report = import(format!("{}{},Leg,4\n{},Seat,1\n{},Top,2\n", header, first, bad, last)).await;
+    assert_eq!((report.chunks_committed, report.products_created), (1, 1));
+
+    let report = import(format!("{}{},Leg,4\n{},Top,2\n", header, first, last)).await;
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!(report.chunks_committed, 2);
+    assert_eq!((report.products_created, report.items_created), (1, 1));
+    assert_eq!((report.products_updated, report.items_updated), (0, 0));
+}
+
+// A dry run reports every chunk that would fail, not just the first
+#[tokio::test]
+async fn a_dry_run_goes_on_past_failing_chunks() {
+    let Some(db) = test_db().await else { return };
+    let csv = format!(
+        "product_name,item_name,item_quantity\n{},Leg,4\n{},Seat,1\n{},Top,2\n{},Lid,1\n",
+        unique(AUDIT_FAILS),
+        unique("import-dry"),
+        unique(AUDIT_FAILS),
+        unique("import-dry")
+    );
+    let options = ImportOptions { dry_run: true, chunk_size: 1, skus: SkuSettings::default() };
+    let report = run_import(&db, "import-test", parse(ImportFormat::Csv, csv.as_bytes()), options)
+        .await
+        .unwrap();
+
+    let lines: Vec<u64> = report.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, [2, 4]);
+    assert_eq!(report.chunks_committed, 0);
+    assert_eq!(report.products_created, 2);
+}
@@ -0,0 +1,45 @@
+use product_service::{gtin, sku};
+
+#[test]
+fn gtin_check_digits_are_verified() {
+    // EAN-13, UPC-A, GTIN-8 and GTIN-14
+    for code in ["4006381333931", "036000291452", "12345670", "10012345678902"] {
+        assert_eq!(gtin::validate(code), Ok(()), "{}", code);
+    }
+
+    assert!(gtin::validate("4006381333932").unwrap_err().contains("should have 1"));
+    assert!(gtin::validate("40063813339").unwrap_err().contains("11 digits"));
+    assert!(gtin::validate("4006-381333931").unwrap_err().contains("only digits"));
+    assert!(gtin::validate("").is_err());
+}
+
+#[test]
+fn shorter_gtins_pad_to_fourteen_digits() {
+    assert_eq!(gtin::to_gtin14("4006381333931"), "04006381333931");
+    assert_eq!(gtin::to_gtin14("12345670"), "00000012345670");
+    assert_eq!(gtin::check_digit("400638133393"), 1);
+}
+
+#[test]
+fn sku_patterns_render_placeholders() {
+    assert_eq!(sku::render("ITEM-{id:06}", 42, 7).unwrap(), "ITEM-000042");
+    assert_eq!(sku::render("P{product_id:03}-{id}", 42, 7).unwrap(), "P007-42");
+    assert_eq!(sku::render("{id}", 1234567, 7).unwrap(), "1234567");
+
+    assert!(sku::check_pattern("ITEM-{id:06}").is_ok());
+    assert!(sku::check_pattern("P{product_id}").unwrap_err().contains("must contain {id}"));
+    assert!(sku::check_pattern("ITEM-{id").unwrap_err().contains("unclosed"));
+    assert!(sku::check_pattern("{id:6}").is_err());
+    assert!(sku::check_pattern("{sku}").unwrap_err().contains("unknown placeholder"));
+    assert!(sku::check_pattern("ITEM {id}").unwrap_err().contains("renders invalid SKUs"));
+    assert!(sku::check_pattern(&format!("{}{{id}}", "X".repeat(55))).unwrap_err().contains("renders invalid SKUs"));
+    assert!(sku::check_pattern(&format!("{}{{id}}", "X".repeat(54))).is_ok());
+}
+
+#[test]
+fn skus_are_checked_for_length_and_spaces() {
+    assert!(sku::validate("ABC-123").is_ok());
+    assert!(sku::validate("").is_err());
+    assert!(sku::validate("has space").is_err());
+    assert!(sku::validate(&"x".repeat(sku::MAX_LENGTH + 1)).is_err());
+}
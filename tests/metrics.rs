@@ -0,0 +1,55 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    Extension,
+};
+use common::TestServer;
+use product_service::{metrics::Metrics, routes::metrics_routes::metrics_routes};
+use sea_orm::DatabaseConnection;
+use tower::ServiceExt;
+
+// The database is unreachable, so the inventory gauges stay at zero while
+// HTTP and pool metrics are still exported
+#[tokio::test]
+async fn requests_are_counted_by_route_template() {
+    let server = TestServer::start().await;
+
+    server.get("/api/get_product/00000000-0000-0000-0000-000000000000", &[]).await;
+    server.get("/api/get_product/11111111-1111-1111-1111-111111111111", &[]).await;
+    server.get("/no/such/path", &[]).await;
+    let response = server.get("/metrics", &[]).await;
+
+    assert_eq!(response.header("content-type"), Some("text/plain; version=0.0.4"));
+    for line in [
+        r#"http_requests_total{method="GET",route="/api/get_product/:uuid",status="401"} 2"#,
+        r#"http_requests_total{method="GET",route="unmatched",status="404"} 1"#,
+        r#"http_request_duration_seconds_count{method="GET",route="/api/get_product/:uuid"} 2"#,
+        "db_pool_max_connections 10",
+        "inventory_products 0",
+    ] {
+        assert!(response.body.contains(line), "missing `{}` in\n{}", line, response.body);
+    }
+
+    server.stop().await;
+}
+
+// Scrapes only read what is already gathered: no query is run and a
+// connection that is not a Postgres pool has no pool stats to report
+#[tokio::test]
+async fn scrapes_do_not_touch_the_database() {
+    let router = metrics_routes()
+        .layer(Extension(DatabaseConnection::Disconnected))
+        .layer(Extension(Arc::new(Metrics::new())));
+
+    let request = Request::builder().uri("/metrics").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("db_pool_max_connections 0"), "{}", body);
+    assert!(body.contains("inventory_items 0"), "{}", body);
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeSet;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use product_service::{auth::Claims, openapi::ApiDoc, routes};
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+const METHODS: [Method; 5] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+];
+
+// axum has no public route listing, but its `Debug` output includes every
+// registered path. Paths are converted to the OpenAPI `{param}` syntax.
+fn router_paths(router: &Router) -> BTreeSet<String> {
+    format!("{:?}", router)
+        .split('"')
+        .filter(|s| s.starts_with('/'))
+        .map(|path| {
+            path.split('/')
+                .map(|seg| match seg.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => seg.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
+
+fn concrete_uri(path: &str) -> String {
+    path.split('/')
+        .map(|seg| match seg {
+            "{uuid}" => "00000000-0000-0000-0000-000000000000",
+            s if s.starts_with('{') => "1",
+            s => s,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Requests carry admin claims so they get past the role guards. A mounted
+// route then fails in its handler (there is no database), while an unknown
+// method gets 405 and an unknown path 404.
+async fn is_routed(router: &Router, method: &Method, path: &str) -> bool {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(concrete_uri(path))
+        .body(Body::empty())
+        .unwrap();
+    request.extensions_mut().insert(Claims {
+        sub: "openapi-test".to_string(),
+        roles: vec!["admin".to_string()],
+    });
+    let status = router.clone().oneshot(request).await.unwrap().status();
+    status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED
+}
+
+fn spec_operations() -> BTreeSet<(String, String)> {
+    let spec = ApiDoc::openapi();
+    let mut operations = BTreeSet::new();
+    for (path, item) in spec.paths.paths {
+        let methods = [
+            (Method::GET, item.get.is_some()),
+            (Method::POST, item.post.is_some()),
+            (Method::PUT, item.put.is_some()),
+            (Method::PATCH, item.patch.is_some()),
+            (Method::DELETE, item.delete.is_some()),
+        ];
+        for (method, present) in methods {
+            if present {
+                operations.insert((method.to_string(), path.clone()));
+            }
+        }
+    }
+    operations
+}
+
+#[tokio::test]
+async fn spec_matches_mounted_routes() {
+    let router = routes::api_routes()
+        .merge(routes::health_routes::health_routes())
+        .merge(routes::metrics_routes::metrics_routes());
+
+    let mut routed = BTreeSet::new();
+    for path in router_paths(&router) {
+        for method in METHODS {
+            if is_routed(&router, &method, &path).await {
+                routed.insert((method.to_string(), path.clone()));
+            }
+        }
+    }
+
+    let documented = spec_operations();
+    let undocumented: Vec<_> = routed.difference(&documented).collect();
+    let unrouted: Vec<_> = documented.difference(&routed).collect();
+
+    assert!(
+        undocumented.is_empty(),
+        "routes missing from the OpenAPI document: {:?}",
+        undocumented
+    );
+    assert!(
+        unrouted.is_empty(),
+        "OpenAPI operations with no matching route: {:?}",
+        unrouted
+    );
+}
+
+#[test]
+fn spec_is_openapi_3_1() {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    assert_eq!(spec["openapi"], "3.1.0");
+}
@@ -0,0 +1,62 @@
+use axum::http::{StatusCode, Uri};
+use entity::item::{Column, Entity as ItemEntity};
+use product_service::pagination::{apply_sort, contains_pattern, page_links, PageParams, MAX_LIMIT};
+use sea_orm::{DbBackend, EntityTrait, QueryTrait};
+
+#[test]
+fn page_params_are_bounded() {
+    let params = PageParams::new(None, None).unwrap();
+    assert_eq!((params.page, params.limit), (1, 20));
+    let params = PageParams::new(Some(3), Some(MAX_LIMIT)).unwrap();
+    assert_eq!((params.page, params.limit), (3, MAX_LIMIT));
+
+    let cases = [
+        (Some(0), None),
+        (None, Some(0)),
+        (None, Some(MAX_LIMIT + 1)),
+        (Some(1_000_000_000_000_000_000), Some(MAX_LIMIT)),
+        (Some(u64::MAX), Some(1)),
+    ];
+    for (page, limit) in cases {
+        let err = PageParams::new(page, limit).unwrap_err();
+        assert_eq!(err.status(), StatusCode::UNPROCESSABLE_ENTITY, "{:?} {:?}", page, limit);
+    }
+    let last = i64::MAX as u64 / MAX_LIMIT + 1;
+    assert!(PageParams::new(Some(last), Some(MAX_LIMIT)).is_ok());
+}
+
+#[test]
+fn like_wildcards_are_escaped() {
+    assert_eq!(contains_pattern("bolt"), "%bolt%");
+    assert_eq!(contains_pattern("50%"), "%50\\%%");
+    assert_eq!(contains_pattern("a_b"), "%a\\_b%");
+    assert_eq!(contains_pattern("c:\\d"), "%c:\\\\d%");
+}
+
+#[test]
+fn sort_keys_map_to_columns() {
+    let select = apply_sort(ItemEntity::find(), Some("name,-quantity"), Column::Id).unwrap();
+    let sql = select.build(DbBackend::Postgres).to_string();
+    assert!(
+        sql.ends_with(r#"ORDER BY "item"."name" ASC, "item"."quantity" DESC, "item"."id" ASC"#),
+        "{}",
+        sql
+    );
+
+    let err = apply_sort(ItemEntity::find(), Some("name,-secret"), Column::Id).unwrap_err();
+    assert_eq!(err.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(err.detail().contains("unknown sort column 'secret'"), "{}", err.detail());
+}
+
+#[test]
+fn page_links_keep_the_other_parameters() {
+    let uri: Uri = "/api/get_all_items?page=2&limit=5&name=bolt".parse().unwrap();
+    let (next, prev) = page_links(&uri, 2, 3);
+    assert_eq!(next.as_deref(), Some("/api/get_all_items?limit=5&name=bolt&page=3"));
+    assert_eq!(prev.as_deref(), Some("/api/get_all_items?limit=5&name=bolt&page=1"));
+
+    let uri: Uri = "/api/get_all_items".parse().unwrap();
+    assert_eq!(page_links(&uri, 1, 1), (None, None));
+    assert_eq!(page_links(&uri, 1, 2).0.as_deref(), Some("/api/get_all_items?page=2"));
+    assert_eq!(page_links(&uri, 1, 0), (None, None));
+}
@@ -0,0 +1,159 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+mod common;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    Extension, Router,
+};
+use common::db::{call, create_item, test_db, unique};
+use product_service::{auth::Claims, currency, routes};
+use rust_decimal::Decimal;
+use sea_orm::{ConnectOptions, Database};
+use serde_json::json;
+use tower::ServiceExt;
+
+// The pool points at a closed port, so only checks made before the first
+// query can pass
+async fn router() -> Router {
+    let mut options = ConnectOptions::new("postgres://postgres@127.0.0.1:1/unused");
+    options
+        .connect_lazy(true)
+        .acquire_timeout(Duration::from_millis(200));
+    let db = Database::connect(options).await.unwrap();
+    routes::api_routes().layer(Extension(db))
+}
+
+async fn send(router: &Router, method: Method, uri: &str, body: &str) -> (StatusCode, String) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    request.extensions_mut().insert(Claims {
+        sub: "pricing-test".to_string(),
+        roles: vec!["editor".to_string()],
+    });
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+fn dec(s: &str) -> Decimal {
+    Decimal::from_str(s).unwrap()
+}
+
+#[test]
+fn amounts_follow_the_currency_minor_unit() {
+    assert_eq!(currency::parse(" eur ").unwrap(), "EUR");
+    assert!(currency::parse("EURO").is_err());
+    assert!(currency::parse("XYZ").is_err());
+    assert_eq!(currency::minor_units("JPY"), Some(0));
+    assert_eq!(currency::minor_units("KWD"), Some(3));
+
+    assert!(currency::check_amount(dec("12.50"), "USD").is_ok());
+    assert!(currency::check_amount(dec("12.5000"), "USD").is_ok());
+    assert!(currency::check_amount(dec("0"), "USD").is_ok());
+    assert!(currency::check_amount(dec("1.005"), "USD").unwrap_err().contains("2 decimal places"));
+    assert!(currency::check_amount(dec("10.5"), "JPY").is_err());
+    assert!(currency::check_amount(dec("-1"), "USD").unwrap_err().contains("negative"));
+
+    assert_eq!(currency::display(dec("12.5000"), "USD").to_string(), "12.50");
+    assert_eq!(currency::display(dec("1500.0000"), "JPY").to_string(), "1500");
+    assert_eq!(currency::display(dec("1.5"), "BHD").to_string(), "1.500");
+}
+
+#[tokio::test]
+async fn invalid_prices_are_rejected_before_any_query() {
+    let router = router().await;
+    let cases = [
+        (Method::POST, "/api/price-lists", r#"{"name":"Retail","currency":"XYZ"}"#, "ISO 4217"),
+        (Method::POST, "/api/price-lists", r#"{"name":" ","currency":"EUR"}"#, "name must not be empty"),
+        (Method::PUT, "/api/item/1/base-price", r#"{"price":"9.999","currency":"USD"}"#, "decimal places"),
+        (Method::PUT, "/api/item/1/base-price", r#"{"price":"-2","currency":"USD"}"#, "negative"),
+        (
+            Method::POST,
+            "/api/price-lists/1/prices",
+            r#"{"item_id":1,"price":"5","valid_from":"2026-02-01T00:00:00Z","valid_to":"2026-01-01T00:00:00Z"}"#,
+            "valid_to must be after valid_from",
+        ),
+        (
+            Method::POST,
+            "/api/price-lists/1/prices",
+            r#"{"item_id":1,"price":"5","valid_from":"2026-01-01T00:00:00Z"}"#,
+            "valid_from must not be in the past",
+        ),
+    ];
+    for (method, uri, body, expected) in cases {
+        let (status, problem) = send(&router, method, uri, body).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{} {}", uri, body);
+        assert!(problem.contains(expected), "{}", problem);
+    }
+}
+
+// Prices of a trashed item are left alone until it is restored
+#[tokio::test]
+async fn scheduled_prices_of_a_trashed_item_are_not_cancelled() {
+    let Some(db) = test_db().await else { return };
+    let router = common::db::router(&db);
+    let (_, item_id) = create_item(&router, 1).await;
+
+    let list = json!({ "name": unique("pricing"), "currency": "EUR" });
+    let (status, list) = call(&router, Method::POST, "/api/price-lists", Some(list)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let entry = json!({ "item_id": item_id, "price": "5", "valid_from": "2099-01-01T00:00:00Z" });
+    let prices = format!("/api/price-lists/{}/prices", list["id"]);
+    let (status, entry) = call(&router, Method::POST, &prices, Some(entry)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let entry_uri = format!("{}/{}", prices, entry["id"]);
+
+    let (status, _) = call(&router, Method::DELETE, &format!("/api/delete_item/{}", item_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, problem) = call(&router, Method::DELETE, &entry_uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", problem);
+
+    let (status, _) = call(&router, Method::POST, &format!("/api/item/{}/restore", item_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&router, Method::DELETE, &entry_uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+// The base price stands in only for the present, and only in the list's
+// currency
+#[tokio::test]
+async fn the_base_price_answers_only_for_now_in_its_currency() {
+    let Some(db) = test_db().await else { return };
+    let router = common::db::router(&db);
+    let (_, item_id) = create_item(&router, 1).await;
+    let base = json!({ "price": "9.99", "currency": "USD" });
+    let (status, _) = call(&router, Method::PUT, &format!("/api/item/{}/base-price", item_id), Some(base)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut lists = Vec::new();
+    for currency in ["USD", "EUR"] {
+        let list = json!({ "name": unique("pricing"), "currency": currency });
+        let (status, list) = call(&router, Method::POST, "/api/price-lists", Some(list)).await;
+        assert_eq!(status, StatusCode::CREATED);
+        lists.push(list["id"].clone());
+    }
+
+    let price = format!("/api/item/{}/price", item_id);
+    let cases = [
+        (price.clone(), StatusCode::OK),
+        (format!("{}?price_list={}", price, lists[0]), StatusCode::OK),
+        (format!("{}?price_list={}", price, lists[1]), StatusCode::NOT_FOUND),
+        (format!("{}?at=2026-01-01T00:00:00Z", price), StatusCode::NOT_FOUND),
+        (format!("{}?price_list={}&at=2026-01-01T00:00:00Z", price, lists[0]), StatusCode::NOT_FOUND),
+    ];
+    for (uri, expected) in cases {
+        let (status, body) = call(&router, Method::GET, &uri, None).await;
+        assert_eq!(status, expected, "{}: {}", uri, body);
+        if status == StatusCode::OK {
+            assert_eq!((body["source"].as_str(), body["currency"].as_str()), (Some("base"), Some("USD")));
+        }
+    }
+}
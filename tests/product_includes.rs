@@ -0,0 +1,100 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::{Method, StatusCode};
+use axum::{Extension, Router};
+use common::db::{call, router, test_db, unique};
+use product_service::routes;
+use sea_orm::{ConnectOptions, Database};
+use serde_json::{json, Value};
+
+// The pool points at a closed port, so every request that gets as far as a
+// query fails with 500
+async fn offline_router() -> Router {
+    let mut options = ConnectOptions::new("postgres://postgres@127.0.0.1:1/unused");
+    options
+        .connect_lazy(true)
+        .acquire_timeout(Duration::from_millis(200));
+    let db = Database::connect(options).await.unwrap();
+    routes::api_routes().layer(Extension(db))
+}
+
+#[tokio::test]
+async fn unknown_includes_are_rejected_before_any_query() {
+    let router = offline_router().await;
+    let uuid = "00000000-0000-0000-0000-000000000000";
+
+    for uri in [
+        "/api/get_all_products?include=variants".to_string(),
+        "/api/get_all_products?include=items,Items".to_string(),
+        format!("/api/get_product/{}?include=items,,prices", uuid),
+        format!("/api/v2/products/{}?include=aggregates;items", uuid),
+    ] {
+        let (status, body) = call(&router, Method::GET, &uri, None).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}: {}", uri, body);
+        assert!(body["detail"].as_str().unwrap().starts_with("cannot include '"), "{}", body);
+    }
+
+    let (status, _) = call(&router, Method::GET, "/api/get_all_products?include=%20,%20", None).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "nothing to include is not an error");
+}
+
+async fn create_product(router: &Router) -> Value {
+    let (status, product) = call(
+        router,
+        Method::POST,
+        "/api/v2/products",
+        Some(json!({ "Name": unique("product"), "Description": "test" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    product
+}
+
+#[tokio::test]
+async fn blanks_and_whitespace_between_includes_are_ignored() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let product = create_product(&router).await;
+
+    let uri = format!("/api/get_product/{}?include=%20items%20,,aggregates%20,", product["uuid"].as_str().unwrap());
+    let (status, body) = call(&router, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["Items"].is_array());
+    assert!(body["Aggregates"].is_object());
+
+    let uri = format!("/api/get_product/{}", product["uuid"].as_str().unwrap());
+    let (_, plain) = call(&router, Method::GET, &uri, None).await;
+    assert!(plain.get("Items").is_none() && plain.get("Aggregates").is_none(), "{}", plain);
+}
+
+#[tokio::test]
+async fn products_without_items_get_zeroed_aggregates() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let empty = create_product(&router).await;
+    let uuid = empty["uuid"].as_str().unwrap();
+
+    let uri = format!("/api/v2/products/{}?include=items,aggregates", uuid);
+    let (status, body) = call(&router, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["Items"], json!([]));
+    assert_eq!(body["Aggregates"], json!({ "item_count": 0, "total_quantity": 0 }));
+
+    // Items in the trash do not count either
+    let (status, item) = call(
+        &router,
+        Method::POST,
+        &format!("/api/v2/products/{}/items", uuid),
+        Some(json!({ "Name": unique("item"), "Quantity": 3 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = call(&router, Method::DELETE, &format!("/api/delete_item/{}", item["id"]), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!("/api/v2/products/{}?include=aggregates", uuid);
+    let (_, body) = call(&router, Method::GET, &uri, None).await;
+    assert_eq!(body["Aggregates"], json!({ "item_count": 0, "total_quantity": 0 }));
+}
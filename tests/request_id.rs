@@ -0,0 +1,31 @@
+mod common;
+
+use common::TestServer;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn a_sent_request_id_is_echoed_in_the_header_and_error_body() {
+    let server = TestServer::start().await;
+
+    let response = server.get("/api/get_all_products", &["X-Request-Id: trace-abc-123"]).await;
+
+    assert_eq!(response.status, 401);
+    assert_eq!(response.header("x-request-id"), Some("trace-abc-123"));
+    assert_eq!(response.json()["request_id"], "trace-abc-123");
+
+    server.stop().await;
+}
+
+#[tokio::test]
+async fn a_missing_or_unusable_request_id_is_replaced_by_a_uuid() {
+    let server = TestServer::start().await;
+
+    let too_long = format!("X-Request-Id: {}", "x".repeat(200));
+    for headers in [vec![], vec![too_long.as_str()]] {
+        let response = server.get("/healthz", &headers).await;
+        let id = response.header("x-request-id").unwrap();
+        assert!(Uuid::parse_str(id).is_ok(), "{}", id);
+    }
+
+    server.stop().await;
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use common::db::{call, create_item, router, test_db};
+use entity::reservation::ReservationStatus::{self, Confirmed, Expired, Held, Released};
+use product_service::reservations::{available, is_live, resolve, Resolution};
+use serde_json::json;
+
+fn now() -> DateTime<FixedOffset> {
+    Utc::now().fixed_offset()
+}
+
+#[test]
+fn only_unexpired_held_reservations_hold_stock() {
+    let now = now();
+    let later = now + Duration::seconds(60);
+    let earlier = now - Duration::seconds(60);
+
+    assert!(is_live(Held, later, now));
+    assert!(!is_live(Held, now, now), "a reservation stops holding at its expiry");
+    assert!(!is_live(Held, earlier, now), "expired but not yet swept");
+    for status in [Confirmed, Released, Expired] {
+        assert!(!is_live(status, later, now), "{:?}", status);
+    }
+}
+
+#[test]
+fn available_is_on_hand_less_held() {
+    assert_eq!(available(10, 0), 10);
+    assert_eq!(available(10, 4), 6);
+    assert_eq!(available(4, 4), 0);
+    assert_eq!(available(-2, 0), -2);
+    assert_eq!(available(3, 5), -2, "a level already below its holds has nothing free");
+}
+
+#[test]
+fn callers_resolve_held_reservations_until_they_expire() {
+    let now = now();
+    let later = now + Duration::seconds(60);
+    let earlier = now - Duration::seconds(60);
+
+    assert_eq!(resolve(Held, later, Resolution::Confirm, now), Ok(Confirmed));
+    assert_eq!(resolve(Held, later, Resolution::Release, now), Ok(Released));
+    assert_eq!(resolve(Held, earlier, Resolution::Confirm, now), Err("has expired".to_string()));
+    assert_eq!(resolve(Held, earlier, Resolution::Release, now), Err("has expired".to_string()));
+}
+
+#[test]
+fn the_sweeper_only_expires_held_reservations_past_their_expiry() {
+    let now = now();
+    let later = now + Duration::seconds(60);
+
+    assert_eq!(resolve(Held, now, Resolution::Expire, now), Ok(Expired));
+    assert_eq!(resolve(Held, now - Duration::days(1), Resolution::Expire, now), Ok(Expired));
+    assert!(resolve(Held, later, Resolution::Expire, now).is_err());
+}
+
+#[test]
+fn resolved_reservations_never_change_again() {
+    let now = now();
+    let later = now + Duration::seconds(60);
+    let terminal: [ReservationStatus; 3] = [Confirmed, Released, Expired];
+
+    for status in terminal {
+        for resolution in [Resolution::Confirm, Resolution::Release, Resolution::Expire] {
+            let err = resolve(status, later, resolution, now).unwrap_err();
+            assert!(err.contains("can no longer change"), "{:?} {:?}: {}", status, resolution, err);
+        }
+    }
+    assert_eq!(
+        resolve(Confirmed, later, Resolution::Release, now),
+        Err("is confirmed and can no longer change".to_string())
+    );
+}
+
+#[tokio::test]
+async fn held_units_are_not_available_to_others_until_released() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (_, item_id) = create_item(&router, 10).await;
+    let reserve = format!("/api/item/{}/reservations", item_id);
+
+    let (status, held) = call(&router, Method::POST, &reserve, Some(json!({ "quantity": 7 }))).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", held);
+
+    let (status, body) = call(&router, Method::POST, &reserve, Some(json!({ "quantity": 4 }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["detail"], format!("item '{}' has 3 units available but 4 were requested", item_id));
+
+    let release = format!("/api/reservation/{}/release", held["id"]);
+    let (status, released) = call(&router, Method::POST, &release, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(released["status"], "released");
+    let (status, body) = call(&router, Method::POST, &release, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["detail"], format!("reservation '{}' is released and can no longer change", held["id"]));
+
+    let (status, _) = call(&router, Method::POST, &reserve, Some(json!({ "quantity": 10 }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+// Confirming locks the item before the reservation, as deleting the item
+// does, so the two never deadlock
+#[tokio::test]
+async fn confirming_while_the_item_is_deleted_does_not_deadlock() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+
+    for _ in 0..10 {
+        let (_, item_id) = create_item(&router, 10).await;
+        let reserve = format!("/api/item/{}/reservations", item_id);
+        let (status, held) = call(&router, Method::POST, &reserve, Some(json!({ "quantity": 2 }))).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", held);
+
+        let confirm = format!("/api/reservation/{}/confirm", held["id"]);
+        let delete = format!("/api/delete_item/{}", item_id);
+        let ((confirmed, body), (deleted, _)) = tokio::join!(
+            call(&router, Method::POST, &confirm, None),
+            call(&router, Method::DELETE, &delete, None),
+        );
+        assert_eq!(deleted, StatusCode::OK);
+        assert!(
+            [StatusCode::OK, StatusCode::NOT_FOUND, StatusCode::CONFLICT].contains(&confirmed),
+            "{}: {}",
+            confirmed,
+            body
+        );
+    }
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use axum::Router;
+use common::db::{call, create_item, router_with, test_db, unique};
+use product_service::config::{Config, NegativeStockPolicy};
+use product_service::stock::adjustable_delta;
+use serde_json::{json, Value};
+
+use NegativeStockPolicy::{Allow, Clamp, Reject};
+
+#[test]
+fn additions_are_booked_in_full_under_every_policy() {
+    for policy in [Reject, Allow, Clamp] {
+        assert_eq!(adjustable_delta(policy, 0, 0, 5), Ok(5));
+        assert_eq!(adjustable_delta(policy, -3, 2, 5), Ok(5));
+    }
+}
+
+#[test]
+fn reject_refuses_more_than_is_free() {
+    assert_eq!(adjustable_delta(Reject, 10, 0, -10), Ok(-10));
+    assert_eq!(adjustable_delta(Reject, 10, 0, -11), Err(10));
+    assert_eq!(adjustable_delta(Reject, 10, 7, -3), Ok(-3));
+    assert_eq!(adjustable_delta(Reject, 10, 7, -4), Err(3), "held units are not available");
+    assert_eq!(adjustable_delta(Reject, 0, 0, -5), Err(0));
+}
+
+#[test]
+fn clamp_takes_only_what_is_free() {
+    assert_eq!(adjustable_delta(Clamp, 10, 0, -4), Ok(-4));
+    assert_eq!(adjustable_delta(Clamp, 10, 0, -15), Ok(-10));
+    assert_eq!(adjustable_delta(Clamp, 10, 7, -5), Ok(-3), "clamps at the held units, not at zero");
+    assert_eq!(adjustable_delta(Clamp, 0, 0, -5), Ok(0));
+    assert_eq!(adjustable_delta(Clamp, 3, 5, -1), Ok(0), "nothing is free below the holds");
+}
+
+#[test]
+fn allow_goes_negative_only_where_nothing_is_held() {
+    assert_eq!(adjustable_delta(Allow, 10, 0, -15), Ok(-15));
+    assert_eq!(adjustable_delta(Allow, 0, 0, -5), Ok(-5));
+    assert_eq!(adjustable_delta(Allow, 10, 7, -3), Ok(-3));
+    assert_eq!(adjustable_delta(Allow, 10, 7, -4), Err(3));
+    assert_eq!(adjustable_delta(Allow, 10, 7, i32::MIN), Err(3));
+}
+
+fn policy_router(db: &sea_orm::DatabaseConnection, policy: NegativeStockPolicy) -> Router {
+    let mut config = Config::default();
+    config.stock.negative_policy = policy;
+    router_with(db, config)
+}
+
+// A location in a new warehouse that holds none of anything yet
+async fn empty_location(router: &Router) -> i64 {
+    let (_, warehouse) =
+        call(router, Method::POST, "/api/warehouse", Some(json!({ "code": unique("WH"), "name": "Empty" }))).await;
+    let (status, location) = call(
+        router,
+        Method::POST,
+        &format!("/api/warehouse/{}/locations", warehouse["id"]),
+        Some(json!({ "code": "BIN", "name": "Bin" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", location);
+    location["id"].as_i64().unwrap()
+}
+
+async fn adjust(router: &Router, item_id: i64, delta: i32, location_id: Option<i64>) -> (StatusCode, Value) {
+    call(
+        router,
+        Method::POST,
+        &format!("/api/item/{}/adjust", item_id),
+        Some(json!({ "delta": delta, "location_id": location_id })),
+    )
+    .await
+}
+
+async fn level_at(router: &Router, item_id: i64, location_id: i64) -> Option<i64> {
+    let (_, item) = call(router, Method::GET, &format!("/api/get_item/{}", item_id), None).await;
+    item["Locations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|l| l["location_id"] == location_id)
+        .map(|l| l["quantity"].as_i64().unwrap())
+}
+
+// Every unit sits at the default location, so the item total has plenty
+// while the empty location has none
+#[tokio::test]
+async fn policies_apply_to_the_target_location_not_the_item_total() {
+    let Some(db) = test_db().await else { return };
+
+    for (policy, expected) in [(Reject, None), (Clamp, Some(0)), (Allow, Some(-5))] {
+        let router = policy_router(&db, policy);
+        let (_, item_id) = create_item(&router, 10).await;
+        let empty = empty_location(&router).await;
+
+        let (status, body) = adjust(&router, item_id, -5, Some(empty)).await;
+        match expected {
+            None => {
+                assert_eq!(status, StatusCode::CONFLICT, "{:?}", policy);
+                assert_eq!(body["detail"], format!("item '{}' has 0 units available but 5 were requested", item_id));
+                assert_eq!(level_at(&router, item_id, empty).await, None);
+            }
+            Some(applied) => {
+                assert_eq!(status, StatusCode::OK, "{:?}: {}", policy, body);
+                assert_eq!(body["applied_delta"], applied, "{:?}", policy);
+                assert_eq!(body["quantity"], 10 + applied, "{:?}", policy);
+                let level = level_at(&router, item_id, empty).await;
+                assert_eq!(level.unwrap_or(0), applied, "{:?}", policy);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn held_units_are_never_adjusted_away() {
+    let Some(db) = test_db().await else { return };
+
+    for (policy, expected) in [(Reject, None), (Clamp, Some(-3)), (Allow, None)] {
+        let router = policy_router(&db, policy);
+        let (_, item_id) = create_item(&router, 10).await;
+        let (status, _) = call(
+            &router,
+            Method::POST,
+            &format!("/api/item/{}/reservations", item_id),
+            Some(json!({ "quantity": 7 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, body) = adjust(&router, item_id, -4, None).await;
+        match expected {
+            None => {
+                assert_eq!(status, StatusCode::CONFLICT, "{:?}", policy);
+                assert_eq!(body["detail"], format!("item '{}' has 3 units available but 4 were requested", item_id));
+            }
+            Some(applied) => {
+                assert_eq!(status, StatusCode::OK, "{:?}: {}", policy, body);
+                assert_eq!(body["applied_delta"], applied);
+                assert_eq!(body["quantity"], 7);
+            }
+        }
+    }
+}
+
+// The shortfall of the smallest delta would not fit the response
+#[tokio::test]
+async fn the_smallest_delta_is_rejected() {
+    let Some(db) = test_db().await else { return };
+    let router = policy_router(&db, Reject);
+    let (_, item_id) = create_item(&router, 10).await;
+
+    let movement = json!({ "kind": "issue", "delta": i32::MIN, "reason_code": "sale" });
+    let uri = format!("/api/item/{}/movements", item_id);
+    let (status, body) = call(&router, Method::POST, &uri, Some(movement)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert!(body["detail"].as_str().unwrap().contains("delta must be greater than"));
+
+    let (status, body) = adjust(&router, item_id, i32::MIN, None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+}
+
+// An adjustment clamped to nothing leaves the item as it was
+#[tokio::test]
+async fn a_clamped_noop_writes_nothing() {
+    let Some(db) = test_db().await else { return };
+    let router = policy_router(&db, Clamp);
+    let (_, item_id) = create_item(&router, 10).await;
+    let empty = empty_location(&router).await;
+
+    let (status, body) = adjust(&router, item_id, -5, Some(empty)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!((body["applied_delta"].as_i64(), body["quantity"].as_i64()), (Some(0), Some(10)));
+
+    let (_, ledger) = call(&router, Method::GET, &format!("/api/item/{}/movements", item_id), None).await;
+    assert_eq!(ledger["total"], 1, "only the opening receipt");
+    let uri = format!("/api/audit?entity_type=item&entity_key={}&action=adjust", item_id);
+    let (_, audit) = call(&router, Method::GET, &uri, None).await;
+    assert_eq!(audit["total"], 0);
+}
+
+// Concurrent adjustments each add to the stored total, none is lost
+#[tokio::test]
+async fn concurrent_adjustments_all_count() {
+    let Some(db) = test_db().await else { return };
+    let router = policy_router(&db, Reject);
+    let (_, item_id) = create_item(&router, 10).await;
+
+    let adjustments = (0..10).map(|_| adjust(&router, item_id, -1, None));
+    for (status, body) in futures_util::future::join_all(adjustments).await {
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+    let (_, item) = call(&router, Method::GET, &format!("/api/get_item/{}", item_id), None).await;
+    assert_eq!(item["Quantity"], 0);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::{Method, StatusCode};
+use chrono::{DateTime, FixedOffset};
+use common::db::{call, create_item, router, test_db};
+use serde_json::{json, Value};
+
+fn timestamp(value: &Value) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(value.as_str().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn updates_keep_created_at_and_advance_updated_at() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (uuid, item_id) = create_item(&router, 1).await;
+    let product_uri = format!("/api/v2/products/{}", uuid);
+    let item_uri = format!("/api/get_item/{}", item_id);
+
+    let (_, product) = call(&router, Method::GET, &product_uri, None).await;
+    let (_, item) = call(&router, Method::GET, &item_uri, None).await;
+    assert_eq!(product["Created_at"], product["Updated_at"]);
+
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let (status, _) = call(&router, Method::PATCH, &product_uri, Some(json!({ "Description": "changed" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) =
+        call(&router, Method::PUT, &format!("/api/item/{}", item_id), Some(json!({ "name": "renamed" }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, patched) = call(&router, Method::GET, &product_uri, None).await;
+    assert_eq!(timestamp(&patched["Created_at"]), timestamp(&product["Created_at"]));
+    assert!(timestamp(&patched["Updated_at"]) > timestamp(&product["Updated_at"]));
+
+    let (_, renamed) = call(&router, Method::GET, &item_uri, None).await;
+    assert_eq!(timestamp(&renamed["Created_at"]), timestamp(&item["Created_at"]));
+    assert!(timestamp(&renamed["Updated_at"]) > timestamp(&item["Updated_at"]));
+}
+
+// Every timestamp the API returns carries its offset, including those of the
+// ledger, reservations and the trash
+#[tokio::test]
+async fn timestamps_are_returned_with_an_offset() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (_, item_id) = create_item(&router, 5).await;
+
+    let (_, reservation) = call(
+        &router,
+        Method::POST,
+        &format!("/api/item/{}/reservations", item_id),
+        Some(json!({ "quantity": 1 })),
+    )
+    .await;
+    let (_, released) =
+        call(&router, Method::POST, &format!("/api/reservation/{}/release", reservation["id"]), None).await;
+    let (_, ledger) = call(&router, Method::GET, &format!("/api/item/{}/movements", item_id), None).await;
+    let (status, _) = call(&router, Method::DELETE, &format!("/api/delete_item/{}", item_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, trash) = call(&router, Method::GET, "/api/trash?resource=item&limit=1", None).await;
+
+    for value in [
+        &released["created_at"],
+        &released["expires_at"],
+        &released["resolved_at"],
+        &ledger["data"][0]["created_at"],
+        &trash["data"][0]["deleted_at"],
+    ] {
+        let text = value.as_str().unwrap_or_else(|| panic!("not a timestamp: {}", value));
+        assert!(DateTime::parse_from_rfc3339(text).is_ok(), "{} has no offset", text);
+    }
+    assert!(timestamp(&released["expires_at"]) > timestamp(&released["created_at"]));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::{Method, StatusCode};
+use common::db::{call, create_item, router, test_db, unique};
+use common::TestServer;
+use entity::item::Entity as ItemEntity;
+use entity::product::{self, Entity as ProductEntity};
+use product_service::config::Config;
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, Statement};
+use serde_json::json;
+
+async fn backdate(db: &DatabaseConnection, table: &str, key_column: &str, key: String, days: i32) {
+    let sql = format!(
+        "UPDATE {} SET deleted_at = now() - make_interval(days => $1) WHERE {} = $2",
+        table, key_column
+    );
+    let stmt = Statement::from_sql_and_values(db.get_database_backend(), sql, [days.into(), key.into()]);
+    db.execute(stmt).await.unwrap();
+}
+
+async fn product_id(db: &DatabaseConnection, uuid: &str) -> Option<i32> {
+    ProductEntity::find()
+        .filter(product::Column::Uuid.eq(uuid.parse::<uuid::Uuid>().unwrap()))
+        .one(db)
+        .await
+        .unwrap()
+        .map(|p| p.id)
+}
+
+#[tokio::test]
+async fn restoring_a_product_brings_back_only_the_items_deleted_with_it() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (uuid, deleted_alone) = create_item(&router, 1).await;
+    let (status, with_product) = call(
+        &router,
+        Method::POST,
+        &format!("/api/v2/products/{}/items", uuid),
+        Some(json!({ "Name": unique("item"), "Quantity": 2 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let with_product = with_product["id"].as_i64().unwrap();
+
+    let (status, _) = call(&router, Method::DELETE, &format!("/api/delete_item/{}", deleted_alone), None).await;
+    assert_eq!(status, StatusCode::OK);
+    // Make sure the two deletions cannot share a timestamp
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let (status, _) = call(&router, Method::DELETE, &format!("/api/delete_product/{}", uuid), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = call(&router, Method::POST, &format!("/api/item/{}/restore", with_product), None).await;
+    assert_eq!(status, StatusCode::CONFLICT, "the product has to come back first");
+
+    let (status, _) = call(&router, Method::POST, &format!("/api/product/{}/restore", uuid), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&router, Method::GET, &format!("/api/get_item/{}", with_product), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&router, Method::GET, &format!("/api/get_item/{}", deleted_alone), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "deleted on its own, so it stays in the trash");
+
+    let (status, _) = call(&router, Method::POST, &format!("/api/item/{}/restore", deleted_alone), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&router, Method::POST, &format!("/api/product/{}/restore", uuid), None).await;
+    assert_eq!(status, StatusCode::CONFLICT, "no longer in the trash");
+}
+
+// Runs the server, whose purger sweeps once at startup, and waits for the
+// expired product to disappear
+#[tokio::test]
+async fn purge_keeps_products_still_referenced_by_younger_trashed_items() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+
+    let (expired, expired_item) = create_item(&router, 1).await;
+    let (referenced, younger_item) = create_item(&router, 1).await;
+    for uuid in [&expired, &referenced] {
+        let (status, _) = call(&router, Method::DELETE, &format!("/api/delete_product/{}", uuid), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let retention_days = Config::default().trash.retention_days as i32;
+    for uuid in [&expired, &referenced] {
+        backdate(&db, "product", "uuid::text", uuid.clone(), retention_days + 10).await;
+    }
+    backdate(&db, "item", "id::text", expired_item.to_string(), retention_days + 10).await;
+    backdate(&db, "item", "id::text", younger_item.to_string(), 1).await;
+
+    let expired_id = product_id(&db, &expired).await.unwrap();
+    // `run` closes its pool when it stops, so it gets one of its own
+    let server = TestServer::start_with(Config::default(), test_db().await.unwrap()).await;
+    for _ in 0..50 {
+        if product_id(&db, &expired).await.is_none() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    server.stop().await;
+
+    assert_eq!(product_id(&db, &expired).await, None, "product {} was not purged", expired_id);
+    assert!(ItemEntity::find_by_id(expired_item as i32).one(&db).await.unwrap().is_none());
+    assert!(product_id(&db, &referenced).await.is_some(), "its item is still within retention");
+    assert!(ItemEntity::find_by_id(younger_item as i32).one(&db).await.unwrap().is_some());
+}
@@ -0,0 +1,41 @@
+use entity::attribute::AttributeType;
+use product_service::variants::{cartesian, normalize_value, typed_value, variant_key};
+use serde_json::json;
+
+#[test]
+fn values_are_normalized_for_their_type() {
+    assert_eq!(normalize_value(AttributeType::Text, &json!("  Navy ")).unwrap(), "Navy");
+    assert_eq!(normalize_value(AttributeType::Integer, &json!("042")).unwrap(), "42");
+    assert_eq!(normalize_value(AttributeType::Decimal, &json!("1.50")).unwrap(), "1.5");
+    assert_eq!(normalize_value(AttributeType::Decimal, &json!(1.5)).unwrap(), "1.5");
+    assert_eq!(normalize_value(AttributeType::Boolean, &json!(true)).unwrap(), "true");
+
+    assert!(normalize_value(AttributeType::Text, &json!(" ")).is_err());
+    assert!(normalize_value(AttributeType::Integer, &json!(1.5)).is_err());
+    assert!(normalize_value(AttributeType::Boolean, &json!("yes")).is_err());
+
+    assert_eq!(typed_value(AttributeType::Integer, "42"), json!(42));
+    assert_eq!(typed_value(AttributeType::Decimal, "1.5"), json!("1.5"));
+}
+
+#[test]
+fn variant_key_ignores_order() {
+    let a = variant_key(&[(2, "red".to_string()), (1, "M".to_string())]);
+    let b = variant_key(&[(1, "M".to_string()), (2, "red".to_string())]);
+    assert_eq!(a, b);
+    assert_ne!(a, variant_key(&[(1, "M".to_string()), (2, "blue".to_string())]));
+    assert_eq!(variant_key(&[]), None);
+}
+
+#[test]
+fn cartesian_keeps_axis_order_and_respects_the_limit() {
+    let axes = vec![vec!["S", "M"], vec!["red", "blue", "green"]];
+    let combinations = cartesian(&axes, 6).unwrap();
+    assert_eq!(combinations.len(), 6);
+    assert_eq!(combinations[0], ["S", "red"]);
+    assert_eq!(combinations[1], ["S", "blue"]);
+    assert_eq!(combinations[5], ["M", "green"]);
+
+    assert!(cartesian(&axes, 5).is_none());
+    assert!(cartesian(&vec![vec![0u8; 1 << 16]; 8], usize::MAX).is_none(), "overflow is over the limit");
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::db::{call, create_item, router, test_db, unique};
+use product_service::config::{ImportFormat, SkuSettings};
+use product_service::import::{parse, run_import, ImportOptions};
+use serde_json::{json, Value};
+
+// Quantity per location code, as `GET /api/get_item/:id` reports it
+fn stock_by_location(item: &Value) -> Vec<(String, i64)> {
+    let mut stock: Vec<(String, i64)> = item["Locations"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| (l["location_code"].as_str().unwrap().to_string(), l["quantity"].as_i64().unwrap()))
+        .collect();
+    stock.sort();
+    stock
+}
+
+// A new warehouse with one location; returns the location id and code
+async fn create_location(router: &axum::Router) -> (i64, String) {
+    let (status, warehouse) = call(
+        router,
+        Method::POST,
+        "/api/warehouse",
+        Some(json!({ "code": unique("WH"), "name": "Overflow" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", warehouse);
+
+    let code = unique("SHELF");
+    let (status, location) = call(
+        router,
+        Method::POST,
+        &format!("/api/warehouse/{}/locations", warehouse["id"]),
+        Some(json!({ "code": code, "name": "Shelf" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{}", location);
+    (location["id"].as_i64().unwrap(), code)
+}
+
+async fn transfer_from_default(router: &axum::Router, item_id: i64, to: i64, quantity: i32) -> (StatusCode, Value) {
+    let (_, item) = call(router, Method::GET, &format!("/api/get_item/{}", item_id), None).await;
+    let default = item["Locations"][0]["location_id"].as_i64().unwrap();
+    call(
+        router,
+        Method::POST,
+        &format!("/api/item/{}/transfers", item_id),
+        Some(json!({
+            "from_location_id": default,
+            "to_location_id": to,
+            "quantity": quantity,
+            "reason_code": "restock",
+        })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn locations_belong_to_their_warehouse() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+
+    let code = unique("WH");
+    let (status, warehouse) =
+        call(&router, Method::POST, "/api/warehouse", Some(json!({ "code": code, "name": "North" }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) =
+        call(&router, Method::POST, "/api/warehouse", Some(json!({ "code": code, "name": "Again" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT, "warehouse codes are unique");
+
+    let uri = format!("/api/warehouse/{}/locations", warehouse["id"]);
+    let (status, _) = call(&router, Method::POST, &uri, Some(json!({ "code": "A1", "name": "Aisle 1" }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _) = call(&router, Method::POST, &uri, Some(json!({ "code": "A1", "name": "Aisle 1" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT, "location codes are unique within a warehouse");
+
+    let (status, locations) = call(&router, Method::GET, &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let codes: Vec<&str> = locations.as_array().unwrap().iter().map(|l| l["code"].as_str().unwrap()).collect();
+    assert_eq!(codes, ["A1"]);
+
+    let (status, _) = call(&router, Method::GET, "/api/warehouse/0/locations", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn transfers_move_stock_between_locations_and_keep_the_total() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (_, item_id) = create_item(&router, 10).await;
+    let (shelf, shelf_code) = create_location(&router).await;
+
+    let (status, body) = transfer_from_default(&router, item_id, shelf, 4).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    let (_, item) = call(&router, Method::GET, &format!("/api/get_item/{}", item_id), None).await;
+    assert_eq!(item["Quantity"], 10);
+    let mut expected = vec![("DEFAULT".to_string(), 6), (shelf_code, 4)];
+    expected.sort();
+    assert_eq!(stock_by_location(&item), expected);
+
+    let (status, _) = transfer_from_default(&router, item_id, shelf, 7).await;
+    assert_eq!(status, StatusCode::CONFLICT, "the default location only holds 6");
+}
+
+#[tokio::test]
+async fn absolute_quantity_is_refused_once_stock_sits_outside_the_default_location() {
+    let Some(db) = test_db().await else { return };
+    let router = router(&db);
+    let (product_uuid, item_id) = create_item(&router, 10).await;
+    let item_uri = format!("/api/item/{}", item_id);
+
+    // With everything at the default location the difference is booked there
+    let (status, body) = call(&router, Method::PUT, &item_uri, Some(json!({ "quantity": 12 }))).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, item) = call(&router, Method::GET, &format!("/api/get_item/{}", item_id), None).await;
+    assert_eq!(stock_by_location(&item), [("DEFAULT".to_string(), 12)]);
+
+    let (shelf, shelf_code) = create_location(&router).await;
+    let (status, _) = transfer_from_default(&router, item_id, shelf, 5).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = call(&router, Method::PUT, &item_uri, Some(json!({ "quantity": 20 }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body["detail"].as_str().unwrap().contains("/adjust"), "{}", body);
+
+    // An import row for the item is refused the same way
+    let item_name = item["Name"].as_str().unwrap();
+    let csv = format!(
+        "product_uuid,product_name,product_description,item_name,item_quantity\n{},Stocked,test,{},20\n",
+        product_uuid, item_name
+    );
+    let options = ImportOptions { dry_run: false, chunk_size: 0, skus: SkuSettings::default() };
+    let report = run_import(&db, "import-test", parse(ImportFormat::Csv, csv.as_bytes()), options)
+        .await
+        .unwrap();
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].line, 2);
+    assert_eq!(report.chunks_committed, 0);
+
+    // Setting the quantity it already has is not a write
+    let (status, _) = call(&router, Method::PUT, &item_uri, Some(json!({ "quantity": 12 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, item) = call(&router, Method::GET, &format!("/api/get_item/{}", item_id), None).await;
+    assert_eq!(item["Quantity"], 12);
+    let mut expected = vec![("DEFAULT".to_string(), 7), (shelf_code, 5)];
+    expected.sort();
+    assert_eq!(stock_by_location(&item), expected);
+}